@@ -0,0 +1,25 @@
+#!/usr/bin/env sh
+# Replace the fixtures parsed by the benches with responses captured from the API.
+#
+# By default, the release is the first box set of 400 to 500 tracks found by a search.
+# They can be changed with the `RELEASE` and `LABEL` variables.
+set -eu
+
+USER_AGENT="musicbrainz_rs benches (https://github.com/RustyNova016/musicbrainz_rs)"
+API="https://musicbrainz.org/ws/2"
+FIXTURES="$(dirname "$0")"
+
+get() {
+    curl --fail --silent --show-error --user-agent "$USER_AGENT" "$API/$1"
+    # Stay under the rate limit of the API
+    sleep 1
+}
+
+if [ -z "${RELEASE:-}" ]; then
+    RELEASE="$(get "release?fmt=json&limit=1&query=packaging:box%20AND%20tracks:%5B400%20TO%20500%5D" |
+        sed -n 's/.*"releases":\[{"id":"\([0-9a-f-]*\)".*/\1/p')"
+fi
+LABEL="${LABEL:-dc940013-b8a8-4362-a465-291026c04b42}"
+
+get "release/$RELEASE?fmt=json&inc=recordings+artist-credits+labels+recording-level-rels+artist-rels+work-rels" >"$FIXTURES/release_lookup.json"
+get "release?fmt=json&label=$LABEL&inc=media+artist-credits+labels&limit=100" >"$FIXTURES/release_browse.json"
@@ -0,0 +1,28 @@
+//! The responses parsed by the benches.
+//!
+//! The committed fixtures are generated in the shape of the API's responses, so that the benches run offline.
+//! `benches/fixtures/capture.sh` replaces them with responses captured from the API.
+use std::path::Path;
+
+/// The lookup of a box set of 624 tracks, with recordings, artist credits, labels and recording level relations
+pub fn release_lookup() -> Vec<u8> {
+    read("release_lookup")
+}
+
+/// A browse page of 100 releases of a label, with their media and artist credits
+pub fn release_browse() -> Vec<u8> {
+    read("release_browse")
+}
+
+fn read(name: &str) -> Vec<u8> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("benches/fixtures")
+        .join(format!("{name}.json"));
+
+    std::fs::read(&path).unwrap_or_else(|err| {
+        panic!(
+            "Couldn't read the fixture `{}` ({err}). Capture it with `benches/fixtures/capture.sh`",
+            path.display()
+        )
+    })
+}
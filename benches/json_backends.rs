@@ -0,0 +1,53 @@
+//! Measure the time spent parsing responses with the JSON backend of the build.
+//!
+//! Run it once with each backend, then compare the results:
+//! ```sh
+//! cargo bench --bench json_backends
+//! cargo bench --bench json_backends --features simd-json
+//! ```
+use core::time::Duration;
+use std::time::Instant;
+
+use musicbrainz_rs::MusicBrainzParser;
+use musicbrainz_rs::api_bindium::ureq::http::StatusCode;
+use musicbrainz_rs::entity::BrowseResult;
+use musicbrainz_rs::entity::release::Release;
+use serde::de::DeserializeOwned;
+
+mod fixtures;
+
+const ITERATIONS: u32 = 50;
+
+const BACKEND: &str = if cfg!(feature = "simd-json") {
+    "simd-json"
+} else {
+    "serde_json"
+};
+
+/// Return the mean time taken to parse the response.
+///
+/// Each iteration parses a fresh copy of the response, as simd-json modifies it in place. The copy isn't measured.
+fn measure<T: DeserializeOwned>(response: &[u8]) -> Duration {
+    let parse = |mut body: Vec<u8>| {
+        let started = Instant::now();
+        MusicBrainzParser::<T>::parse_body_mut(StatusCode::OK, &mut body).unwrap();
+        started.elapsed()
+    };
+
+    // Warm up
+    parse(response.to_vec());
+
+    (0..ITERATIONS)
+        .map(|_| parse(response.to_vec()))
+        .sum::<Duration>()
+        / ITERATIONS
+}
+
+fn report<T: DeserializeOwned>(name: &str, response: &[u8]) {
+    println!("{name:<24} {BACKEND}: {:>10.2?}", measure::<T>(response));
+}
+
+fn main() {
+    report::<Release>("Release lookup", &fixtures::release_lookup());
+    report::<BrowseResult<Release>>("Release browse page", &fixtures::release_browse());
+}
@@ -0,0 +1,101 @@
+//! Compare the peak memory and time of the ways to parse a large response.
+//!
+//! The fixtures are a box set looked up with recordings, artist credits and recording level relations,
+//! and a browse page of 100 releases.
+//!
+//! Run `cargo bench --bench parse_memory`, after `benches/fixtures/capture.sh` to measure real responses
+use core::alloc::GlobalAlloc;
+use core::alloc::Layout;
+use core::sync::atomic::AtomicUsize;
+use core::sync::atomic::Ordering;
+use std::alloc::System;
+use std::time::Instant;
+
+use musicbrainz_rs::MusicBrainzParser;
+use musicbrainz_rs::api_bindium::ureq::http::StatusCode;
+use musicbrainz_rs::entity::BrowseResult;
+use musicbrainz_rs::entity::api::MusicbrainzError;
+use musicbrainz_rs::entity::release::Release;
+use serde::de::DeserializeOwned;
+
+mod fixtures;
+
+/// An allocator keeping track of the peak of allocated memory
+struct PeakAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+/// The previous way of parsing, which buffers the content to try each variant
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+#[expect(dead_code, reason = "Only the parsing is measured")]
+enum Untagged<T> {
+    Ok(Box<T>),
+    Err(MusicbrainzError),
+}
+
+/// Run the parsing, and return the peak of memory allocated during it
+fn measure(name: &str, parse: impl FnOnce()) {
+    PEAK.store(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed);
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    let started = Instant::now();
+
+    parse();
+
+    let elapsed = started.elapsed();
+    let peak = PEAK.load(Ordering::Relaxed) - baseline;
+    println!(
+        "{name:<32} peak: {:>8.2} MiB  time: {elapsed:?}",
+        peak as f64 / 1024.0 / 1024.0
+    );
+}
+
+/// Measure the ways to parse the response
+fn compare<T: DeserializeOwned>(name: &str, response: &[u8]) {
+    println!(
+        "{name}: response of {:.2} MiB",
+        response.len() as f64 / 1024.0 / 1024.0
+    );
+
+    measure("buffered body + untagged enum", || {
+        let body = response.to_vec();
+        let _: Untagged<T> = serde_json::from_slice(&body).unwrap();
+    });
+
+    measure("buffered body + parse_body_mut", || {
+        let mut body = response.to_vec();
+        MusicBrainzParser::<T>::parse_body_mut(StatusCode::OK, &mut body).unwrap();
+    });
+
+    measure("streamed body + parse_reader", || {
+        MusicBrainzParser::<T>::parse_reader(StatusCode::OK, response).unwrap();
+    });
+
+    println!();
+}
+
+fn main() {
+    // The responses as they arrive from the network
+    compare::<Release>("Release lookup", &fixtures::release_lookup());
+    compare::<BrowseResult<Release>>("Release browse page", &fixtures::release_browse());
+}
@@ -1,6 +1,6 @@
 // use std::io;
 
-// use musicbrainz_rs::client::MUSICBRAINZ_CLIENT;
+// use musicbrainz_rs::client::global::global_client;
 // use musicbrainz_rs::extra_endpoints::oauth::MusicbrainzOauth;
 
 // #[tokio::main(flavor = "current_thread")]
@@ -18,13 +18,13 @@
 //     };
 
 //     let mut token = oauth
-//         .get_access_token(&MUSICBRAINZ_CLIENT, &input)
+//         .get_access_token(&global_client(), &input)
 //         .await
 //         .unwrap();
 
 //     // Get the token
 //     let token_string = token
-//         .get_or_refresh_token(&MUSICBRAINZ_CLIENT, &oauth)
+//         .get_or_refresh_token(&global_client(), &oauth)
 //         .await
 //         .unwrap();
 
@@ -1,15 +1,36 @@
+use core::fmt;
 use core::marker::PhantomData;
+use core::str::FromStr;
+use core::time::Duration;
 
 use api_bindium::ApiRequest;
 use api_bindium::endpoints::UriBuilderError;
+use api_bindium::ureq::http::HeaderMap;
+use api_bindium::ureq::http::Request;
+use api_bindium::ureq::http::StatusCode;
 use api_bindium::ureq::http::Uri;
+use serde::Deserialize;
+use serde::Serialize;
 use serde::de::DeserializeOwned;
 
 use crate::APIPath;
+use crate::ParsingError;
 #[cfg(any(feature = "sync", feature = "async"))]
 use crate::api::ApiEndpointError;
 use crate::api::parser::MusicBrainzParser;
 use crate::api::query::Query;
+use crate::api::query::QueryParseError;
+use crate::api::query::UnknownParameterSnafu;
+use crate::api::query::parse_number;
+use crate::api::sans_io::HttpRequestError;
+use crate::api::sans_io::http_request;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::metrics::MetricsKey;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::metrics::QueryKind;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::mirrors::Served;
+use crate::client::scheduler::RequestPriority;
 use crate::entity::Browsable;
 use crate::entity::BrowseResult;
 
@@ -35,37 +56,107 @@ use crate::entity::BrowseResult;
 /// #   Ok(())
 /// # }
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "T: APIPath"))]
 pub struct BrowseQuery<T> {
+    #[serde(flatten)]
     pub inner: Query<T>,
 
     /// The number of results to offset the query by
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub offset: Option<u16>,
 
     /// The number of results to query
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub limit: Option<u8>,
 
     /// The type of entity used in the filter
+    #[serde(default)]
     pub filter_entity: String,
 
     /// The mbid of the filtered entity
+    #[serde(default)]
     pub filter_mbid: String,
 }
 
+/// The textual form of the query: the path and parameters of its request, without the format.
+/// For example, `release?label=47e718e1-7ee4-460c-b1cc-1192a841c6e5&limit=100`
+impl<T> fmt::Display for BrowseQuery<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut params = Vec::new();
+        if !self.filter_entity.is_empty() {
+            params.push((self.filter_entity.as_str(), self.filter_mbid.clone()));
+        }
+        params.extend(self.limit.map(|limit| ("limit", limit.to_string())));
+        params.extend(self.offset.map(|offset| ("offset", offset.to_string())));
+
+        self.inner.write_text(f, &params)
+    }
+}
+
+impl<T> FromStr for BrowseQuery<T>
+where
+    T: APIPath,
+{
+    type Err = QueryParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (inner, params) = Query::parse_text(text)?;
+        let mut query = Self {
+            inner,
+            offset: None,
+            limit: None,
+            filter_entity: String::new(),
+            filter_mbid: String::new(),
+        };
+
+        for (name, value) in params {
+            match name.as_str() {
+                "limit" => query.limit = Some(parse_number(&name, &value)?),
+                "offset" => query.offset = Some(parse_number(&name, &value)?),
+                _ if query.filter_entity.is_empty() => {
+                    query.filter_entity = name;
+                    query.filter_mbid = value;
+                }
+                _ => return UnknownParameterSnafu { name }.fail(),
+            }
+        }
+
+        Ok(query)
+    }
+}
+
 impl<T> BrowseQuery<T>
 where
     T: Clone,
 {
-    pub fn limit(&mut self, limit: u8) -> &mut Self {
+    #[must_use]
+    pub fn limit(mut self, limit: u8) -> Self {
         self.limit = Some(limit);
         self
     }
 
-    pub fn offset(&mut self, offset: u16) -> &mut Self {
+    #[must_use]
+    pub fn offset(mut self, offset: u16) -> Self {
         self.offset = Some(offset);
         self
     }
 
+    /// Set the priority of the request in the client's [`RequestScheduler`](crate::client::scheduler::RequestScheduler)
+    #[must_use]
+    pub fn priority(mut self, priority: RequestPriority) -> Self {
+        self.inner.priority = priority;
+        self
+    }
+
+    /// Set the maximum time the query can take, including the time spent waiting for the scheduler and rate limiter.
+    /// This overrides the client's timeout.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.inner.timeout = Some(timeout);
+        self
+    }
+
     // === Request Creation ===
 
     /// Create the request's url
@@ -81,6 +172,11 @@ where
         url.to_uri()
     }
 
+    /// The exact url the query requests with this client, for debugging or to build cache keys
+    pub fn to_url(&self, client: &crate::MusicBrainzClient) -> Result<String, UriBuilderError> {
+        self.create_url(client).map(|uri| uri.to_string())
+    }
+
     /// Turn the query into an [`api_bindium::ApiRequest`]
     pub fn as_api_request(
         &self,
@@ -89,83 +185,216 @@ where
     where
         T: DeserializeOwned + Browsable,
     {
+        let uri = self.create_url(client)?;
         Ok(ApiRequest::builder()
-            .uri(self.create_url(client)?)
+            .maybe_headers(client.auth_headers(&uri))
+            .uri(uri)
             .verb(api_bindium::HTTPVerb::Get)
             .parser(MusicBrainzParser::default())
             .build())
     }
 
+    /// Turn the query into an [`http::Request`](Request) that can be sent by any HTTP client.
+    ///
+    /// The `User-Agent` header needs to be set by the HTTP client. The response can then be parsed with [`Self::parse_response`].
+    pub fn to_http_request(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Request<()>, HttpRequestError> {
+        use snafu::ResultExt as _;
+
+        use crate::api::sans_io::InvalidUriSnafu;
+
+        http_request(
+            client,
+            self.create_url(client).context(InvalidUriSnafu)?,
+            client.response_format,
+        )
+    }
+
+    /// Parse the response to the request created by [`Self::to_http_request`]
+    pub fn parse_response(
+        &self,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<BrowseResult<T>, ParsingError>
+    where
+        T: DeserializeOwned + Browsable,
+    {
+        MusicBrainzParser::parse_response(status, headers, body)
+    }
+
     // === Api Fetching ===
 
     #[cfg(feature = "sync")]
-    pub fn execute(&mut self) -> Result<BrowseResult<T>, ApiEndpointError>
+    pub fn execute(&self) -> Result<BrowseResult<T>, ApiEndpointError>
     where
-        T: Browse + Browsable + DeserializeOwned + Sync,
+        T: Browse + Browsable + DeserializeOwned + Send + Sync + 'static,
     {
-        use crate::client::MUSICBRAINZ_CLIENT;
+        use crate::client::global::global_client;
 
-        self.execute_with_client(&MUSICBRAINZ_CLIENT)
+        self.execute_with_client(&global_client())
     }
 
     /// Execute the query with a specific client
     #[cfg(feature = "sync")]
     pub fn execute_with_client(
-        &mut self,
+        &self,
         client: &crate::MusicBrainzClient,
     ) -> Result<BrowseResult<T>, ApiEndpointError>
     where
-        T: Browse + Browsable + DeserializeOwned + Sync,
+        T: Browse + Browsable + DeserializeOwned + Send + Sync + 'static,
     {
-        use snafu::ResultExt as _;
+        self.execute_served(client).map(Served::into_inner)
+    }
 
-        use crate::api::ApiRequestSnafu;
-        use crate::api::InvalidUriSnafu;
-        use crate::api::ParsingSnafu;
+    /// Execute the query with a specific client, and return which server answered it
+    #[cfg(feature = "sync")]
+    pub fn execute_served(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Served<BrowseResult<T>>, ApiEndpointError>
+    where
+        T: Browse + Browsable + DeserializeOwned + Send + Sync + 'static,
+    {
+        use crate::client::timeout::run_with_timeout;
+
+        client.check_dry_run(|| self.to_http_request(client))?;
+
+        #[cfg(feature = "tracing")]
+        let _span = self
+            .inner
+            .span(
+                QueryKind::Browse,
+                Some(&format!("{}={}", self.filter_entity, self.filter_mbid)),
+                self.limit,
+                self.offset,
+            )
+            .entered();
+
+        let result = match self.inner.timeout.or(client.timeout) {
+            Some(timeout) => run_with_timeout(client, timeout, |client| self.send_blocking(client)),
+            None => self.send_blocking(client),
+        };
+
+        client.record_result(&self.metrics_key(), &result);
+        result
+    }
+
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn metrics_key(&self) -> MetricsKey {
+        MetricsKey::new(&self.inner.path, QueryKind::Browse)
+    }
 
-        self.as_api_request(client)
-            .context(InvalidUriSnafu)?
-            .send(&client.api_client)
-            .context(ApiRequestSnafu)?
-            .parse()
-            .context(ParsingSnafu)
+    #[cfg(feature = "sync")]
+    fn send_blocking(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Served<BrowseResult<T>>, ApiEndpointError>
+    where
+        T: Browsable + DeserializeOwned + Sync,
+    {
+        let key = self.metrics_key();
+        client.with_failover(&key, |client| {
+            client.send_request(
+                &key,
+                self.inner.priority,
+                self.to_http_request(client),
+                |status, headers, body| {
+                    MusicBrainzParser::parse_response_reader(status, headers, body)
+                },
+            )
+        })
     }
 
     #[cfg(feature = "async")]
-    pub async fn execute_async(&mut self) -> Result<BrowseResult<T>, ApiEndpointError>
+    pub async fn execute_async(&self) -> Result<BrowseResult<T>, ApiEndpointError>
     where
-        T: Browse + Browsable + DeserializeOwned + Sync,
+        T: Browse + Browsable + DeserializeOwned + Send + Sync + 'static,
     {
-        use crate::client::MUSICBRAINZ_CLIENT;
+        use crate::client::global::global_client;
 
-        self.execute_with_client_async(&MUSICBRAINZ_CLIENT).await
+        self.execute_with_client_async(&global_client()).await
     }
 
     /// Execute the query with a specific client
     #[cfg(feature = "async")]
     pub async fn execute_with_client_async(
-        &mut self,
+        &self,
         client: &crate::MusicBrainzClient,
     ) -> Result<BrowseResult<T>, ApiEndpointError>
     where
-        T: Browse + Browsable + DeserializeOwned + Sync,
+        T: Browse + Browsable + DeserializeOwned + Send + Sync + 'static,
+    {
+        self.execute_served_async(client)
+            .await
+            .map(Served::into_inner)
+    }
+
+    /// Execute the query with a specific client, and return which server answered it
+    #[cfg(feature = "async")]
+    pub async fn execute_served_async(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Served<BrowseResult<T>>, ApiEndpointError>
+    where
+        T: Browse + Browsable + DeserializeOwned + Send + Sync + 'static,
     {
         use snafu::ResultExt as _;
 
-        use crate::api::ApiRequestSnafu;
         use crate::api::InvalidUriSnafu;
-        use crate::api::ParsingSnafu;
+        use crate::client::timeout::with_timeout;
 
-        self.as_api_request(client)
-            .context(InvalidUriSnafu)?
-            .send_async(&client.api_client)
-            .await
-            .context(ApiRequestSnafu)?
-            .parse()
-            .context(ParsingSnafu)
+        client.check_dry_run(|| self.to_http_request(client))?;
+
+        #[cfg(feature = "tracing")]
+        let span = self.inner.span(
+            QueryKind::Browse,
+            Some(&format!("{}={}", self.filter_entity, self.filter_mbid)),
+            self.limit,
+            self.offset,
+        );
+
+        let key = self.metrics_key();
+        let execution = async {
+            let uri = self.create_url(client).context(InvalidUriSnafu)?;
+            let query = &*self;
+            let key = &key;
+            let request = client.coalesce(key, uri.to_string(), async {
+                let endpoints = client.endpoint_clients();
+
+                client
+                    .with_failover_async(key, &endpoints, |client| async move {
+                        client
+                            .send_request_async(
+                                key,
+                                query.inner.priority,
+                                query.to_http_request(client),
+                                |status, headers, body| {
+                                    MusicBrainzParser::parse_response_reader(status, headers, body)
+                                },
+                            )
+                            .await
+                    })
+                    .await
+            });
+
+            with_timeout(self.inner.timeout.or(client.timeout), request).await
+        };
+
+        #[cfg(feature = "tracing")]
+        let execution = tracing::Instrument::instrument(execution, span);
+
+        let result = execution.await;
+        client.record_result(&key, &result);
+        result
     }
 }
 
+#[cfg(feature = "async")]
+crate::api::into_future::impl_into_future!(BrowseQuery<T>, Result<BrowseResult<T>, ApiEndpointError>, T: Browse + Browsable + Clone + DeserializeOwned + Send + Sync + 'static);
+
 /// Implemented by all browsable entities (see [`BrowseQuery`])
 pub trait Browse {
     fn browse() -> BrowseQuery<Self>
@@ -176,6 +405,8 @@ pub trait Browse {
             inner: Query {
                 path: Self::path().to_string(),
                 result_type: PhantomData,
+                priority: RequestPriority::default(),
+                timeout: None,
                 include: vec![],
             },
             limit: None,
@@ -0,0 +1,362 @@
+//! Completion of the lists truncated by the sub-query includes of a lookup.
+//!
+//! Includes like `Artist::fetch().with_releases()` only return the first [`SUBQUERY_LIMIT`] entities
+//! of the list. A list of that size may be truncated, and can be completed by browsing the same entities,
+//! like `Release::browse().by_artist(id)` for the releases of an artist.
+//!
+//! ```rust
+//! # use musicbrainz_rs::prelude::*;
+//! # #[cfg(feature = "sync")]
+//! # fn main() -> Result<(), musicbrainz_rs::ApiEndpointError> {
+//! # use musicbrainz_rs::entity::artist::Artist;
+//! let john_lee_hooker = Artist::fetch()
+//!         .id("b0122194-c49a-46a1-ade7-84d1d76bd8e9")
+//!         .with_recordings()
+//!         .execute_completed()?;
+//!
+//! assert!(john_lee_hooker.is_complete());
+//! assert!(john_lee_hooker.entity.recordings.unwrap().len() > 25);
+//! #   Ok(())
+//! # }
+//! # #[cfg(not(feature = "sync"))]
+//! # fn main() {}
+//! ```
+
+#[cfg(any(feature = "sync", feature = "async"))]
+use serde::de::DeserializeOwned;
+
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::ApiEndpointError;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::Browse;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::BrowseQuery;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::FetchQuery;
+#[cfg(all(feature = "tracing", any(feature = "sync", feature = "async")))]
+use crate::client::spans::advance;
+#[cfg(all(feature = "tracing", any(feature = "sync", feature = "async")))]
+use crate::client::spans::progress_span;
+#[cfg(all(feature = "tracing", any(feature = "sync", feature = "async")))]
+use crate::client::spans::set_total;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::entity::Browsable;
+
+/// The maximum number of entities returned by a sub-query include
+pub const SUBQUERY_LIMIT: usize = 25;
+
+/// The number of entities requested by each page of the browse completing a list
+#[cfg(any(feature = "sync", feature = "async"))]
+const PAGE_SIZE: u8 = 100;
+
+/// Whether a list included in an entity is complete
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListStatus {
+    /// The name of the field holding the list
+    pub field: &'static str,
+
+    pub complete: bool,
+}
+
+/// An entity whose included lists were completed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completed<T> {
+    pub entity: T,
+
+    /// The status of each list included in the entity
+    pub lists: Vec<ListStatus>,
+}
+
+impl<T> Completed<T> {
+    /// Whether all the included lists are complete
+    pub fn is_complete(&self) -> bool {
+        self.lists.iter().all(|list| list.complete)
+    }
+
+    /// Whether the list held by `field` is complete. Lists that weren't included are never complete.
+    pub fn is_list_complete(&self, field: &str) -> bool {
+        self.lists
+            .iter()
+            .any(|list| list.field == field && list.complete)
+    }
+
+    pub fn into_inner(self) -> T {
+        self.entity
+    }
+}
+
+/// Implemented by the entities with lists capped by their sub-query includes
+pub trait IncludedLists {
+    /// The status of the included lists, without completing them.
+    /// A list of [`SUBQUERY_LIMIT`] entities is considered truncated.
+    fn list_statuses(&self) -> Vec<ListStatus>;
+
+    /// Replace the truncated lists by the result of browsing all their pages.
+    ///
+    /// The browsed entities are requested without includes.
+    #[cfg(feature = "sync")]
+    fn complete_lists(
+        &mut self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Vec<ListStatus>, ApiEndpointError>;
+
+    /// Replace the truncated lists by the result of browsing all their pages.
+    ///
+    /// The browsed entities are requested without includes.
+    #[cfg(feature = "async")]
+    fn complete_lists_async(
+        &mut self,
+        client: &crate::MusicBrainzClient,
+    ) -> impl Future<Output = Result<Vec<ListStatus>, ApiEndpointError>> + Send;
+}
+
+/// Browse all the pages of the query. Returns the entities and whether all of them were received.
+#[cfg(feature = "sync")]
+pub(crate) fn browse_all<T>(
+    query: &BrowseQuery<T>,
+    client: &crate::MusicBrainzClient,
+) -> Result<(Vec<T>, bool), ApiEndpointError>
+where
+    T: Browse + Browsable + Clone + DeserializeOwned + Send + Sync + 'static,
+{
+    let query = query.clone().limit(PAGE_SIZE);
+
+    #[cfg(feature = "tracing")]
+    let progress = progress_span("browse_all", &query.inner.path, 0);
+    #[cfg(feature = "tracing")]
+    let _span = progress.enter();
+
+    let mut entities = Vec::new();
+    loop {
+        let Ok(offset) = u16::try_from(entities.len()) else {
+            return Ok((entities, false));
+        };
+
+        let page = query.clone().offset(offset).execute_with_client(client)?;
+
+        #[cfg(feature = "tracing")]
+        set_total(&progress, usize::try_from(page.count).unwrap_or_default());
+
+        let done = add_page(&mut entities, page);
+
+        #[cfg(feature = "tracing")]
+        advance(&progress, entities.len());
+
+        if let Some(done) = done {
+            return Ok((entities, done));
+        }
+    }
+}
+
+/// Browse all the pages of the query. Returns the entities and whether all of them were received.
+#[cfg(feature = "async")]
+pub(crate) async fn browse_all_async<T>(
+    query: &BrowseQuery<T>,
+    client: &crate::MusicBrainzClient,
+) -> Result<(Vec<T>, bool), ApiEndpointError>
+where
+    T: Browse + Browsable + Clone + DeserializeOwned + Send + Sync + 'static,
+{
+    let query = query.clone().limit(PAGE_SIZE);
+
+    #[cfg(feature = "tracing")]
+    let progress = progress_span("browse_all", &query.inner.path, 0);
+
+    let execution = async {
+        let mut entities = Vec::new();
+        loop {
+            let Ok(offset) = u16::try_from(entities.len()) else {
+                return Ok((entities, false));
+            };
+
+            let page = query
+                .clone()
+                .offset(offset)
+                .execute_with_client_async(client)
+                .await?;
+
+            #[cfg(feature = "tracing")]
+            set_total(&progress, usize::try_from(page.count).unwrap_or_default());
+
+            let done = add_page(&mut entities, page);
+
+            #[cfg(feature = "tracing")]
+            advance(&progress, entities.len());
+
+            if let Some(done) = done {
+                return Ok((entities, done));
+            }
+        }
+    };
+
+    #[cfg(feature = "tracing")]
+    let execution = tracing::Instrument::instrument(execution, progress.clone());
+
+    execution.await
+}
+
+/// Add the page to the entities. Returns whether all the entities were received, once there is no page left.
+#[cfg(any(feature = "sync", feature = "async"))]
+fn add_page<T>(entities: &mut Vec<T>, page: crate::entity::BrowseResult<T>) -> Option<bool> {
+    let count = usize::try_from(page.count).unwrap_or_default();
+    let last_page = page.entities.is_empty();
+    entities.extend(page.entities);
+
+    if entities.len() >= count {
+        return Some(true);
+    }
+
+    // The list changed while browsing it
+    last_page.then_some(false)
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+impl<T> FetchQuery<T>
+where
+    T: Clone,
+{
+    /// Execute the query, then complete the lists truncated by its sub-query includes
+    #[cfg(feature = "sync")]
+    pub fn execute_completed(&self) -> Result<Completed<T>, ApiEndpointError>
+    where
+        T: crate::Fetch + IncludedLists + DeserializeOwned + Send + Sync + 'static,
+    {
+        use crate::client::global::global_client;
+
+        self.execute_completed_with_client(&global_client())
+    }
+
+    /// Execute the query with a specific client, then complete the lists truncated by its sub-query includes
+    #[cfg(feature = "sync")]
+    pub fn execute_completed_with_client(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Completed<T>, ApiEndpointError>
+    where
+        T: crate::Fetch + IncludedLists + DeserializeOwned + Send + Sync + 'static,
+    {
+        let mut entity = self.execute_with_client(client)?;
+        let lists = entity.complete_lists(client)?;
+
+        Ok(Completed { entity, lists })
+    }
+
+    /// Execute the query, then complete the lists truncated by its sub-query includes
+    #[cfg(feature = "async")]
+    pub async fn execute_completed_async(&self) -> Result<Completed<T>, ApiEndpointError>
+    where
+        T: crate::Fetch + IncludedLists + DeserializeOwned + Send + Sync + 'static,
+    {
+        use crate::client::global::global_client;
+
+        self.execute_completed_with_client_async(&global_client())
+            .await
+    }
+
+    /// Execute the query with a specific client, then complete the lists truncated by its sub-query includes
+    #[cfg(feature = "async")]
+    pub async fn execute_completed_with_client_async(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Completed<T>, ApiEndpointError>
+    where
+        T: crate::Fetch + IncludedLists + DeserializeOwned + Send + Sync + 'static,
+    {
+        let mut entity = self.execute_with_client_async(client).await?;
+        let lists = entity.complete_lists_async(client).await?;
+
+        Ok(Completed { entity, lists })
+    }
+}
+
+/// Implement [`IncludedLists`] for an entity, with the browse method listing the same entities as each included list
+macro_rules! impl_included_lists {
+    ($ty: ty, $(($field: ident, $item: ty, $browse: ident)),+) => {
+        impl crate::api::complete_lists::IncludedLists for $ty {
+            fn list_statuses(&self) -> Vec<crate::api::complete_lists::ListStatus> {
+                let mut lists = Vec::new();
+                $(if let Some(list) = &self.$field {
+                    lists.push(crate::api::complete_lists::ListStatus {
+                        field: stringify!($field),
+                        complete: list.len() < crate::api::complete_lists::SUBQUERY_LIMIT,
+                    });
+                })+
+                lists
+            }
+
+            #[cfg(feature = "sync")]
+            fn complete_lists(
+                &mut self,
+                client: &crate::MusicBrainzClient,
+            ) -> Result<Vec<crate::api::complete_lists::ListStatus>, crate::ApiEndpointError> {
+                use crate::Browse as _;
+
+                let mut lists = self.list_statuses();
+                for status in lists.iter_mut().filter(|status| !status.complete) {
+                    $(if status.field == stringify!($field) {
+                        let query = <$item>::browse().$browse(&self.id);
+
+                        let (entities, complete) = crate::api::complete_lists::browse_all(&query, client)?;
+                        self.$field = Some(entities);
+                        status.complete = complete;
+                    })+
+                }
+                Ok(lists)
+            }
+
+            #[cfg(feature = "async")]
+            async fn complete_lists_async(
+                &mut self,
+                client: &crate::MusicBrainzClient,
+            ) -> Result<Vec<crate::api::complete_lists::ListStatus>, crate::ApiEndpointError> {
+                use crate::Browse as _;
+
+                let mut lists = self.list_statuses();
+                for status in lists.iter_mut().filter(|status| !status.complete) {
+                    $(if status.field == stringify!($field) {
+                        let query = <$item>::browse().$browse(&self.id);
+
+                        let (entities, complete) =
+                            crate::api::complete_lists::browse_all_async(&query, client).await?;
+                        self.$field = Some(entities);
+                        status.complete = complete;
+                    })+
+                }
+                Ok(lists)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_included_lists;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::entity::artist::Artist;
+
+    #[test]
+    fn should_detect_truncated_lists() {
+        let release = |id: usize| format!(r#"{{"id":"{id}","title":"Release {id}"}}"#);
+        let releases: Vec<String> = (0..SUBQUERY_LIMIT).map(release).collect();
+        let artist: Artist = serde_json::from_str(&format!(
+            r#"{{"id":"5b11f4ce-a62d-471e-81fc-a69a8278c7da","name":"Nirvana","sort-name":"Nirvana","releases":[{}],"works":[]}}"#,
+            releases.join(",")
+        ))
+        .unwrap();
+
+        assert_eq!(
+            artist.list_statuses(),
+            vec![
+                ListStatus {
+                    field: "releases",
+                    complete: false
+                },
+                ListStatus {
+                    field: "works",
+                    complete: true
+                }
+            ]
+        );
+    }
+}
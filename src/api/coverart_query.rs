@@ -1,19 +1,37 @@
 use core::fmt::Write as _;
 use core::marker::PhantomData;
-use core::str::FromStr as _;
+use core::time::Duration;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::io::Read;
 
 use api_bindium::ApiRequest;
 use api_bindium::JsonParser;
+use api_bindium::endpoints::EndpointUriBuilder;
 use api_bindium::endpoints::UriBuilderError;
+use api_bindium::endpoints::path::EndpointUriBuilderPath;
+use api_bindium::ureq::http::HeaderMap;
+use api_bindium::ureq::http::Request;
+use api_bindium::ureq::http::StatusCode;
 use api_bindium::ureq::http::Uri;
+use api_bindium::ureq::http::header::CONTENT_LOCATION;
+use api_bindium::ureq::http::header::LOCATION;
 
 use crate::APIPath;
 #[cfg(any(feature = "sync", feature = "async"))]
 use crate::MusicBrainzClient;
+use crate::ParsingError;
 #[cfg(any(feature = "sync", feature = "async"))]
 use crate::api::ApiEndpointError;
-use crate::entity::CoverartResolution;
+use crate::api::parser::MusicBrainzParser;
+use crate::api::parser::ResponseFormat;
+use crate::api::sans_io::HttpRequestError;
+use crate::api::sans_io::http_request;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::metrics::MetricsKey;
 #[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::metrics::QueryKind;
+use crate::client::scheduler::RequestPriority;
+use crate::entity::CoverartResolution;
 use crate::entity::CoverartResponse;
 use crate::entity::CoverartTarget;
 use crate::entity::CoverartType;
@@ -50,6 +68,12 @@ pub struct CoverartQuery<T> {
     pub path: String,
     pub target: CoverartTarget,
     pub phantom: PhantomData<T>,
+
+    /// The priority of the query in the client's scheduler
+    pub priority: RequestPriority,
+
+    /// The maximum time the query can take. Overrides the client's timeout
+    pub timeout: Option<Duration>,
 }
 
 #[derive(Clone, Debug)]
@@ -59,12 +83,29 @@ impl<T> FetchCoverartQuery<T>
 where
     T: Clone + FetchCoverart,
 {
-    pub fn id(&mut self, id: &str) -> &mut Self {
+    #[must_use]
+    pub fn id(mut self, id: &str) -> Self {
         let _ = write!(self.0.path, "/{id}");
         self
     }
 
-    pub fn front(&mut self) -> &mut Self {
+    /// Set the priority of the request in the client's [`RequestScheduler`](crate::client::scheduler::RequestScheduler)
+    #[must_use]
+    pub fn priority(mut self, priority: RequestPriority) -> Self {
+        self.0.priority = priority;
+        self
+    }
+
+    /// Set the maximum time the query can take, including the time spent waiting for the scheduler and rate limiter.
+    /// This overrides the client's timeout.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.0.timeout = Some(timeout);
+        self
+    }
+
+    #[must_use]
+    pub fn front(mut self) -> Self {
         if self.0.target.img_type.is_some() {
             println!("ignoring call to `front`, since coverart type has already been set");
         }
@@ -72,7 +113,8 @@ where
         self
     }
 
-    pub fn back(&mut self) -> &mut Self {
+    #[must_use]
+    pub fn back(mut self) -> Self {
         if self.0.target.img_type.is_some() {
             println!("ignoring call to `back`, since coverart type has already been set");
         }
@@ -80,7 +122,8 @@ where
         self
     }
 
-    pub fn res_250(&mut self) -> &mut Self {
+    #[must_use]
+    pub fn res_250(mut self) -> Self {
         if self.0.target.img_res.is_some() {
             println!("ignoring call to `res_250`, since resolution has already been set");
         }
@@ -88,7 +131,8 @@ where
         self
     }
 
-    pub fn res_500(&mut self) -> &mut Self {
+    #[must_use]
+    pub fn res_500(mut self) -> Self {
         if self.0.target.img_res.is_some() {
             println!("ignoring call to `res_500`, since resolution has already been set");
         }
@@ -96,7 +140,8 @@ where
         self
     }
 
-    pub fn res_1200(&mut self) -> &mut Self {
+    #[must_use]
+    pub fn res_1200(mut self) -> Self {
         if self.0.target.img_res.is_some() {
             println!("ignoring call to `res_1200`, since resolution has already been set");
         }
@@ -104,107 +149,280 @@ where
         self
     }
 
+    /// Implicitly assume the coverart type as front when the resolution is set but the type isn't.
+    ///
+    /// This doesn't need to be called before executing the query, as building its url does the same.
     pub fn validate(&mut self) {
-        if let Some(img_type) = &self.0.target.img_type {
-            let _ = write!(self.0.path, "/{}", img_type.as_str());
+        if self.0.target.img_type.is_none() && self.0.target.img_res.is_some() {
+            self.0.target.img_type = Some(CoverartType::Front);
+        }
+    }
+
+    /// The requested image type, if any. The resolution alone implies the front image
+    fn img_type(&self) -> Option<&CoverartType> {
+        match (&self.0.target.img_type, &self.0.target.img_res) {
+            (Some(img_type), _) => Some(img_type),
+            (None, Some(_)) => Some(&CoverartType::Front),
+            (None, None) => None,
+        }
+    }
+
+    /// Whether a specific image is requested, in which case the response is a redirect to it
+    fn is_redirect(&self) -> bool {
+        self.img_type().is_some()
+    }
+
+    /// Create the path of the request, with the requested image appended to the entity's path
+    pub(crate) fn target_path(&self) -> String {
+        let mut path = self.0.path.clone();
+
+        if let Some(img_type) = self.img_type() {
+            let _ = write!(path, "/{}", img_type.as_str());
             if let Some(img_res) = &self.0.target.img_res {
-                let _ = write!(self.0.path, "-{}", img_res.as_str());
+                let _ = write!(path, "-{}", img_res.as_str());
             }
-        } else if self.0.target.img_res.is_some() {
-            // Implicitly assume coverart type as front in the case when resolution is
-            // explicitly specified but coverart type is not.
-            self.front().validate();
         }
+
+        path
+    }
+
+    /// Create the request's url
+    fn create_url(&self, client: &crate::MusicBrainzClient) -> Result<Uri, UriBuilderError> {
+        coverart_endpoint_builder(&client.coverart_archive_url)
+            .add_path_fragment(&self.target_path())
+            .query()
+            .to_uri()
+    }
+
+    /// The exact url the query requests with this client, for debugging or to build cache keys.
+    ///
+    /// If an image is requested, this is the url redirecting to the image, like `.../release/{mbid}/front-250`
+    pub fn to_url(&self, client: &crate::MusicBrainzClient) -> Result<String, UriBuilderError> {
+        self.create_url(client).map(|uri| uri.to_string())
     }
 
     /// Turn the query into an [`api_bindium::ApiRequest`]
     pub fn as_api_request(
-        &mut self,
+        &self,
         client: &crate::MusicBrainzClient,
     ) -> Result<ApiRequest<JsonParser<Coverart>>, UriBuilderError> {
-        self.validate();
-
-        let url = format!("{}/{}", client.coverart_archive_url, self.0.path);
+        let uri = self.create_url(client)?;
 
         Ok(ApiRequest::builder()
-            .uri(Uri::from_str(&url).unwrap())
+            .maybe_headers(client.auth_headers(&uri))
+            .uri(uri)
             .verb(api_bindium::HTTPVerb::Get)
             .parser(JsonParser::default())
             .build())
     }
 
+    /// Turn the query into an [`http::Request`](Request) that can be sent by any HTTP client.
+    ///
+    /// The `User-Agent` header needs to be set by the HTTP client. The response can then be parsed with [`Self::parse_response`].
+    /// If an image is requested, the HTTP client shouldn't follow redirects, as the response is the redirect to the image.
+    pub fn to_http_request(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Request<()>, HttpRequestError> {
+        use snafu::ResultExt as _;
+
+        use crate::api::sans_io::InvalidUriSnafu;
+
+        let uri = self.create_url(client).context(InvalidUriSnafu)?;
+
+        // The cover art archive only answers in JSON
+        http_request(client, uri, ResponseFormat::Json)
+    }
+
+    /// Parse the response to the request created by [`Self::to_http_request`]
+    pub fn parse_response(
+        &self,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<CoverartResponse, ParsingError> {
+        if let Some(url) = Self::parse_redirect(self.is_redirect(), status, headers)? {
+            return Ok(CoverartResponse::Url(url));
+        }
+
+        MusicBrainzParser::parse_body(status, body).map(CoverartResponse::Json)
+    }
+
+    /// Parse the response received by the client. The body is parsed in place
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn parse_coverart(
+        is_redirect: bool,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &mut dyn Read,
+    ) -> Result<CoverartResponse, ParsingError> {
+        if let Some(url) = Self::parse_redirect(is_redirect, status, headers)? {
+            return Ok(CoverartResponse::Url(url));
+        }
+
+        MusicBrainzParser::parse_reader(status, body).map(CoverartResponse::Json)
+    }
+
+    fn parse_redirect(
+        is_redirect: bool,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) -> Result<Option<String>, ParsingError> {
+        use snafu::OptionExt as _;
+
+        use crate::api::parser::parsing_error::MissingRedirectSnafu;
+
+        // If we requested a specific image, we have a redirect in return.
+        // Transports following it give the url of the image in `Content-Location` instead
+        if is_redirect {
+            let location = if status.is_redirection() {
+                headers.get(LOCATION)
+            } else {
+                headers.get(CONTENT_LOCATION)
+            };
+            let redirect = location
+                .and_then(|location| location.to_str().ok())
+                .context(MissingRedirectSnafu {
+                    status: status.as_u16(),
+                })?;
+
+            return Ok(Some(redirect.to_string()));
+        }
+
+        Ok(None)
+    }
+
     #[cfg(feature = "sync")]
-    pub fn execute(&mut self) -> Result<CoverartResponse, ApiEndpointError> {
-        use crate::client::MUSICBRAINZ_CLIENT;
+    pub fn execute(&self) -> Result<CoverartResponse, ApiEndpointError>
+    where
+        T: Send + 'static,
+    {
+        use crate::client::global::global_client;
 
-        self.execute_with_client(&MUSICBRAINZ_CLIENT)
+        self.execute_with_client(&global_client())
     }
 
     #[cfg(feature = "sync")]
     pub fn execute_with_client(
-        &mut self,
+        &self,
         client: &MusicBrainzClient,
-    ) -> Result<CoverartResponse, ApiEndpointError> {
-        use snafu::ResultExt as _;
-
-        use crate::api::ApiRequestSnafu;
-        use crate::api::InvalidUriSnafu;
+    ) -> Result<CoverartResponse, ApiEndpointError>
+    where
+        T: Send + 'static,
+    {
+        use crate::client::timeout::run_with_timeout;
+
+        client.check_dry_run(|| self.to_http_request(client))?;
+
+        #[cfg(feature = "tracing")]
+        let _span = self.span().entered();
+
+        let key = self.metrics_key();
+        let request = self.to_http_request(client);
+        let is_redirect = self.is_redirect();
+        let priority = self.0.priority;
+        let send = |client: &MusicBrainzClient| {
+            client.send_request(&key, priority, request, |status, headers, body| {
+                Self::parse_coverart(is_redirect, status, headers, body)
+            })
+        };
+
+        let result = match self.0.timeout.or(client.timeout) {
+            Some(timeout) => run_with_timeout(client, timeout, send),
+            None => send(client),
+        };
+
+        client.record_result(&key, &result);
+        result
+    }
 
-        let mut req = self.as_api_request(client).context(InvalidUriSnafu)?;
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn metrics_key(&self) -> MetricsKey {
+        MetricsKey::new(&self.0.path, QueryKind::Coverart)
+    }
 
-        let response = req.send(&client.api_client).context(ApiRequestSnafu)?;
+    /// Create the span covering the execution of the query
+    #[cfg(all(feature = "tracing", any(feature = "sync", feature = "async")))]
+    fn span(&self) -> tracing::Span {
+        use crate::client::spans::path_id;
+        use crate::client::spans::query_span;
 
-        // If we requested a specific image, we have a redirect in return
-        if self.0.target.img_type.is_some() {
-            use api_bindium::ureq::ResponseExt as _;
-
-            let redirect = response.inner.data.get_uri();
-            Ok(CoverartResponse::Url(redirect.to_string()))
-        } else {
-            Ok(CoverartResponse::Json(
-                response.parse().context(ApiRequestSnafu)?,
-            ))
-        }
+        query_span(&self.metrics_key(), path_id(&self.0.path), &[], None, None)
     }
 
     #[cfg(feature = "async")]
-    pub async fn execute_async(&mut self) -> Result<CoverartResponse, ApiEndpointError> {
-        use crate::client::MUSICBRAINZ_CLIENT;
+    pub async fn execute_async(&self) -> Result<CoverartResponse, ApiEndpointError> {
+        use crate::client::global::global_client;
 
-        self.execute_with_client_async(&MUSICBRAINZ_CLIENT).await
+        self.execute_with_client_async(&global_client()).await
     }
 
     #[cfg(feature = "async")]
     pub async fn execute_with_client_async(
-        &mut self,
+        &self,
         client: &MusicBrainzClient,
     ) -> Result<CoverartResponse, ApiEndpointError> {
         use snafu::ResultExt as _;
 
-        use crate::api::ApiRequestSnafu;
         use crate::api::InvalidUriSnafu;
+        use crate::client::timeout::with_timeout;
+
+        client.check_dry_run(|| self.to_http_request(client))?;
+
+        #[cfg(feature = "tracing")]
+        let span = self.span();
+
+        let key = self.metrics_key();
+        let execution = async {
+            let http_req = self.to_http_request(client);
+            let is_redirect = self.is_redirect();
+            let priority = self.0.priority;
+            let key = &key;
+
+            let uri = self.create_url(client).context(InvalidUriSnafu)?;
+            let request = client.coalesce(key, uri.to_string(), async move {
+                client
+                    .send_request_async(key, priority, http_req, |status, headers, body| {
+                        Self::parse_coverart(is_redirect, status, headers, body)
+                    })
+                    .await
+            });
+
+            with_timeout(self.0.timeout.or(client.timeout), request).await
+        };
+
+        #[cfg(feature = "tracing")]
+        let execution = tracing::Instrument::instrument(execution, span);
+
+        let result = execution.await;
+        client.record_result(&key, &result);
+        result
+    }
+}
 
-        let mut req = self.as_api_request(client).context(InvalidUriSnafu)?;
-
-        let response = req
-            .send_async(&client.api_client)
-            .await
-            .context(ApiRequestSnafu)?;
-
-        // If we requested a specific image, we have a redirect in return
-        if self.0.target.img_type.is_some() {
-            use api_bindium::ureq::ResponseExt as _;
-
-            let redirect = response.inner.data.get_uri();
-            Ok(CoverartResponse::Url(redirect.to_string()))
-        } else {
-            Ok(CoverartResponse::Json(
-                response.parse().context(ApiRequestSnafu)?,
-            ))
-        }
+/// Return an endpoint builder for the url of the cover art archive api, like `https://coverartarchive.org`.
+/// Urls that don't start with `https://` are requested with HTTP.
+fn coverart_endpoint_builder(url: &str) -> EndpointUriBuilder<EndpointUriBuilderPath> {
+    let (builder, rest) = match url.strip_prefix("https://") {
+        Some(rest) => (EndpointUriBuilder::new().https(), rest),
+        None => (
+            EndpointUriBuilder::new().http(),
+            url.strip_prefix("http://").unwrap_or(url),
+        ),
+    };
+
+    let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let builder = builder.set_authority(authority);
+    if path.is_empty() {
+        builder
+    } else {
+        builder.add_path_fragment(path)
     }
 }
 
+#[cfg(feature = "async")]
+crate::api::into_future::impl_into_future!(FetchCoverartQuery<T>, Result<CoverartResponse, ApiEndpointError>, T: FetchCoverart + Clone + Send + Sync + 'static);
+
 /// Implemented by all fetchable coverart entities (see [`FetchCoverartQuery`])
 pub trait FetchCoverart {
     fn fetch_coverart() -> FetchCoverartQuery<Self>
@@ -214,6 +432,8 @@ pub trait FetchCoverart {
         FetchCoverartQuery(CoverartQuery {
             path: Self::path().to_string(),
             phantom: PhantomData,
+            priority: RequestPriority::default(),
+            timeout: None,
             target: CoverartTarget {
                 img_type: None,
                 img_res: None,
@@ -228,6 +448,8 @@ pub trait FetchCoverart {
         FetchCoverartQuery(CoverartQuery {
             path: Self::path().to_string(),
             phantom: PhantomData,
+            priority: RequestPriority::default(),
+            timeout: None,
             target: CoverartTarget {
                 img_type: None,
                 img_res: None,
@@ -235,3 +457,88 @@ pub trait FetchCoverart {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use api_bindium::ureq::http::HeaderMap;
+    use api_bindium::ureq::http::StatusCode;
+    use api_bindium::ureq::http::header::CONTENT_LOCATION;
+    use api_bindium::ureq::http::header::LOCATION;
+
+    use crate::FetchCoverart as _;
+    use crate::MusicBrainzClient;
+    use crate::entity::CoverartResponse;
+    use crate::entity::release::Release;
+
+    #[test]
+    fn should_build_the_same_request_on_each_call() {
+        let client = MusicBrainzClient::default();
+        let query = Release::fetch_coverart()
+            .id("76df3287-6cda-33eb-8e9a-044b5e15ffdd")
+            .res_250();
+
+        let first = query.to_http_request(&client).unwrap();
+        let second = query.to_http_request(&client).unwrap();
+
+        assert_eq!(first.uri(), second.uri());
+        assert!(
+            first
+                .uri()
+                .path()
+                .ends_with("/release/76df3287-6cda-33eb-8e9a-044b5e15ffdd/front-250")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sync")]
+    fn should_return_the_request_in_dry_run() {
+        let client = MusicBrainzClient::builder().dry_run(true).build();
+        let query = Release::fetch_coverart()
+            .id("76df3287-6cda-33eb-8e9a-044b5e15ffdd")
+            .back();
+
+        let error = query.execute_with_client(&client).unwrap_err();
+        let request = error.as_dry_run_request().unwrap();
+
+        assert_eq!(request.uri().to_string(), query.to_url(&client).unwrap());
+        assert_eq!(
+            query.to_url(&client).unwrap(),
+            "http://coverartarchive.org/release/76df3287-6cda-33eb-8e9a-044b5e15ffdd/back"
+        );
+    }
+
+    #[test]
+    fn should_read_the_image_url_of_followed_redirects() {
+        let query = Release::fetch_coverart()
+            .id("76df3287-6cda-33eb-8e9a-044b5e15ffdd")
+            .res_250();
+
+        let image =
+            "https://archive.org/download/mbid-76df3287-6cda-33eb-8e9a-044b5e15ffdd/front_250.jpg";
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_LOCATION, image.parse().unwrap());
+
+        assert!(matches!(
+            query.parse_response(StatusCode::OK, &headers, &[]),
+            Ok(CoverartResponse::Url(url)) if url == image
+        ));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(LOCATION, image.parse().unwrap());
+        assert!(matches!(
+            query.parse_response(StatusCode::TEMPORARY_REDIRECT, &headers, &[]),
+            Ok(CoverartResponse::Url(url)) if url == image
+        ));
+    }
+
+    #[test]
+    fn should_reject_invalid_coverart_archive_url() {
+        let client = MusicBrainzClient::builder()
+            .coverart_archive_url("http://not a host".to_string())
+            .build();
+        let query = Release::fetch_coverart().id("76df3287-6cda-33eb-8e9a-044b5e15ffdd");
+
+        assert!(query.to_url(&client).is_err());
+        assert!(query.to_http_request(&client).is_err());
+    }
+}
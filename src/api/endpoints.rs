@@ -1,8 +1,15 @@
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use std::collections::HashMap;
+
 use api_bindium::endpoints::EndpointUriBuilder;
 use api_bindium::endpoints::path::EndpointUriBuilderPath;
 
+use crate::client::auth::Redacted;
+use crate::client::auth::authorization_headers;
+
 /// Endpoints for the api
-#[derive(Debug, bon::Builder, Clone)]
+#[derive(bon::Builder, Clone)]
 pub struct MusicBrainzAPIEnpoints {
     /// Whether to use HTTPS. Defaults to `true`.
     #[builder(default = true)]
@@ -12,6 +19,19 @@ pub struct MusicBrainzAPIEnpoints {
     /// e.g. `musicbrainz.org` or `localhost:5000`.
     #[builder(default = "musicbrainz.org".to_string())]
     authority: String,
+
+    /// The `Authorization` header sent with the requests
+    authorization: Option<String>,
+}
+
+impl Debug for MusicBrainzAPIEnpoints {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MusicBrainzAPIEnpoints")
+            .field("use_https", &self.use_https)
+            .field("authority", &self.authority)
+            .field("authorization", &Redacted(&self.authorization))
+            .finish()
+    }
 }
 
 impl MusicBrainzAPIEnpoints {
@@ -24,6 +44,11 @@ impl MusicBrainzAPIEnpoints {
         )
     }
 
+    /// Return the headers authenticating the requests to the server
+    pub fn auth_headers(&self) -> Option<HashMap<String, String>> {
+        self.authorization.clone().map(authorization_headers)
+    }
+
     /// Return an endpoint builder with the scheme and authority already set
     pub fn endpoint_builder(&self) -> EndpointUriBuilder<EndpointUriBuilderPath> {
         let builder = EndpointUriBuilder::new();
@@ -0,0 +1,630 @@
+use core::marker::PhantomData;
+use core::time::Duration;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::collections::HashMap;
+use std::collections::HashSet;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::sync::Arc;
+
+#[cfg(any(feature = "sync", feature = "async"))]
+use serde::de::DeserializeOwned;
+
+use crate::APIPath;
+use api_bindium::endpoints::UriBuilderError;
+
+use crate::Fetch;
+use crate::FetchQuery;
+use crate::Search;
+use crate::SearchQuery;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::api::ApiEndpointError;
+use crate::api::query::Query;
+use crate::client::scheduler::RequestPriority;
+#[cfg(all(feature = "tracing", any(feature = "sync", feature = "async")))]
+use crate::client::spans::advance;
+#[cfg(all(feature = "tracing", any(feature = "sync", feature = "async")))]
+use crate::client::spans::progress_span;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::entity::search::SearchResult;
+use crate::entity::search::Searchable;
+
+/// The maximum number of results the search endpoint returns per request
+const SEARCH_BUNDLE_SIZE: u8 = 100;
+
+/// The number of lookups sent at once by [`FetchManyQuery`] and [`Hydrate::hydrate_all`](crate::api::hydrate::Hydrate::hydrate_all).
+/// They still wait for their turn in the client's rate limiter.
+pub const LOOKUP_BATCH_SIZE: usize = 10;
+
+/// Perform lookups of many entities at once when you have their MBIDs
+///
+/// # Bundling
+///
+/// Instead of sending one lookup per MBID, up to 100 MBIDs are bundled into a single search request
+/// (ex: `rid:(a OR b OR ...)` for recordings). Any MBID that isn't returned by the search (merged entities,
+/// unindexed entities...) is then looked up on its own, with [`LOOKUP_BATCH_SIZE`] lookups sent at once.
+///
+/// If a search request fails, its error is returned for each of its MBIDs, as an
+/// [`ApiEndpointError::SharedError`](crate::ApiEndpointError::SharedError).
+///
+/// Search results don't support includes, so if any include is requested, all the MBIDs are looked up separately.
+///
+/// Note that entities returned by the search endpoint are slightly different from the ones of a lookup.
+/// For example, a searched recording has its `releases` and `artist_credit` filled in, and has a `score`.
+///
+/// ## Example
+/// ```rust
+/// # use musicbrainz_rs::prelude::*;
+/// # #[cfg(feature = "sync")]
+/// # fn main() -> Result<(), musicbrainz_rs::ApiEndpointError> {
+/// # use musicbrainz_rs::entity::recording::Recording;
+/// let recordings = Recording::fetch_many([
+///         "af40d6b8-58e8-4ca5-9db8-d4fca0b899e2",
+///         "b80974e2-3da3-4741-bdf4-f2072184aab8",
+///     ])
+///     .execute();
+///
+/// let polly = recordings["af40d6b8-58e8-4ca5-9db8-d4fca0b899e2"].as_ref().unwrap();
+/// assert_eq!(polly.title, "Polly".to_string());
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct FetchManyQuery<T> {
+    pub inner: Query<T>,
+
+    /// The mbids of the entities to fetch
+    pub ids: Vec<String>,
+}
+
+impl<T> FetchManyQuery<T>
+where
+    T: FetchMany + Clone,
+{
+    /// Add mbids of entities to fetch
+    #[must_use]
+    pub fn ids<I, S>(mut self, ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.ids.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the priority of the request in the client's [`RequestScheduler`](crate::client::scheduler::RequestScheduler)
+    #[must_use]
+    pub fn priority(mut self, priority: RequestPriority) -> Self {
+        self.inner.priority = priority;
+        self
+    }
+
+    /// Set the maximum time each of the underlying requests can take. This overrides the client's timeout.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.inner.timeout = Some(timeout);
+        self
+    }
+
+    /// Split the ids into the ones that can be bundled in search requests, and those needing a lookup
+    fn split_ids(&self) -> (Vec<&str>, Vec<&str>) {
+        let mut seen = HashSet::new();
+        let ids = self.ids.iter().filter(|id| seen.insert(id.as_str()));
+
+        // Search doesn't support includes, so everything needs to be looked up
+        if !self.inner.include.is_empty() {
+            return (Vec::new(), ids.map(String::as_str).collect());
+        }
+
+        ids.map(String::as_str).partition(|id| is_bundlable(id))
+    }
+
+    /// Create the search query of a bundle of ids
+    fn bundle(&self, ids: &[&str]) -> SearchQuery<T> {
+        let mut query =
+            T::search(bundle_query(T::MBID_SEARCH_FIELD, ids)).limit(SEARCH_BUNDLE_SIZE);
+        query.inner.priority = self.inner.priority;
+        query.inner.timeout = self.inner.timeout;
+        query
+    }
+
+    /// Create the lookup query of a single entity
+    fn lookup(&self, id: &str) -> FetchQuery<T> {
+        FetchQuery(self.inner.clone()).id(id)
+    }
+
+    /// The urls of the requests the query starts with: the bundled searches, then the lookups of the ids that can't be bundled.
+    ///
+    /// The ids missing from the search results are then looked up on their own, which can only be known once the searches are done.
+    pub fn to_urls(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Vec<String>, UriBuilderError> {
+        let (bundled, lookups) = self.split_ids();
+
+        bundled
+            .chunks(usize::from(SEARCH_BUNDLE_SIZE))
+            .map(|chunk| self.bundle(chunk).to_url(client))
+            .chain(lookups.into_iter().map(|id| self.lookup(id).to_url(client)))
+            .collect()
+    }
+
+    // === Api Fetching ===
+
+    #[cfg(feature = "sync")]
+    pub fn execute(&self) -> HashMap<String, Result<T, ApiEndpointError>>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        use crate::client::global::global_client;
+
+        self.execute_with_client(&global_client())
+    }
+
+    /// Execute the query with a specific client
+    #[cfg(feature = "sync")]
+    pub fn execute_with_client(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> HashMap<String, Result<T, ApiEndpointError>>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        let (bundled, lookups) = self.split_ids();
+        let mut results = HashMap::with_capacity(self.ids.len());
+        let mut missing = lookups;
+
+        #[cfg(feature = "tracing")]
+        let progress = progress_span(
+            "fetch_many",
+            &self.inner.path,
+            bundled.len() + missing.len(),
+        );
+        #[cfg(feature = "tracing")]
+        let _span = progress.enter();
+
+        for chunk in bundled.chunks(usize::from(SEARCH_BUNDLE_SIZE)) {
+            let searched = self.bundle(chunk).execute_with_client(client);
+            missing.extend(collect_bundle(chunk, searched, &mut results));
+
+            #[cfg(feature = "tracing")]
+            advance(&progress, results.len());
+        }
+
+        let lookups = missing
+            .into_iter()
+            .map(|id| (id.to_string(), self.lookup(id)))
+            .collect();
+        results.extend(lookup_all(client, lookups));
+
+        #[cfg(feature = "tracing")]
+        advance(&progress, results.len());
+
+        results
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn execute_async(&self) -> HashMap<String, Result<T, ApiEndpointError>>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        use crate::client::global::global_client;
+
+        self.execute_with_client_async(&global_client()).await
+    }
+
+    /// Execute the query with a specific client
+    #[cfg(feature = "async")]
+    pub async fn execute_with_client_async(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> HashMap<String, Result<T, ApiEndpointError>>
+    where
+        T: DeserializeOwned + Send + Sync + 'static,
+    {
+        let (bundled, lookups) = self.split_ids();
+
+        #[cfg(feature = "tracing")]
+        let progress = progress_span(
+            "fetch_many",
+            &self.inner.path,
+            bundled.len() + lookups.len(),
+        );
+
+        let execution = async {
+            let mut results = HashMap::with_capacity(self.ids.len());
+            let mut missing = lookups;
+
+            for chunk in bundled.chunks(usize::from(SEARCH_BUNDLE_SIZE)) {
+                let searched = self.bundle(chunk).execute_with_client_async(client).await;
+                missing.extend(collect_bundle(chunk, searched, &mut results));
+
+                #[cfg(feature = "tracing")]
+                advance(&progress, results.len());
+            }
+
+            let lookups = missing
+                .into_iter()
+                .map(|id| (id.to_string(), self.lookup(id)))
+                .collect();
+            results.extend(lookup_all_async(client, lookups).await);
+
+            #[cfg(feature = "tracing")]
+            advance(&progress, results.len());
+
+            results
+        };
+
+        #[cfg(feature = "tracing")]
+        let execution = tracing::Instrument::instrument(execution, progress.clone());
+
+        execution.await
+    }
+}
+
+/// Whether the id can be safely put inside a lucene query
+fn is_bundlable(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+}
+
+/// Create the lucene query matching all the ids of the bundle
+fn bundle_query(field: &str, ids: &[&str]) -> String {
+    format!("{field}:({})", ids.join(" OR "))
+}
+
+/// Move the entities of the bundle's search result into the results, and return the ids that are missing from it.
+///
+/// If the search failed, its error is shared by all the ids of the bundle, and none of them is missing.
+#[cfg(any(feature = "sync", feature = "async"))]
+fn collect_bundle<'a, T>(
+    chunk: &[&'a str],
+    searched: Result<SearchResult<T>, ApiEndpointError>,
+    results: &mut HashMap<String, Result<T, ApiEndpointError>>,
+) -> Vec<&'a str>
+where
+    T: FetchMany,
+{
+    use snafu::IntoError as _;
+
+    use crate::api::SharedSnafu;
+
+    let found = match searched {
+        Ok(searched) => searched.entities,
+        Err(error) => {
+            let error = Arc::new(error);
+            for id in chunk {
+                results.insert(id.to_string(), Err(SharedSnafu.into_error(error.clone())));
+            }
+            return Vec::new();
+        }
+    };
+
+    for entity in found {
+        if chunk.contains(&entity.mbid()) {
+            results.insert(entity.mbid().to_string(), Ok(entity));
+        }
+    }
+
+    chunk
+        .iter()
+        .filter(|id| !results.contains_key(**id))
+        .copied()
+        .collect()
+}
+
+/// Send the lookups by batches of [`LOOKUP_BATCH_SIZE`], each lookup of a batch on its own thread.
+/// The results are returned with the key of their lookup.
+#[cfg(feature = "sync")]
+pub(crate) fn lookup_all<K, T>(
+    client: &crate::MusicBrainzClient,
+    lookups: Vec<(K, FetchQuery<T>)>,
+) -> Vec<(K, Result<T, ApiEndpointError>)>
+where
+    K: Send,
+    T: Fetch + Clone + DeserializeOwned + Send + Sync + 'static,
+{
+    #[cfg(feature = "tracing")]
+    let span = tracing::Span::current();
+
+    let mut results = Vec::with_capacity(lookups.len());
+    let mut lookups = lookups.into_iter().peekable();
+
+    while lookups.peek().is_some() {
+        let batch: Vec<_> = lookups.by_ref().take(LOOKUP_BATCH_SIZE).collect();
+
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = batch
+                .into_iter()
+                .map(|(key, query)| {
+                    #[cfg(feature = "tracing")]
+                    let span = &span;
+
+                    scope.spawn(move || {
+                        #[cfg(feature = "tracing")]
+                        let _span = span.enter();
+
+                        let result = query.execute_with_client(client);
+                        (key, result)
+                    })
+                })
+                .collect();
+
+            results.extend(handles.into_iter().map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            }));
+        });
+    }
+
+    results
+}
+
+/// Send the lookups by batches of [`LOOKUP_BATCH_SIZE`], the lookups of a batch waiting for their response concurrently.
+/// The results are returned with the key of their lookup.
+#[cfg(feature = "async")]
+pub(crate) async fn lookup_all_async<K, T>(
+    client: &crate::MusicBrainzClient,
+    lookups: Vec<(K, FetchQuery<T>)>,
+) -> Vec<(K, Result<T, ApiEndpointError>)>
+where
+    K: Send,
+    T: Fetch + Clone + DeserializeOwned + Send + Sync + 'static,
+{
+    use crate::api::into_future::QueryFuture;
+
+    let mut results = Vec::with_capacity(lookups.len());
+    let mut lookups = lookups.into_iter().peekable();
+
+    while lookups.peek().is_some() {
+        let batch = lookups
+            .by_ref()
+            .take(LOOKUP_BATCH_SIZE)
+            .map(|(key, query)| {
+                Box::pin(async move {
+                    let result = query.execute_with_client_async(client).await;
+                    (key, result)
+                }) as QueryFuture<'_, _>
+            })
+            .collect();
+
+        results.extend(JoinAll::new(batch).await);
+    }
+
+    results
+}
+
+/// Wait for all the futures, polling them concurrently.
+///
+/// This is a minimal `join_all`, as `futures` is only a dev-dependency and the crate doesn't depend on an async runtime.
+/// Every pending future is polled again on each wake up. This is fine for the [`LOOKUP_BATCH_SIZE`] lookups of a batch,
+/// that spend most of their time waiting for the rate limiter.
+#[cfg(feature = "async")]
+struct JoinAll<'a, O> {
+    pending: Vec<Option<crate::api::into_future::QueryFuture<'a, O>>>,
+    outputs: Vec<O>,
+}
+
+#[cfg(feature = "async")]
+impl<'a, O> JoinAll<'a, O> {
+    fn new(futures: Vec<crate::api::into_future::QueryFuture<'a, O>>) -> Self {
+        Self {
+            outputs: Vec::with_capacity(futures.len()),
+            pending: futures.into_iter().map(Some).collect(),
+        }
+    }
+}
+
+// The outputs are never pinned, and the futures are already boxed
+#[cfg(feature = "async")]
+impl<O> Unpin for JoinAll<'_, O> {}
+
+#[cfg(feature = "async")]
+impl<O> Future for JoinAll<'_, O> {
+    type Output = Vec<O>;
+
+    fn poll(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Self::Output> {
+        use core::task::Poll;
+
+        let this = self.get_mut();
+        for slot in &mut this.pending {
+            if let Some(future) = slot {
+                if let Poll::Ready(output) = future.as_mut().poll(cx) {
+                    this.outputs.push(output);
+                    *slot = None;
+                }
+            }
+        }
+
+        if this.pending.iter().any(Option::is_some) {
+            return Poll::Pending;
+        }
+
+        Poll::Ready(core::mem::take(&mut this.outputs))
+    }
+}
+
+#[cfg(feature = "async")]
+crate::api::into_future::impl_into_future!(FetchManyQuery<T>, HashMap<String, Result<T, ApiEndpointError>>, T: FetchMany + Clone + DeserializeOwned + Send + Sync + 'static);
+
+/// Implemented by all entities that can be fetched in bulk (see [`FetchManyQuery`])
+pub trait FetchMany: Fetch + Search + Searchable + APIPath {
+    /// The search field matching the MBID of the entity
+    const MBID_SEARCH_FIELD: &'static str;
+
+    /// The MBID of the entity
+    fn mbid(&self) -> &str;
+
+    fn fetch_many<I, S>(ids: I) -> FetchManyQuery<Self>
+    where
+        Self: Sized,
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        FetchManyQuery {
+            inner: Query {
+                path: Self::path().to_string(),
+                result_type: PhantomData,
+                priority: RequestPriority::default(),
+                timeout: None,
+                include: vec![],
+            },
+            ids: ids.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[cfg(all(test, any(feature = "sync", feature = "async")))]
+mod test {
+    use api_bindium::ureq::http::HeaderMap;
+    use api_bindium::ureq::http::StatusCode;
+
+    use super::*;
+    use crate::entity::api::MusicbrainzError;
+    use crate::entity::recording::Recording;
+
+    #[test]
+    fn should_bundle_ids_in_lucene_query() {
+        assert_eq!(
+            bundle_query(
+                "rid",
+                &[
+                    "af40d6b8-58e8-4ca5-9db8-d4fca0b899e2",
+                    "b80974e2-3da3-4741-bdf4-f2072184aab8"
+                ]
+            ),
+            "rid:(af40d6b8-58e8-4ca5-9db8-d4fca0b899e2 OR b80974e2-3da3-4741-bdf4-f2072184aab8)"
+        );
+    }
+
+    #[test]
+    fn should_only_bundle_mbid_like_ids() {
+        assert!(is_bundlable("af40d6b8-58e8-4ca5-9db8-d4fca0b899e2"));
+        assert!(!is_bundlable(""));
+        assert!(!is_bundlable("a OR rid:*"));
+    }
+
+    const POLLY: &str = "af40d6b8-58e8-4ca5-9db8-d4fca0b899e2";
+    const MERGED: &str = "b80974e2-3da3-4741-bdf4-f2072184aab8";
+
+    fn search_response(body: &str) -> Result<SearchResult<Recording>, ApiEndpointError> {
+        use snafu::ResultExt as _;
+
+        use crate::api::ParsingSnafu;
+
+        Recording::search(String::new())
+            .parse_response(StatusCode::OK, &HeaderMap::new(), body.as_bytes())
+            .context(ParsingSnafu)
+    }
+
+    #[test]
+    fn should_look_up_the_ids_missing_from_the_search() {
+        let searched = search_response(&format!(
+            r#"{{"created":"2024-01-01T00:00:00.000Z","count":1,"offset":0,"recordings":[{{"id":"{POLLY}","title":"Polly","score":100}}]}}"#
+        ));
+
+        let mut results = HashMap::new();
+        let missing = collect_bundle(&[POLLY, MERGED], searched, &mut results);
+
+        assert_eq!(missing, vec![MERGED]);
+        assert_eq!(results[POLLY].as_ref().unwrap().title, "Polly");
+        assert!(!results.contains_key(MERGED));
+    }
+
+    #[test]
+    fn should_keep_the_error_of_a_failed_search() {
+        let searched =
+            search_response(r#"{"error":"Invalid search query","help":"See the documentation"}"#);
+
+        let mut results = HashMap::new();
+        let missing = collect_bundle(&[POLLY, MERGED], searched, &mut results);
+
+        assert!(missing.is_empty());
+        for id in [POLLY, MERGED] {
+            assert_eq!(
+                results[id].as_ref().unwrap_err().as_musicbrainz_error(),
+                Some(&MusicbrainzError::new(
+                    "Invalid search query".to_string(),
+                    "See the documentation".to_string()
+                ))
+            );
+        }
+    }
+
+    /// Answers the lookups of recordings after a while, counting the lookups waiting for their response at once
+    #[cfg(feature = "sync")]
+    #[derive(Default)]
+    struct Slow {
+        in_flight: core::sync::atomic::AtomicUsize,
+        most_in_flight: core::sync::atomic::AtomicUsize,
+    }
+
+    #[cfg(feature = "sync")]
+    impl crate::client::transport::Transport for Slow {
+        fn send(
+            &self,
+            request: api_bindium::ureq::http::Request<()>,
+        ) -> Result<
+            api_bindium::ureq::http::Response<crate::client::transport::ResponseBody>,
+            crate::client::transport::TransportError,
+        > {
+            use core::sync::atomic::Ordering;
+
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.most_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            let id = request.uri().path().rsplit('/').next().unwrap_or_default();
+            Ok(api_bindium::ureq::http::Response::new(
+                format!(r#"{{"id":"{id}","title":"Polly"}}"#).into(),
+            ))
+        }
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn should_send_the_lookups_of_a_batch_at_once() {
+        use core::sync::atomic::Ordering;
+
+        let transport = Arc::new(Slow::default());
+        let client = crate::MusicBrainzClient::builder()
+            .transport(transport.clone())
+            .build();
+
+        let lookups = [POLLY, MERGED]
+            .into_iter()
+            .map(|id| (id, Recording::fetch().id(id)))
+            .collect();
+        let results = lookup_all(&client, lookups);
+
+        assert_eq!(results.len(), 2);
+        for (id, result) in results {
+            assert_eq!(result.unwrap().id, id);
+        }
+        assert_eq!(transport.most_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn should_wait_for_all_the_futures() {
+        use crate::api::into_future::QueryFuture;
+
+        // Each future is ready after yielding as many times as its value, so they finish in reverse order
+        let futures = [3, 0, 1]
+            .into_iter()
+            .map(|yields| {
+                Box::pin(async move {
+                    for _ in 0..yields {
+                        tokio::task::yield_now().await;
+                    }
+                    yields
+                }) as QueryFuture<'_, _>
+            })
+            .collect();
+
+        assert_eq!(JoinAll::new(futures).await, [0, 1, 3]);
+    }
+}
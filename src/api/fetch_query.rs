@@ -1,19 +1,37 @@
 use api_bindium::ApiRequest;
 use api_bindium::endpoints::UriBuilderError;
+use api_bindium::ureq::http::HeaderMap;
+use api_bindium::ureq::http::Request;
+use api_bindium::ureq::http::StatusCode;
+use api_bindium::ureq::http::Uri;
+use core::fmt;
 use core::fmt::Write as _;
 use core::marker::PhantomData;
+use core::str::FromStr;
+use core::time::Duration;
+use serde::Deserialize;
+use serde::Serialize;
 use serde::de::DeserializeOwned;
-use std::collections::HashMap;
 
 use crate::APIPath;
+use crate::ParsingError;
 #[cfg(any(feature = "sync", feature = "async"))]
 use crate::api::ApiEndpointError;
 use crate::api::parser::MusicBrainzParser;
 use crate::api::query::Query;
-#[cfg(feature = "basic_auth")]
-use base64::Engine as _;
-#[cfg(feature = "basic_auth")]
-use base64::engine::general_purpose::STANDARD;
+use crate::api::query::QueryParseError;
+use crate::api::query::UnknownParameterSnafu;
+use crate::api::sans_io::HttpRequestError;
+use crate::api::sans_io::http_request;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::metrics::MetricsKey;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::metrics::QueryKind;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::mirrors::Served;
+use crate::client::scheduler::RequestPriority;
+#[cfg(feature = "lazy_relations")]
+use crate::entity::relations::WithLazyRelations;
 
 /// Perform a lookup of an entity when you have the MBID for that entity
 ///
@@ -35,21 +53,112 @@ use base64::engine::general_purpose::STANDARD;
 /// #   Ok(())
 /// # }
 /// ```
-#[derive(Clone, Debug)]
+///
+/// ## Reusing queries
+///
+/// Executing a query doesn't modify it. The end of a builder chain can be turned into an owned query with `to_owned`,
+/// which can be stored, cloned, and executed as many times as needed.
+///
+/// With the `async` feature, queries can also be awaited directly, which executes them with the global client.
+///
+/// ```rust
+/// # use musicbrainz_rs::prelude::*;
+/// # #[cfg(feature = "async")]
+/// # async fn example() -> Result<(), musicbrainz_rs::ApiEndpointError> {
+/// # use musicbrainz_rs::entity::artist::Artist;
+/// let query = Artist::fetch()
+///         .id("5b11f4ce-a62d-471e-81fc-a69a8278c7da")
+///         .with_aliases()
+///         .to_owned();
+///
+/// let nirvana = query.clone().await?;
+/// let nirvana_again = query.await?;
+/// assert_eq!(nirvana, nirvana_again);
+///
+/// let nirvana = Artist::fetch().id("5b11f4ce-a62d-471e-81fc-a69a8278c7da").await?;
+/// #   Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent, bound(serialize = "", deserialize = "T: APIPath"))]
 pub struct FetchQuery<T>(pub Query<T>);
 
+/// The textual form of the query: the path and parameters of its request, without the format.
+/// For example, `artist/5b11f4ce-a62d-471e-81fc-a69a8278c7da?inc=aliases`
+impl<T> fmt::Display for FetchQuery<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.write_text(f, &[])
+    }
+}
+
+impl<T> FromStr for FetchQuery<T>
+where
+    T: APIPath,
+{
+    type Err = QueryParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (query, params) = Query::parse_text(text)?;
+
+        if let Some((name, _)) = params.into_iter().next() {
+            return UnknownParameterSnafu { name }.fail();
+        }
+
+        Ok(Self(query))
+    }
+}
+
 impl<T> FetchQuery<T>
 where
     T: Clone,
 {
     /// The mbid of the entity to fetch
-    pub fn id(&mut self, id: &str) -> &mut Self {
+    #[must_use]
+    pub fn id(mut self, id: &str) -> Self {
         let _ = write!(self.0.path, "/{id}");
         self
     }
 
+    /// Set the priority of the request in the client's [`RequestScheduler`](crate::client::scheduler::RequestScheduler)
+    #[must_use]
+    pub fn priority(mut self, priority: RequestPriority) -> Self {
+        self.0.priority = priority;
+        self
+    }
+
+    /// Set the maximum time the query can take, including the time spent waiting for the scheduler and rate limiter.
+    /// This overrides the client's timeout.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.0.timeout = Some(timeout);
+        self
+    }
+
+    /// Keep the relations of the fetched entity as raw JSON, only deserialized when needed.
+    /// See [`WithLazyRelations`]
+    #[cfg(feature = "lazy_relations")]
+    pub fn with_lazy_relations(self) -> FetchQuery<WithLazyRelations<T>> {
+        FetchQuery(Query {
+            path: self.0.path,
+            result_type: PhantomData,
+            priority: self.0.priority,
+            timeout: self.0.timeout,
+            include: self.0.include,
+        })
+    }
+
     // === Request Creation ===
 
+    /// Create the request's url
+    fn create_url(&self, client: &crate::MusicBrainzClient) -> Result<Uri, UriBuilderError> {
+        self.0.get_endpoint(client).to_uri()
+    }
+
+    /// The exact url the query requests with this client, for debugging or to build cache keys
+    pub fn to_url(&self, client: &crate::MusicBrainzClient) -> Result<String, UriBuilderError> {
+        self.create_url(client).map(|uri| uri.to_string())
+    }
+
     /// Turn the query into an [`api_bindium::ApiRequest`]
     pub fn as_api_request(
         &self,
@@ -58,126 +167,201 @@ where
     where
         T: DeserializeOwned,
     {
-        let uri = self.0.get_endpoint(client).to_uri()?;
-        let host = uri.host().unwrap_or_default().to_owned();
+        let uri = self.create_url(client)?;
         Ok(ApiRequest::builder()
+            .maybe_headers(client.auth_headers(&uri))
             .uri(uri)
-            .maybe_headers(Self::auth_headers(client, &host))
             .verb(api_bindium::HTTPVerb::Get)
             .parser(MusicBrainzParser::default())
             .build())
     }
 
-    #[cfg(feature = "basic_auth")]
-    fn auth_headers(
+    /// Turn the query into an [`http::Request`](Request) that can be sent by any HTTP client.
+    ///
+    /// The `User-Agent` header needs to be set by the HTTP client. The response can then be parsed with [`Self::parse_response`].
+    pub fn to_http_request(
+        &self,
         client: &crate::MusicBrainzClient,
-        host: &str,
-    ) -> Option<HashMap<String, String>> {
-        Self::credentials(client, host).map(|(username, password)| {
-            HashMap::from([(
-                "Authorization".to_string(),
-                format!(
-                    "Basic {}",
-                    STANDARD.encode(format!("{username}:{password}"))
-                ),
-            )])
-        })
-    }
+    ) -> Result<Request<()>, HttpRequestError> {
+        use snafu::ResultExt as _;
 
-    #[cfg(feature = "basic_auth")]
-    fn credentials<'a>(
-        client: &'a crate::MusicBrainzClient,
-        host: &str,
-    ) -> Option<(&'a str, &'a str)> {
-        #[cfg(feature = "netrc")]
-        if let Some(auth) = client.netrc.as_deref().and_then(|nrc| nrc.hosts.get(host)) {
-            return Some((&auth.login, &auth.password));
-        }
-        #[cfg(not(feature = "netrc"))]
-        let _ = host;
+        use crate::api::sans_io::InvalidUriSnafu;
 
-        client
-            .basic_auth_credentials
-            .as_ref()
-            .map(|(username, password)| (username.as_str(), password.as_str()))
+        http_request(
+            client,
+            self.create_url(client).context(InvalidUriSnafu)?,
+            client.response_format,
+        )
     }
 
-    #[cfg(not(feature = "basic_auth"))]
-    fn auth_headers(
-        _client: &crate::MusicBrainzClient,
-        _host: &str,
-    ) -> Option<HashMap<String, String>> {
-        None
+    /// Parse the response to the request created by [`Self::to_http_request`]
+    pub fn parse_response(
+        &self,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<T, ParsingError>
+    where
+        T: DeserializeOwned,
+    {
+        MusicBrainzParser::parse_response(status, headers, body)
     }
 
     #[cfg(feature = "sync")]
-    pub fn execute(&mut self) -> Result<T, ApiEndpointError>
+    pub fn execute(&self) -> Result<T, ApiEndpointError>
     where
-        T: Fetch + DeserializeOwned + Sync,
+        T: Fetch + DeserializeOwned + Send + Sync + 'static,
     {
-        use crate::client::MUSICBRAINZ_CLIENT;
+        use crate::client::global::global_client;
 
-        self.execute_with_client(&MUSICBRAINZ_CLIENT)
+        self.execute_with_client(&global_client())
     }
 
     /// Execute the query with a specific client
     #[cfg(feature = "sync")]
     pub fn execute_with_client(
-        &mut self,
+        &self,
         client: &crate::MusicBrainzClient,
     ) -> Result<T, ApiEndpointError>
     where
-        T: Fetch + DeserializeOwned + Sync,
+        T: Fetch + DeserializeOwned + Send + Sync + 'static,
     {
-        use snafu::ResultExt as _;
+        self.execute_served(client).map(Served::into_inner)
+    }
 
-        use crate::api::ApiRequestSnafu;
-        use crate::api::InvalidUriSnafu;
-        use crate::api::ParsingSnafu;
+    /// Execute the query with a specific client, and return which server answered it
+    #[cfg(feature = "sync")]
+    pub fn execute_served(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Served<T>, ApiEndpointError>
+    where
+        T: Fetch + DeserializeOwned + Send + Sync + 'static,
+    {
+        use crate::client::timeout::run_with_timeout;
+
+        client.check_dry_run(|| self.to_http_request(client))?;
+
+        #[cfg(feature = "tracing")]
+        let _span = self.0.span(QueryKind::Fetch, None, None, None).entered();
+
+        let result = match self.0.timeout.or(client.timeout) {
+            Some(timeout) => run_with_timeout(client, timeout, |client| self.send_blocking(client)),
+            None => self.send_blocking(client),
+        };
 
-        self.as_api_request(client)
-            .context(InvalidUriSnafu)?
-            .send(&client.api_client)
-            .context(ApiRequestSnafu)?
-            .parse()
-            .context(ParsingSnafu)
+        client.record_result(&self.metrics_key(), &result);
+        result
+    }
+
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn metrics_key(&self) -> MetricsKey {
+        MetricsKey::new(&self.0.path, QueryKind::Fetch)
+    }
+
+    #[cfg(feature = "sync")]
+    fn send_blocking(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Served<T>, ApiEndpointError>
+    where
+        T: DeserializeOwned + Sync,
+    {
+        let key = self.metrics_key();
+        client.with_failover(&key, |client| {
+            client.send_request(
+                &key,
+                self.0.priority,
+                self.to_http_request(client),
+                |status, headers, body| {
+                    MusicBrainzParser::parse_response_reader(status, headers, body)
+                },
+            )
+        })
     }
 
     #[cfg(feature = "async")]
-    pub async fn execute_async(&mut self) -> Result<T, ApiEndpointError>
+    pub async fn execute_async(&self) -> Result<T, ApiEndpointError>
     where
-        T: Fetch + DeserializeOwned + Sync,
+        T: Fetch + DeserializeOwned + Send + Sync + 'static,
     {
-        use crate::client::MUSICBRAINZ_CLIENT;
+        use crate::client::global::global_client;
 
-        self.execute_with_client_async(&MUSICBRAINZ_CLIENT).await
+        self.execute_with_client_async(&global_client()).await
     }
 
     /// Execute the query with a specific client
     #[cfg(feature = "async")]
     pub async fn execute_with_client_async(
-        &mut self,
+        &self,
         client: &crate::MusicBrainzClient,
     ) -> Result<T, ApiEndpointError>
     where
-        T: Fetch + DeserializeOwned + Sync,
+        T: Fetch + DeserializeOwned + Send + Sync + 'static,
+    {
+        self.execute_served_async(client)
+            .await
+            .map(Served::into_inner)
+    }
+
+    /// Execute the query with a specific client, and return which server answered it
+    #[cfg(feature = "async")]
+    pub async fn execute_served_async(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Served<T>, ApiEndpointError>
+    where
+        T: Fetch + DeserializeOwned + Send + Sync + 'static,
     {
         use snafu::ResultExt as _;
 
-        use crate::api::ApiRequestSnafu;
         use crate::api::InvalidUriSnafu;
-        use crate::api::ParsingSnafu;
+        use crate::client::timeout::with_timeout;
 
-        self.as_api_request(client)
-            .context(InvalidUriSnafu)?
-            .send_async(&client.api_client)
-            .await
-            .context(ApiRequestSnafu)?
-            .parse()
-            .context(ParsingSnafu)
+        client.check_dry_run(|| self.to_http_request(client))?;
+
+        #[cfg(feature = "tracing")]
+        let span = self.0.span(QueryKind::Fetch, None, None, None);
+
+        let key = self.metrics_key();
+        let execution = async {
+            let uri = self.create_url(client).context(InvalidUriSnafu)?;
+            let query = &*self;
+            let key = &key;
+            let request = client.coalesce(key, uri.to_string(), async {
+                let endpoints = client.endpoint_clients();
+
+                client
+                    .with_failover_async(key, &endpoints, |client| async move {
+                        client
+                            .send_request_async(
+                                key,
+                                query.0.priority,
+                                query.to_http_request(client),
+                                |status, headers, body| {
+                                    MusicBrainzParser::parse_response_reader(status, headers, body)
+                                },
+                            )
+                            .await
+                    })
+                    .await
+            });
+
+            with_timeout(self.0.timeout.or(client.timeout), request).await
+        };
+
+        #[cfg(feature = "tracing")]
+        let execution = tracing::Instrument::instrument(execution, span);
+
+        let result = execution.await;
+        client.record_result(&key, &result);
+        result
     }
 }
 
+#[cfg(feature = "async")]
+crate::api::into_future::impl_into_future!(FetchQuery<T>, Result<T, ApiEndpointError>, T: Fetch + Clone + DeserializeOwned + Send + Sync + 'static);
+
 /// Implemented by all fetchable entities (see [`FetchQuery`])
 pub trait Fetch {
     fn fetch() -> FetchQuery<Self>
@@ -187,7 +371,12 @@ pub trait Fetch {
         FetchQuery(Query {
             path: Self::path().to_string(),
             result_type: PhantomData,
+            priority: RequestPriority::default(),
+            timeout: None,
             include: vec![],
         })
     }
 }
+
+#[cfg(feature = "lazy_relations")]
+impl<T: Fetch> Fetch for WithLazyRelations<T> {}
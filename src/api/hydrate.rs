@@ -0,0 +1,295 @@
+//! Completion of partial entities with their full record.
+//!
+//! Search results, the artists of artist credits, the labels of label infos and the targets of relations
+//! only hold some of the fields of their entity. Hydrating them looks them up by id, and [merges](Merge)
+//! the full record with the fields already known.
+//!
+//! ```rust
+//! # use musicbrainz_rs::prelude::*;
+//! # #[cfg(feature = "sync")]
+//! # fn main() -> Result<(), musicbrainz_rs::ApiEndpointError> {
+//! # use musicbrainz_rs::MusicBrainzClient;
+//! # use musicbrainz_rs::entity::recording::Recording;
+//! let client = MusicBrainzClient::default();
+//! let polly = Recording::fetch()
+//!         .id("af40d6b8-58e8-4ca5-9db8-d4fca0b899e2")
+//!         .with_artists()
+//!         .execute_with_client(&client)?;
+//!
+//! let credited = &polly.artist_credit.unwrap()[0].artist;
+//! let nirvana = credited.hydrate(&client, |query| query.with_aliases())?;
+//! assert!(nirvana.aliases.is_some());
+//! #   Ok(())
+//! # }
+//! # #[cfg(not(feature = "sync"))]
+//! # fn main() {}
+//! ```
+
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::collections::HashMap;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::collections::HashSet;
+
+#[cfg(any(feature = "sync", feature = "async"))]
+use serde::de::DeserializeOwned;
+
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::APIPath;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::ApiEndpointError;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::Fetch;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::FetchQuery;
+use crate::entity::merge::Merge;
+
+/// Implemented by the entities that can be completed by a lookup of their id
+pub trait Hydrate: Merge {
+    /// The id the entity is looked up by
+    fn lookup_id(&self) -> &str;
+
+    /// Fetch the full record of the entity, with the includes set by `includes`, and merge it with this instance.
+    ///
+    /// The fields of the full record are preferred, and the ones it lacks are kept from this instance, like the `score` of a search result.
+    #[cfg(feature = "sync")]
+    fn hydrate(
+        &self,
+        client: &crate::MusicBrainzClient,
+        includes: impl FnOnce(FetchQuery<Self>) -> FetchQuery<Self>,
+    ) -> Result<Self, ApiEndpointError>
+    where
+        Self: Fetch + APIPath + Clone + DeserializeOwned + Send + Sync + 'static,
+    {
+        let template = lookup_template(includes);
+        Ok(lookup(&template, self)
+            .execute_with_client(client)?
+            .merge(self.clone()))
+    }
+
+    /// Hydrate all the entities, keyed by id. Each id is only looked up once,
+    /// with [`LOOKUP_BATCH_SIZE`](crate::api::fetch_many::LOOKUP_BATCH_SIZE) lookups sent at once.
+    #[cfg(feature = "sync")]
+    fn hydrate_all(
+        stubs: &[Self],
+        client: &crate::MusicBrainzClient,
+        includes: impl FnOnce(FetchQuery<Self>) -> FetchQuery<Self>,
+    ) -> HashMap<String, Result<Self, ApiEndpointError>>
+    where
+        Self: Fetch + APIPath + Clone + DeserializeOwned + Send + Sync + 'static,
+    {
+        let lookups = lookups(stubs, &lookup_template(includes));
+
+        merge_all(crate::api::fetch_many::lookup_all(client, lookups))
+    }
+
+    /// Fetch the full record of the entity, with the includes set by `includes`, and merge it with this instance.
+    ///
+    /// The fields of the full record are preferred, and the ones it lacks are kept from this instance, like the `score` of a search result.
+    #[cfg(feature = "async")]
+    fn hydrate_async(
+        &self,
+        client: &crate::MusicBrainzClient,
+        includes: impl FnOnce(FetchQuery<Self>) -> FetchQuery<Self>,
+    ) -> impl Future<Output = Result<Self, ApiEndpointError>> + Send
+    where
+        Self: Fetch + APIPath + Clone + DeserializeOwned + Send + Sync + 'static,
+    {
+        let query = lookup(&lookup_template(includes), self);
+
+        async move {
+            Ok(query
+                .execute_with_client_async(client)
+                .await?
+                .merge(self.clone()))
+        }
+    }
+
+    /// Hydrate all the entities, keyed by id. Each id is only looked up once,
+    /// with [`LOOKUP_BATCH_SIZE`](crate::api::fetch_many::LOOKUP_BATCH_SIZE) lookups waiting for the rate limiter at once.
+    #[cfg(feature = "async")]
+    fn hydrate_all_async(
+        stubs: &[Self],
+        client: &crate::MusicBrainzClient,
+        includes: impl FnOnce(FetchQuery<Self>) -> FetchQuery<Self>,
+    ) -> impl Future<Output = HashMap<String, Result<Self, ApiEndpointError>>> + Send
+    where
+        Self: Fetch + APIPath + Clone + DeserializeOwned + Send + Sync + 'static,
+    {
+        let lookups = lookups(stubs, &lookup_template(includes));
+
+        async move { merge_all(crate::api::fetch_many::lookup_all_async(client, lookups).await) }
+    }
+}
+
+/// The lookup query of the entities, without id
+#[cfg(any(feature = "sync", feature = "async"))]
+fn lookup_template<T>(includes: impl FnOnce(FetchQuery<T>) -> FetchQuery<T>) -> FetchQuery<T>
+where
+    T: Fetch + APIPath,
+{
+    includes(T::fetch())
+}
+
+/// The lookup query of an entity
+#[cfg(any(feature = "sync", feature = "async"))]
+fn lookup<T>(template: &FetchQuery<T>, stub: &T) -> FetchQuery<T>
+where
+    T: Hydrate + Clone,
+{
+    template.clone().id(stub.lookup_id())
+}
+
+/// The first stub of each id
+#[cfg(any(feature = "sync", feature = "async"))]
+fn unique_stubs<T: Hydrate>(stubs: &[T]) -> Vec<&T> {
+    let mut seen = HashSet::new();
+    stubs
+        .iter()
+        .filter(|stub| seen.insert(stub.lookup_id()))
+        .collect()
+}
+
+/// The lookup of each id of the stubs, keyed by the stub it completes
+#[cfg(any(feature = "sync", feature = "async"))]
+fn lookups<'a, T>(stubs: &'a [T], template: &FetchQuery<T>) -> Vec<(&'a T, FetchQuery<T>)>
+where
+    T: Hydrate + Clone,
+{
+    unique_stubs(stubs)
+        .into_iter()
+        .map(|stub| (stub, lookup(template, stub)))
+        .collect()
+}
+
+/// Merge the full records with the stubs they were looked up for, keyed by id
+#[cfg(any(feature = "sync", feature = "async"))]
+fn merge_all<T>(
+    looked_up: Vec<(&T, Result<T, ApiEndpointError>)>,
+) -> HashMap<String, Result<T, ApiEndpointError>>
+where
+    T: Hydrate + Clone,
+{
+    looked_up
+        .into_iter()
+        .map(|(stub, result)| {
+            let result = result.map(|full| full.merge(stub.clone()));
+            (stub.lookup_id().to_string(), result)
+        })
+        .collect()
+}
+
+#[cfg(all(test, any(feature = "sync", feature = "async")))]
+mod test {
+    use core::sync::atomic::AtomicUsize;
+    use core::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use api_bindium::ureq::http::Request;
+    use api_bindium::ureq::http::Response;
+
+    use super::*;
+    use crate::client::MusicBrainzClient;
+    use crate::client::transport::ResponseBody;
+    use crate::client::transport::Transport;
+    use crate::client::transport::TransportError;
+    use crate::entity::artist::Artist;
+
+    const NIRVANA: &str = "5b11f4ce-a62d-471e-81fc-a69a8278c7da";
+    const KURT_COBAIN: &str = "5c0b2a52-c0a6-4fd8-b3b0-f3fdc2e05e9d";
+
+    /// Answers the lookups of artists with their full record, counting them
+    #[derive(Default)]
+    struct Lookups(AtomicUsize);
+
+    impl Transport for Lookups {
+        fn send(&self, request: Request<()>) -> Result<Response<ResponseBody>, TransportError> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+
+            let id = request.uri().path().rsplit('/').next().unwrap_or_default();
+            let name = match id {
+                NIRVANA => "Nirvana",
+                KURT_COBAIN => "Kurt Cobain",
+                _ => return Err(format!("Unexpected request: {}", request.uri()).into()),
+            };
+
+            Ok(Response::new(
+                format!(r#"{{"id":"{id}","name":"{name}","sort-name":"{name}","country":"US"}}"#)
+                    .into(),
+            ))
+        }
+    }
+
+    /// An artist as found in search results
+    fn searched(id: &str, name: &str) -> Artist {
+        serde_json::from_str(&format!(
+            r#"{{"id":"{id}","name":"{name}","sort-name":"{name}","score":100}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn should_keep_the_first_stub_of_each_id() {
+        let stubs = [
+            searched(NIRVANA, "nirvana"),
+            searched(KURT_COBAIN, "kurt cobain"),
+            searched(NIRVANA, "Nirvana (2)"),
+        ];
+
+        let unique: Vec<&str> = unique_stubs(&stubs)
+            .into_iter()
+            .map(|stub| stub.name.as_str())
+            .collect();
+
+        assert_eq!(unique, ["nirvana", "kurt cobain"]);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn should_prefer_the_fields_of_the_full_record() {
+        let client = MusicBrainzClient::builder()
+            .transport(Arc::new(Lookups::default()))
+            .build();
+
+        let nirvana = searched(NIRVANA, "nirvana")
+            .hydrate(&client, |query| query)
+            .unwrap();
+
+        assert_eq!(nirvana.name, "Nirvana");
+        assert_eq!(nirvana.country.as_deref(), Some("US"));
+        assert_eq!(nirvana.score, Some(100));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn should_look_up_each_id_once() {
+        let lookups = Arc::new(Lookups::default());
+        let client = MusicBrainzClient::builder()
+            .transport(lookups.clone())
+            .build();
+        let stubs = [
+            searched(NIRVANA, "nirvana"),
+            searched(KURT_COBAIN, "kurt cobain"),
+            searched(NIRVANA, "nirvana"),
+        ];
+
+        let hydrated = Artist::hydrate_all_async(&stubs, &client, |query| query).await;
+
+        assert_eq!(lookups.0.load(Ordering::Relaxed), 2);
+        assert_eq!(hydrated.len(), 2);
+        assert_eq!(hydrated[KURT_COBAIN].as_ref().unwrap().name, "Kurt Cobain");
+        assert_eq!(hydrated[NIRVANA].as_ref().unwrap().score, Some(100));
+    }
+}
+
+/// Implement [`Hydrate`] for entities looked up by their `id` field
+macro_rules! impl_hydrate {
+    ($($t: ty),+) => {
+        $(impl crate::api::hydrate::Hydrate for $t {
+            fn lookup_id(&self) -> &str {
+                &self.id
+            }
+        })+
+    };
+}
+
+pub(crate) use impl_hydrate;
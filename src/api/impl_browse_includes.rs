@@ -73,7 +73,8 @@ pub(crate) use impl_browse_relationships_includes;
 
 macro_rules! impl_browse_includes_inner {
     ($args:ident, $inc: expr_2021) => {
-        pub fn $args(&mut self) -> &mut Self {
+        #[must_use]
+        pub fn $args(mut self) -> Self {
             self.inner.include($inc);
             self
         }
@@ -0,0 +1,88 @@
+use core::pin::Pin;
+
+/// The future returned when awaiting a query directly
+pub type QueryFuture<'a, O> = Pin<Box<dyn Future<Output = O> + Send + 'a>>;
+
+/// Implement [`IntoFuture`] for a query and a mutable reference to it, so the end of a builder chain can be awaited.
+/// The query is executed with the global client, like `execute_async`.
+macro_rules! impl_into_future {
+    ($query: ty, $output: ty, $($bounds: tt)+) => {
+        impl<T> core::future::IntoFuture for $query
+        where
+            $($bounds)+
+        {
+            type Output = $output;
+            type IntoFuture = crate::api::into_future::QueryFuture<'static, $output>;
+
+            fn into_future(self) -> Self::IntoFuture {
+                Box::pin(async move { self.execute_async().await })
+            }
+        }
+
+        impl<'a, T> core::future::IntoFuture for &'a mut $query
+        where
+            $($bounds)+
+        {
+            type Output = $output;
+            type IntoFuture = crate::api::into_future::QueryFuture<'a, $output>;
+
+            fn into_future(self) -> Self::IntoFuture {
+                Box::pin(self.execute_async())
+            }
+        }
+    };
+}
+
+pub(crate) use impl_into_future;
+
+#[cfg(all(test, feature = "async"))]
+mod test {
+    use std::sync::Arc;
+
+    use api_bindium::ureq::http::Request;
+    use api_bindium::ureq::http::Response;
+
+    use crate::Fetch as _;
+    use crate::client::MusicBrainzClient;
+    use crate::client::global::with_global_client_async;
+    use crate::client::transport::ResponseBody;
+    use crate::client::transport::Transport;
+    use crate::client::transport::TransportError;
+    use crate::entity::artist::Artist;
+
+    /// Answers the lookup of Nirvana with its aliases
+    struct Nirvana;
+
+    impl Transport for Nirvana {
+        fn send(&self, request: Request<()>) -> Result<Response<ResponseBody>, TransportError> {
+            if request.uri().query() != Some("fmt=json&inc=aliases") {
+                return Err(format!("Unexpected request: {}", request.uri()).into());
+            }
+
+            Ok(Response::new(
+                r#"{"id":"5b11f4ce-a62d-471e-81fc-a69a8278c7da","name":"Nirvana","sort-name":"Nirvana","aliases":[]}"#.into(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn should_await_a_chained_builder() {
+        let client = MusicBrainzClient::builder()
+            .transport(Arc::new(Nirvana))
+            .build();
+
+        // Spawned to check that the future borrowing the temporary query is `Send`
+        let nirvana = tokio::spawn(with_global_client_async(client, async {
+            Artist::fetch()
+                .id("5b11f4ce-a62d-471e-81fc-a69a8278c7da")
+                .with_aliases()
+                .await
+        }))
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(nirvana.name, "Nirvana");
+        assert_eq!(nirvana.aliases, Some(Vec::new()));
+    }
+}
@@ -0,0 +1,367 @@
+use core::any::Any;
+use core::fmt;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use snafu::ResultExt as _;
+
+use crate::ApiEndpointError;
+use crate::BrowseQuery;
+use crate::FetchCoverartQuery;
+use crate::FetchQuery;
+use crate::SearchQuery;
+use crate::api::ParsingSnafu;
+use crate::api::parser::parsing_error::MusicBrainzSnafu;
+use crate::client::metrics::QueryKind;
+use crate::entity::BrowseResult;
+use crate::entity::CoverartResponse;
+use crate::entity::api::MusicbrainzError;
+use crate::entity::search::SearchResult;
+
+/// An in-memory implementation of the [`MusicBrainzApi`](crate::api::musicbrainz_api::MusicBrainzApi) traits,
+/// returning canned responses instead of sending requests.
+///
+/// The responses are matched on the query's textual form (its path and parameters).
+/// Queries without a response get a "Not Found" [`MusicbrainzError`].
+///
+/// ## Example
+/// ```rust
+/// # use musicbrainz_rs::prelude::*;
+/// # #[cfg(feature = "sync")]
+/// # fn main() {
+/// # use musicbrainz_rs::api::mock::MockMusicBrainzApi;
+/// # use musicbrainz_rs::entity::artist::Artist;
+/// # let nirvana: Artist = serde_json::from_str(r#"{"id": "5b11f4ce-a62d-471e-81fc-a69a8278c7da", "name": "Nirvana", "sort-name": "Nirvana"}"#).unwrap();
+/// let api = MockMusicBrainzApi::new();
+/// api.on_fetch(&Artist::fetch().id("5b11f4ce-a62d-471e-81fc-a69a8278c7da"), Ok(nirvana));
+///
+/// let artist = api.fetch(&Artist::fetch().id("5b11f4ce-a62d-471e-81fc-a69a8278c7da")).unwrap();
+/// assert_eq!(artist.name, "Nirvana");
+/// assert_eq!(api.calls().len(), 1);
+/// # }
+/// # #[cfg(not(feature = "sync"))]
+/// # fn main() {}
+/// ```
+#[derive(Default)]
+pub struct MockMusicBrainzApi {
+    responses: Mutex<HashMap<(QueryKind, String), MockResponse>>,
+    calls: Mutex<Vec<MockCall>>,
+}
+
+/// A query received by a [`MockMusicBrainzApi`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockCall {
+    pub kind: QueryKind,
+
+    /// The textual form of the query (ex: `artist/5b11f4ce-a62d-471e-81fc-a69a8278c7da?inc=aliases`)
+    pub query: String,
+}
+
+/// A canned response. The value is the output type of the query it is registered for
+#[derive(Clone)]
+enum MockResponse {
+    Value(Arc<dyn Any + Send + Sync>),
+    Error(MusicbrainzError),
+}
+
+impl MockMusicBrainzApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the response to a fetch query
+    pub fn on_fetch<T>(&self, query: &FetchQuery<T>, response: Result<T, MusicbrainzError>) -> &Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.respond(QueryKind::Fetch, query.to_string(), response)
+    }
+
+    /// Set the response to a browse query
+    pub fn on_browse<T>(
+        &self,
+        query: &BrowseQuery<T>,
+        response: Result<BrowseResult<T>, MusicbrainzError>,
+    ) -> &Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.respond(QueryKind::Browse, query.to_string(), response)
+    }
+
+    /// Set the response to a search query
+    pub fn on_search<T>(
+        &self,
+        query: &SearchQuery<T>,
+        response: Result<SearchResult<T>, MusicbrainzError>,
+    ) -> &Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.respond(QueryKind::Search, query.to_string(), response)
+    }
+
+    /// Set the response to a coverart query
+    pub fn on_coverart<T>(
+        &self,
+        query: &FetchCoverartQuery<T>,
+        response: Result<CoverartResponse, MusicbrainzError>,
+    ) -> &Self
+    where
+        T: Clone + crate::FetchCoverart,
+    {
+        self.respond(QueryKind::Coverart, query.target_path(), response)
+    }
+
+    /// The queries received so far, in order
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn respond<R>(
+        &self,
+        kind: QueryKind,
+        query: String,
+        response: Result<R, MusicbrainzError>,
+    ) -> &Self
+    where
+        R: Send + Sync + 'static,
+    {
+        let response = match response {
+            Ok(value) => MockResponse::Value(Arc::new(value)),
+            Err(error) => MockResponse::Error(error),
+        };
+
+        self.responses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((kind, query), response);
+        self
+    }
+
+    /// Record the call, and return its canned response
+    fn answer<R>(&self, kind: QueryKind, query: String) -> Result<R, ApiEndpointError>
+    where
+        R: Clone + 'static,
+    {
+        self.calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(MockCall {
+                kind,
+                query: query.clone(),
+            });
+
+        let response = self
+            .responses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&(kind, query))
+            .cloned();
+
+        let error = match response {
+            Some(MockResponse::Value(value)) => {
+                return Ok(value
+                    .downcast_ref::<R>()
+                    .expect("responses are registered with the output type of their query")
+                    .clone());
+            }
+            Some(MockResponse::Error(error)) => error,
+            None => MusicbrainzError::not_found(),
+        };
+
+        Err(error).context(MusicBrainzSnafu).context(ParsingSnafu)
+    }
+}
+
+impl fmt::Debug for MockMusicBrainzApi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockMusicBrainzApi")
+            .field(
+                "responses",
+                &self
+                    .responses
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .len(),
+            )
+            .field("calls", &self.calls)
+            .finish()
+    }
+}
+
+#[cfg(feature = "sync")]
+impl crate::api::musicbrainz_api::MusicBrainzApi for MockMusicBrainzApi {
+    fn fetch<T>(&self, query: &FetchQuery<T>) -> Result<T, ApiEndpointError>
+    where
+        T: crate::Fetch + Clone + serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        self.answer(QueryKind::Fetch, query.to_string())
+    }
+
+    fn browse<T>(&self, query: &BrowseQuery<T>) -> Result<BrowseResult<T>, ApiEndpointError>
+    where
+        T: crate::Browse
+            + crate::entity::Browsable
+            + Clone
+            + serde::de::DeserializeOwned
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.answer(QueryKind::Browse, query.to_string())
+    }
+
+    fn search<T>(&self, query: &SearchQuery<T>) -> Result<SearchResult<T>, ApiEndpointError>
+    where
+        T: crate::Search
+            + crate::entity::search::Searchable
+            + Clone
+            + serde::de::DeserializeOwned
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.answer(QueryKind::Search, query.to_string())
+    }
+
+    fn coverart<T>(
+        &self,
+        query: &FetchCoverartQuery<T>,
+    ) -> Result<CoverartResponse, ApiEndpointError>
+    where
+        T: crate::FetchCoverart + Clone + Send + 'static,
+    {
+        self.answer(QueryKind::Coverart, query.target_path())
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::api::musicbrainz_api::MusicBrainzApiAsync for MockMusicBrainzApi {
+    fn fetch_async<T>(
+        &self,
+        query: &FetchQuery<T>,
+    ) -> impl Future<Output = Result<T, ApiEndpointError>> + Send
+    where
+        T: crate::Fetch + Clone + serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        core::future::ready(self.answer(QueryKind::Fetch, query.to_string()))
+    }
+
+    fn browse_async<T>(
+        &self,
+        query: &BrowseQuery<T>,
+    ) -> impl Future<Output = Result<BrowseResult<T>, ApiEndpointError>> + Send
+    where
+        T: crate::Browse
+            + crate::entity::Browsable
+            + Clone
+            + serde::de::DeserializeOwned
+            + Send
+            + Sync
+            + 'static,
+    {
+        core::future::ready(self.answer(QueryKind::Browse, query.to_string()))
+    }
+
+    fn search_async<T>(
+        &self,
+        query: &SearchQuery<T>,
+    ) -> impl Future<Output = Result<SearchResult<T>, ApiEndpointError>> + Send
+    where
+        T: crate::Search
+            + crate::entity::search::Searchable
+            + Clone
+            + serde::de::DeserializeOwned
+            + Send
+            + Sync
+            + 'static,
+    {
+        core::future::ready(self.answer(QueryKind::Search, query.to_string()))
+    }
+
+    fn coverart_async<T>(
+        &self,
+        query: &FetchCoverartQuery<T>,
+    ) -> impl Future<Output = Result<CoverartResponse, ApiEndpointError>> + Send
+    where
+        T: crate::FetchCoverart + Clone + Send + Sync + 'static,
+    {
+        core::future::ready(self.answer(QueryKind::Coverart, query.target_path()))
+    }
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    use super::*;
+    use crate::Fetch as _;
+    use crate::FetchCoverart as _;
+    use crate::api::musicbrainz_api::MusicBrainzApi;
+    use crate::entity::artist::Artist;
+    use crate::entity::release::Release;
+
+    const NIRVANA_ID: &str = "5b11f4ce-a62d-471e-81fc-a69a8278c7da";
+
+    fn nirvana() -> Artist {
+        serde_json::from_str(&format!(
+            r#"{{"id": "{NIRVANA_ID}", "name": "Nirvana", "sort-name": "Nirvana"}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn should_answer_with_canned_responses() {
+        let api = MockMusicBrainzApi::new();
+        api.on_fetch(&Artist::fetch().id(NIRVANA_ID), Ok(nirvana()))
+            .on_fetch(
+                &Artist::fetch().id(NIRVANA_ID).with_aliases(),
+                Err(MusicbrainzError::new(
+                    "Invalid inc".to_string(),
+                    String::new(),
+                )),
+            );
+
+        assert_eq!(
+            api.fetch(&Artist::fetch().id(NIRVANA_ID)).unwrap(),
+            nirvana()
+        );
+        assert!(
+            api.fetch(&Artist::fetch().id(NIRVANA_ID).with_aliases())
+                .unwrap_err()
+                .as_musicbrainz_error()
+                .is_some_and(|error| !error.is_not_found())
+        );
+        assert!(
+            api.coverart(&Release::fetch_coverart().id(NIRVANA_ID).front())
+                .unwrap_err()
+                .as_musicbrainz_error()
+                .is_some_and(MusicbrainzError::is_not_found)
+        );
+    }
+
+    #[test]
+    fn should_record_calls() {
+        let api = MockMusicBrainzApi::new();
+        let _ = api.fetch(&Artist::fetch().id(NIRVANA_ID).with_aliases());
+        let _ = api.coverart(&Release::fetch_coverart().id(NIRVANA_ID).front());
+
+        assert_eq!(
+            api.calls(),
+            [
+                MockCall {
+                    kind: QueryKind::Fetch,
+                    query: format!("artist/{NIRVANA_ID}?inc=aliases"),
+                },
+                MockCall {
+                    kind: QueryKind::Coverart,
+                    query: format!("release/{NIRVANA_ID}/front"),
+                },
+            ]
+        );
+    }
+}
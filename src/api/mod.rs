@@ -1,19 +1,36 @@
+use core::time::Duration;
+use std::sync::Arc;
+
 use api_bindium::ApiRequestError;
 use api_bindium::endpoints::UriBuilderError;
+use api_bindium::ureq::http::Request;
 use snafu::Snafu;
 
 use crate::ParsingError;
+use crate::api::sans_io::HttpRequestError;
+use crate::client::middleware::MiddlewareError;
 use crate::entity::api::MusicbrainzError;
 
 pub mod browse_query;
+pub mod complete_lists;
 pub mod coverart_query;
 pub mod endpoints;
+pub mod fetch_many;
 pub mod fetch_query;
+pub mod hydrate;
 pub mod impl_browse_includes;
 pub mod impl_relations_includes;
+#[cfg(feature = "async")]
+pub mod into_future;
+#[cfg(any(feature = "sync", feature = "async"))]
+pub mod mock;
+#[cfg(any(feature = "sync", feature = "async"))]
+pub mod musicbrainz_api;
 pub mod parser;
 pub mod query;
+pub mod sans_io;
 pub mod search_query;
+pub mod typed_fetch;
 pub mod ws;
 
 #[derive(Debug, Snafu)]
@@ -48,6 +65,99 @@ pub enum ApiEndpointError {
         #[cfg(feature = "backtrace")]
         backtrace: snafu::Backtrace,
     },
+
+    /// The query couldn't be turned into an HTTP request for the client's [`Transport`](crate::client::transport::Transport)
+    HttpRequestError {
+        source: HttpRequestError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The client's [`Transport`](crate::client::transport::Transport) failed to send the request
+    TransportError {
+        source: crate::client::transport::TransportError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// A [`Middleware`](crate::client::middleware::Middleware) cancelled the request
+    MiddlewareError {
+        source: MiddlewareError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// A server answered with a server error (5xx) while other servers were left to be tried
+    ServerError {
+        status: u16,
+        host: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The client is in [dry run](crate::MusicBrainzClient::dry_run) mode. This holds the request that would have been sent
+    #[snafu(display("Dry run of the request to `{}`", request.uri()))]
+    DryRunError {
+        request: Box<Request<()>>,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The request didn't complete before its timeout. This includes the time spent waiting for the scheduler and rate limiter.
+    TimeoutError {
+        timeout: Duration,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// An identical request failed while this one was [coalesced](crate::MusicBrainzClient::coalesce_requests) with it.
+    /// This holds the error of that request.
+    SharedError {
+        source: Arc<ApiEndpointError>,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+/// The kind of error a query failed with. See [`ApiEndpointError::kind`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    Request,
+    InvalidUri,
+    Parsing,
+    Middleware,
+    Server,
+    Timeout,
+    Transport,
+    DryRun,
 }
 
 impl ApiEndpointError {
@@ -55,7 +165,106 @@ impl ApiEndpointError {
     pub fn as_musicbrainz_error(&self) -> Option<&MusicbrainzError> {
         match self {
             Self::ParsingError { source, .. } => source.as_musicbrainz_error(),
-            Self::ApiRequestError { .. } | Self::InvalidUriError { .. } => None,
+            Self::SharedError { source, .. } => source.as_musicbrainz_error(),
+            Self::ApiRequestError { .. }
+            | Self::InvalidUriError { .. }
+            | Self::HttpRequestError { .. }
+            | Self::TransportError { .. }
+            | Self::MiddlewareError { .. }
+            | Self::ServerError { .. }
+            | Self::DryRunError { .. }
+            | Self::TimeoutError { .. } => None,
+        }
+    }
+
+    /// If the client is in dry run mode, the request that would have been sent
+    pub fn as_dry_run_request(&self) -> Option<&Request<()>> {
+        match self {
+            Self::DryRunError { request, .. } => Some(request),
+            Self::SharedError { source, .. } => source.as_dry_run_request(),
+            Self::ApiRequestError { .. }
+            | Self::InvalidUriError { .. }
+            | Self::ParsingError { .. }
+            | Self::HttpRequestError { .. }
+            | Self::TransportError { .. }
+            | Self::MiddlewareError { .. }
+            | Self::ServerError { .. }
+            | Self::TimeoutError { .. } => None,
+        }
+    }
+
+    /// Whether the request got cancelled because it reached its timeout
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            Self::TimeoutError { .. } => true,
+            Self::SharedError { source, .. } => source.is_timeout(),
+            Self::ApiRequestError { .. }
+            | Self::InvalidUriError { .. }
+            | Self::ParsingError { .. }
+            | Self::HttpRequestError { .. }
+            | Self::TransportError { .. }
+            | Self::MiddlewareError { .. }
+            | Self::ServerError { .. }
+            | Self::DryRunError { .. } => false,
+        }
+    }
+
+    /// Whether sending the request again may succeed, like when the server couldn't be reached or answered with a server error
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::ApiRequestError { .. }
+            | Self::TransportError { .. }
+            | Self::ServerError { .. }
+            | Self::TimeoutError { .. } => true,
+            Self::SharedError { source, .. } => source.is_transient(),
+            Self::InvalidUriError { .. }
+            | Self::ParsingError { .. }
+            | Self::HttpRequestError { .. }
+            | Self::MiddlewareError { .. }
+            | Self::DryRunError { .. } => false,
+        }
+    }
+
+    /// A copy of the error, if it only holds data that can be copied.
+    /// The errors of the transports and the I/O errors can't be.
+    #[cfg(feature = "async")]
+    pub(crate) fn duplicate(&self) -> Option<Self> {
+        use snafu::IntoError as _;
+
+        match self {
+            Self::ParsingError { source, .. } => source
+                .duplicate()
+                .map(|source| ParsingSnafu.into_error(source)),
+            Self::ServerError { status, host, .. } => Some(
+                ServerSnafu {
+                    status: *status,
+                    host: host.clone(),
+                }
+                .build(),
+            ),
+            Self::TimeoutError { timeout, .. } => Some(TimeoutSnafu { timeout: *timeout }.build()),
+            Self::SharedError { source, .. } => Some(SharedSnafu.into_error(Arc::clone(source))),
+            Self::ApiRequestError { .. }
+            | Self::InvalidUriError { .. }
+            | Self::HttpRequestError { .. }
+            | Self::TransportError { .. }
+            | Self::MiddlewareError { .. }
+            | Self::DryRunError { .. } => None,
+        }
+    }
+
+    /// Return the kind of the error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::ApiRequestError { .. } => ErrorKind::Request,
+            Self::InvalidUriError { .. } | Self::HttpRequestError { .. } => ErrorKind::InvalidUri,
+            Self::TransportError { .. } => ErrorKind::Transport,
+            Self::ParsingError { .. } => ErrorKind::Parsing,
+            Self::MiddlewareError { .. } => ErrorKind::Middleware,
+            Self::ServerError { .. } => ErrorKind::Server,
+            Self::TimeoutError { .. } => ErrorKind::Timeout,
+            Self::DryRunError { .. } => ErrorKind::DryRun,
+            Self::SharedError { source, .. } => source.kind(),
         }
     }
 }
@@ -0,0 +1,163 @@
+use serde::de::DeserializeOwned;
+
+use crate::ApiEndpointError;
+use crate::Browse;
+use crate::BrowseQuery;
+use crate::Fetch;
+use crate::FetchCoverart;
+use crate::FetchCoverartQuery;
+use crate::FetchQuery;
+use crate::MusicBrainzClient;
+use crate::Search;
+use crate::SearchQuery;
+use crate::entity::Browsable;
+use crate::entity::BrowseResult;
+use crate::entity::CoverartResponse;
+use crate::entity::search::SearchResult;
+use crate::entity::search::Searchable;
+
+/// The queries that can be sent to MusicBrainz, implemented by [`MusicBrainzClient`].
+///
+/// Taking an `impl MusicBrainzApi` instead of a client allows testing code with a
+/// [`MockMusicBrainzApi`](crate::api::mock::MockMusicBrainzApi), without sending requests.
+///
+/// ## Example
+/// ```rust
+/// # use musicbrainz_rs::prelude::*;
+/// # use musicbrainz_rs::entity::artist::Artist;
+/// # #[cfg(feature = "sync")]
+/// fn artist_name(api: &impl MusicBrainzApi, id: &str) -> Result<String, musicbrainz_rs::ApiEndpointError> {
+///     let artist = api.fetch(&Artist::fetch().id(id))?;
+///     Ok(artist.name)
+/// }
+/// ```
+#[cfg(feature = "sync")]
+pub trait MusicBrainzApi {
+    fn fetch<T>(&self, query: &FetchQuery<T>) -> Result<T, ApiEndpointError>
+    where
+        T: Fetch + Clone + DeserializeOwned + Send + Sync + 'static;
+
+    fn browse<T>(&self, query: &BrowseQuery<T>) -> Result<BrowseResult<T>, ApiEndpointError>
+    where
+        T: Browse + Browsable + Clone + DeserializeOwned + Send + Sync + 'static;
+
+    fn search<T>(&self, query: &SearchQuery<T>) -> Result<SearchResult<T>, ApiEndpointError>
+    where
+        T: Search + Searchable + Clone + DeserializeOwned + Send + Sync + 'static;
+
+    fn coverart<T>(
+        &self,
+        query: &FetchCoverartQuery<T>,
+    ) -> Result<CoverartResponse, ApiEndpointError>
+    where
+        T: FetchCoverart + Clone + Send + 'static;
+}
+
+/// The async version of [`MusicBrainzApi`](crate::api::musicbrainz_api::MusicBrainzApi)
+#[cfg(feature = "async")]
+pub trait MusicBrainzApiAsync {
+    fn fetch_async<T>(
+        &self,
+        query: &FetchQuery<T>,
+    ) -> impl Future<Output = Result<T, ApiEndpointError>> + Send
+    where
+        T: Fetch + Clone + DeserializeOwned + Send + Sync + 'static;
+
+    fn browse_async<T>(
+        &self,
+        query: &BrowseQuery<T>,
+    ) -> impl Future<Output = Result<BrowseResult<T>, ApiEndpointError>> + Send
+    where
+        T: Browse + Browsable + Clone + DeserializeOwned + Send + Sync + 'static;
+
+    fn search_async<T>(
+        &self,
+        query: &SearchQuery<T>,
+    ) -> impl Future<Output = Result<SearchResult<T>, ApiEndpointError>> + Send
+    where
+        T: Search + Searchable + Clone + DeserializeOwned + Send + Sync + 'static;
+
+    fn coverart_async<T>(
+        &self,
+        query: &FetchCoverartQuery<T>,
+    ) -> impl Future<Output = Result<CoverartResponse, ApiEndpointError>> + Send
+    where
+        T: FetchCoverart + Clone + Send + Sync + 'static;
+}
+
+#[cfg(feature = "sync")]
+impl MusicBrainzApi for MusicBrainzClient {
+    fn fetch<T>(&self, query: &FetchQuery<T>) -> Result<T, ApiEndpointError>
+    where
+        T: Fetch + Clone + DeserializeOwned + Send + Sync + 'static,
+    {
+        query.execute_with_client(self)
+    }
+
+    fn browse<T>(&self, query: &BrowseQuery<T>) -> Result<BrowseResult<T>, ApiEndpointError>
+    where
+        T: Browse + Browsable + Clone + DeserializeOwned + Send + Sync + 'static,
+    {
+        query.execute_with_client(self)
+    }
+
+    fn search<T>(&self, query: &SearchQuery<T>) -> Result<SearchResult<T>, ApiEndpointError>
+    where
+        T: Search + Searchable + Clone + DeserializeOwned + Send + Sync + 'static,
+    {
+        query.execute_with_client(self)
+    }
+
+    fn coverart<T>(
+        &self,
+        query: &FetchCoverartQuery<T>,
+    ) -> Result<CoverartResponse, ApiEndpointError>
+    where
+        T: FetchCoverart + Clone + Send + 'static,
+    {
+        query.execute_with_client(self)
+    }
+}
+
+#[cfg(feature = "async")]
+impl MusicBrainzApiAsync for MusicBrainzClient {
+    fn fetch_async<T>(
+        &self,
+        query: &FetchQuery<T>,
+    ) -> impl Future<Output = Result<T, ApiEndpointError>> + Send
+    where
+        T: Fetch + Clone + DeserializeOwned + Send + Sync + 'static,
+    {
+        query.execute_with_client_async(self)
+    }
+
+    fn browse_async<T>(
+        &self,
+        query: &BrowseQuery<T>,
+    ) -> impl Future<Output = Result<BrowseResult<T>, ApiEndpointError>> + Send
+    where
+        T: Browse + Browsable + Clone + DeserializeOwned + Send + Sync + 'static,
+    {
+        query.execute_with_client_async(self)
+    }
+
+    fn search_async<T>(
+        &self,
+        query: &SearchQuery<T>,
+    ) -> impl Future<Output = Result<SearchResult<T>, ApiEndpointError>> + Send
+    where
+        T: Search + Searchable + Clone + DeserializeOwned + Send + Sync + 'static,
+    {
+        query.execute_with_client_async(self)
+    }
+
+    fn coverart_async<T>(
+        &self,
+        query: &FetchCoverartQuery<T>,
+    ) -> impl Future<Output = Result<CoverartResponse, ApiEndpointError>> + Send
+    where
+        T: FetchCoverart + Clone + Send + Sync + 'static,
+    {
+        query.execute_with_client_async(self)
+    }
+}
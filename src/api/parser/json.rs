@@ -0,0 +1,168 @@
+//! The JSON backend of the parser.
+//!
+//! This is `serde_json`, or `simd-json` with the `simd-json` feature.
+use std::io::Read;
+
+use serde::de::DeserializeOwned;
+use snafu::ResultExt as _;
+
+use crate::ParsingError;
+
+/// Deserialize a whole body
+#[cfg(not(feature = "simd-json"))]
+pub(crate) fn from_slice<T: DeserializeOwned>(body: &[u8]) -> Result<T, ParsingError> {
+    use crate::api::parser::parsing_error::JsonSnafu;
+
+    serde_json::from_slice(body).context(JsonSnafu)
+}
+
+/// Deserialize a whole body.
+///
+/// simd-json parses in place, so it works on a copy of the body. Use [`from_slice_mut`] to avoid it.
+#[cfg(feature = "simd-json")]
+pub(crate) fn from_slice<T: DeserializeOwned>(body: &[u8]) -> Result<T, ParsingError> {
+    from_slice_mut(&mut body.to_vec())
+}
+
+/// Deserialize a whole body, that may be modified by the parser
+#[cfg(not(feature = "simd-json"))]
+#[expect(
+    clippy::needless_pass_by_ref_mut,
+    reason = "Only simd-json modifies the body"
+)]
+pub(crate) fn from_slice_mut<T: DeserializeOwned>(body: &mut [u8]) -> Result<T, ParsingError> {
+    from_slice(body)
+}
+
+/// Deserialize a whole body, that may be modified by the parser.
+///
+/// simd-json parses in place, so the body isn't copied.
+#[cfg(feature = "simd-json")]
+pub(crate) fn from_slice_mut<T: DeserializeOwned>(body: &mut [u8]) -> Result<T, ParsingError> {
+    use crate::api::parser::parsing_error::SimdJsonSnafu;
+
+    simd_json::serde::from_slice(body).context(SimdJsonSnafu)
+}
+
+/// Deserialize a body as it is read
+#[cfg(not(feature = "simd-json"))]
+pub(crate) fn from_reader<T: DeserializeOwned>(reader: impl Read) -> Result<T, ParsingError> {
+    use crate::api::parser::parsing_error::JsonSnafu;
+
+    serde_json::from_reader(std::io::BufReader::new(reader)).context(JsonSnafu)
+}
+
+/// Deserialize a body as it is read.
+///
+/// simd-json needs the whole body, so it is read first.
+#[cfg(feature = "simd-json")]
+pub(crate) fn from_reader<T: DeserializeOwned>(mut reader: impl Read) -> Result<T, ParsingError> {
+    use crate::api::parser::parsing_error::ReadBodySnafu;
+    use crate::api::parser::parsing_error::SimdJsonSnafu;
+
+    let mut body = Vec::new();
+    reader.read_to_end(&mut body).context(ReadBodySnafu)?;
+    simd_json::serde::from_slice(&mut body).context(SimdJsonSnafu)
+}
+
+#[cfg(test)]
+mod test {
+    use api_bindium::ureq::http::StatusCode;
+
+    use crate::ParsingError;
+    use crate::api::parser::MusicBrainzParser;
+    use crate::entity::place::Coordinate;
+    use crate::entity::place::Place;
+    use crate::entity::relations::RelationContent;
+
+    // Run with each backend, as simd-json handles `flatten` and `untagged` through its own deserializer
+    const PLACE: &str = r#"{
+        "id": "4352063b-a833-421b-a420-e7fb295dece0",
+        "name": "Royal Albert Hall",
+        "type": "Venue",
+        "type-id": "cd92781a-a73f-30e8-a430-55d7521338db",
+        "address": "Kensington Gore, London SW7 2AP, UK",
+        "coordinates": { "latitude": "51.50105", "longitude": -0.17748 },
+        "life-span": { "begin": "1871-03-29", "end": null, "ended": false },
+        "disambiguation": "",
+        "relations": [
+            {
+                "type": "held at",
+                "type-id": "e2c6f697-07dc-38b1-be0b-83d740165532",
+                "target-type": "event",
+                "direction": "backward",
+                "begin": null,
+                "end": null,
+                "ended": false,
+                "attributes": [],
+                "attribute-ids": {},
+                "attribute-values": {},
+                "source-credit": "",
+                "target-credit": "",
+                "event": {
+                    "id": "e1a2a8be-4a3b-4cd6-a5f3-9f8b7c6b2e21",
+                    "name": "The Proms 2019, Prom 1",
+                    "type": "Concert",
+                    "type-id": "ef55e8d7-3d00-394a-8012-f5506a29ff0b",
+                    "cancelled": false,
+                    "time": "19:30",
+                    "setlist": "",
+                    "life-span": { "begin": "2019-07-19", "end": "2019-07-19", "ended": true },
+                    "disambiguation": ""
+                }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn should_parse_flattened_fields() {
+        let mut body = PLACE.as_bytes().to_vec();
+        let place = MusicBrainzParser::<Place>::parse_body_mut(StatusCode::OK, &mut body).unwrap();
+
+        let coordinates = place.coordinates.unwrap();
+        assert_eq!(coordinates.latitude, "51.50105");
+        assert_eq!(coordinates.longitude, -0.17748);
+
+        let relations = place.relations.unwrap();
+        assert!(matches!(
+            &relations[0].content,
+            RelationContent::Event(event) if event.name == "The Proms 2019, Prom 1"
+        ));
+    }
+
+    #[test]
+    fn should_parse_untagged_enums() {
+        let mut body = br#"["51.50105", -0.17748]"#.to_vec();
+        let coordinates: Vec<Coordinate> = super::from_slice_mut(&mut body).unwrap();
+
+        assert_eq!(
+            coordinates,
+            [
+                Coordinate::StringCoordinate("51.50105".to_string()),
+                Coordinate::NumberCoordinate(serde_json::Number::from_f64(-0.17748).unwrap()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_the_same_as_a_copy() {
+        let copied =
+            MusicBrainzParser::<Place>::parse_body(StatusCode::OK, PLACE.as_bytes()).unwrap();
+        let in_place = MusicBrainzParser::<Place>::parse_body_mut(
+            StatusCode::OK,
+            &mut PLACE.as_bytes().to_vec(),
+        )
+        .unwrap();
+
+        assert_eq!(copied, in_place);
+    }
+
+    #[test]
+    fn should_read_errors_through_musicbrainz_result() {
+        let mut body = br#"{"error": "Not Found", "help": "For usage, please see: https://musicbrainz.org/development/mmd"}"#.to_vec();
+
+        let result = MusicBrainzParser::<Place>::parse_body_mut(StatusCode::OK, &mut body);
+
+        assert!(matches!(result, Err(ParsingError::MusicBrainzError { .. })));
+    }
+}
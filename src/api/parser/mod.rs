@@ -1,18 +1,78 @@
 use core::marker::PhantomData;
+use std::io::Read;
 
-use api_bindium::JsonParser;
 use api_bindium::Parser;
 use api_bindium::api_response::ureq_response::UreqResponseInner;
+use api_bindium::ureq::http::HeaderMap;
+use api_bindium::ureq::http::StatusCode;
+use api_bindium::ureq::http::header::CONTENT_TYPE;
 use serde::de::DeserializeOwned;
 use snafu::ResultExt as _;
 
 use crate::ParsingError;
 use crate::api::parser::musicbrainz_result::MusicbrainzResult;
-use crate::api::parser::parsing_error::ApiResponseSnafu;
+use crate::api::parser::parsing_error::HttpStatusSnafu;
 use crate::api::parser::parsing_error::MusicBrainzSnafu;
+use crate::api::parser::parsing_error::ReadBodySnafu;
+use crate::entity::api::MusicbrainzError;
+use crate::entity::interning::with_interning;
 
+mod json;
 pub mod musicbrainz_result;
 pub mod parsing_error;
+#[cfg(feature = "xml")]
+pub mod xml;
+
+/// The format of the responses requested to the API
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ResponseFormat {
+    /// The JSON format
+    #[default]
+    Json,
+
+    /// The XML format ([MMD](https://musicbrainz.org/doc/MusicBrainz_XML)).
+    /// The responses are parsed into the same entity structs as the JSON ones
+    #[cfg(feature = "xml")]
+    Xml,
+}
+
+impl ResponseFormat {
+    /// The value of the `fmt` parameter of the requests
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            #[cfg(feature = "xml")]
+            Self::Xml => "xml",
+        }
+    }
+
+    /// The mime type of the format
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            #[cfg(feature = "xml")]
+            Self::Xml => "application/xml",
+        }
+    }
+
+    /// Find the format of a response from its `Content-Type` header. Defaults to JSON.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        #[cfg(feature = "xml")]
+        if headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.contains("xml"))
+        {
+            return Self::Xml;
+        }
+
+        #[cfg(not(feature = "xml"))]
+        let _ = (headers, CONTENT_TYPE);
+
+        Self::Json
+    }
+}
 
 pub struct MusicBrainzParser<T>(PhantomData<T>);
 
@@ -24,10 +84,122 @@ where
     type Error = ParsingError;
 
     fn parse(&self, response: UreqResponseInner) -> Result<Self::Output, Self::Error> {
-        let res: MusicbrainzResult<T> = JsonParser::default()
-            .parse(response)
-            .context(ApiResponseSnafu)?;
+        let (parts, body) = response.data.into_parts();
+
+        Self::parse_response_reader(parts.status, &parts.headers, body.into_reader())
+    }
+}
+
+impl<T> MusicBrainzParser<T>
+where
+    T: DeserializeOwned,
+{
+    /// Parse the body of a response received by any HTTP client.
+    ///
+    /// With the `simd-json` feature, the body is copied to be parsed. [`Self::parse_body_mut`] avoids it.
+    pub fn parse_body(status: StatusCode, body: &[u8]) -> Result<T, ParsingError> {
+        Self::check_status(status, body)?;
+
+        let res: MusicbrainzResult<T> = with_interning(|| json::from_slice(body))?;
+        res.into_result().context(MusicBrainzSnafu)
+    }
+
+    /// Parse the body of a response received by any HTTP client. The body may be modified while being parsed.
+    ///
+    /// With the `simd-json` feature, the body is parsed in place instead of being copied.
+    pub fn parse_body_mut(status: StatusCode, body: &mut [u8]) -> Result<T, ParsingError> {
+        Self::check_status(status, body)?;
+
+        let res: MusicbrainzResult<T> = with_interning(|| json::from_slice_mut(body))?;
+        res.into_result().context(MusicBrainzSnafu)
+    }
+
+    /// Return the error of a response that wasn't successful
+    fn check_status(status: StatusCode, body: &[u8]) -> Result<(), ParsingError> {
+        if status.is_success() {
+            return Ok(());
+        }
+
+        match serde_json::from_slice::<MusicbrainzError>(body) {
+            Ok(err) => Err(err).context(MusicBrainzSnafu),
+            Err(_) => HttpStatusSnafu {
+                status: status.as_u16(),
+            }
+            .fail(),
+        }
+    }
+
+    /// Parse the body of a response as it is read, without loading it whole in memory first.
+    ///
+    /// This is what the client uses for the JSON responses, as lookups with many includes can weigh several megabytes.
+    /// With the `simd-json` feature, the body is read whole before being parsed, as simd-json needs it all.
+    pub fn parse_reader(status: StatusCode, mut reader: impl Read) -> Result<T, ParsingError> {
+        if !status.is_success() {
+            // Error bodies are small, and may not be JSON
+            let mut body = Vec::new();
+            reader.read_to_end(&mut body).context(ReadBodySnafu)?;
+            return Self::parse_body(status, &body);
+        }
+
+        let res: MusicbrainzResult<T> = with_interning(|| json::from_reader(reader))?;
+        res.into_result().context(MusicBrainzSnafu)
+    }
+
+    /// Parse the body of a response received by any HTTP client, in the format given by its `Content-Type` header
+    pub fn parse_response(
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<T, ParsingError> {
+        match ResponseFormat::from_headers(headers) {
+            ResponseFormat::Json => Self::parse_body(status, body),
+            #[cfg(feature = "xml")]
+            ResponseFormat::Xml => Self::parse_xml_body(status, body),
+        }
+    }
+
+    /// Parse the body of a response as it is read, in the format given by its `Content-Type` header.
+    ///
+    /// This is what the client uses for the responses of its [`Transport`](crate::client::transport::Transport).
+    /// The XML responses are read whole before being converted.
+    pub fn parse_response_reader(
+        status: StatusCode,
+        headers: &HeaderMap,
+        reader: impl Read,
+    ) -> Result<T, ParsingError> {
+        match ResponseFormat::from_headers(headers) {
+            ResponseFormat::Json => Self::parse_reader(status, reader),
+            #[cfg(feature = "xml")]
+            ResponseFormat::Xml => {
+                let mut reader = reader;
+                let mut body = Vec::new();
+                reader.read_to_end(&mut body).context(ReadBodySnafu)?;
+                Self::parse_xml_body(status, &body)
+            }
+        }
+    }
+
+    /// Parse the body of a response in the XML format
+    #[cfg(feature = "xml")]
+    pub fn parse_xml_body(status: StatusCode, body: &[u8]) -> Result<T, ParsingError> {
+        use crate::api::parser::parsing_error::JsonSnafu;
+        use crate::api::parser::parsing_error::XmlSnafu;
+        use crate::api::parser::xml::to_json;
+
+        let value = to_json(&String::from_utf8_lossy(body)).context(XmlSnafu)?;
+
+        if !status.is_success() {
+            return match serde_json::from_value::<MusicbrainzError>(value) {
+                Ok(err) => Err(err).context(MusicBrainzSnafu),
+                Err(_) => HttpStatusSnafu {
+                    status: status.as_u16(),
+                }
+                .fail(),
+            };
+        }
 
+        let res: MusicbrainzResult<T> =
+            with_interning(|| serde_json::from_value(value)).context(JsonSnafu)?;
         res.into_result().context(MusicBrainzSnafu)
     }
 }
@@ -1,6 +1,19 @@
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::de::DeserializeSeed;
+use serde::de::MapAccess;
+use serde::de::SeqAccess;
+use serde::de::Visitor;
+use serde::de::value::MapAccessDeserializer;
+use serde::de::value::SeqAccessDeserializer;
+use serde::de::value::StringDeserializer;
+
 use crate::entity::api::MusicbrainzError;
 
-#[derive(Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, serde::Serialize)]
 #[serde(untagged)]
 pub(crate) enum MusicbrainzResult<T> {
     Ok(T),
@@ -15,3 +28,112 @@ impl<T> MusicbrainzResult<T> {
         }
     }
 }
+
+// An untagged enum would buffer the whole response before trying each variant.
+// Instead, the first key of the response tells which variant it is, and the value is deserialized as it is read.
+impl<'de, T> Deserialize<'de> for MusicbrainzResult<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(MusicbrainzResultVisitor(PhantomData))
+    }
+}
+
+struct MusicbrainzResultVisitor<T>(PhantomData<T>);
+
+impl<'de, T> Visitor<'de> for MusicbrainzResultVisitor<T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = MusicbrainzResult<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a MusicBrainz response or error")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let first_key: Option<String> = map.next_key()?;
+        let is_error = first_key.as_deref() == Some("error");
+        let map = MapAccessDeserializer::new(PeekedMap {
+            first_key,
+            inner: map,
+        });
+
+        if is_error {
+            MusicbrainzError::deserialize(map).map(MusicbrainzResult::Err)
+        } else {
+            T::deserialize(map).map(MusicbrainzResult::Ok)
+        }
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        T::deserialize(SeqAccessDeserializer::new(seq)).map(MusicbrainzResult::Ok)
+    }
+}
+
+/// A map whose first key has already been read
+struct PeekedMap<A> {
+    first_key: Option<String>,
+    inner: A,
+}
+
+impl<'de, A> MapAccess<'de> for PeekedMap<A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.first_key.take() {
+            Some(key) => seed.deserialize(StringDeserializer::new(key)).map(Some),
+            None => self.inner.next_key_seed(seed),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.inner.next_value_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner
+            .size_hint()
+            .map(|size| size + usize::from(self.first_key.is_some()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::entity::artist::Artist;
+
+    #[test]
+    fn should_detect_errors_from_the_first_key() {
+        let result: MusicbrainzResult<Artist> = serde_json::from_str(
+            r#"{"error": "Not Found", "help": "For usage, please see: https://musicbrainz.org/development/mmd"}"#,
+        )
+        .unwrap();
+        assert!(result.into_result().unwrap_err().is_not_found());
+
+        let result: MusicbrainzResult<Artist> = serde_json::from_str(
+            r#"{"id": "5b11f4ce-a62d-471e-81fc-a69a8278c7da", "name": "Nirvana", "sort-name": "Nirvana"}"#,
+        )
+        .unwrap();
+        assert_eq!(result.into_result().unwrap().name, "Nirvana");
+    }
+}
@@ -1,9 +1,12 @@
 use snafu::Snafu;
 
+#[cfg(feature = "xml")]
+use crate::api::parser::xml::XmlError;
 use crate::entity::api::MusicbrainzError;
 
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub(crate)))]
+#[non_exhaustive]
 pub enum ParsingError {
     ApiResponseError {
         source: api_bindium::ApiRequestError,
@@ -24,14 +27,150 @@ pub enum ParsingError {
         #[cfg(feature = "backtrace")]
         backtrace: snafu::Backtrace,
     },
+
+    /// The body of the response isn't valid JSON for the expected type
+    JsonError {
+        source: serde_json::Error,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The body of the response couldn't be read
+    ReadBodyError {
+        source: std::io::Error,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The body of the response isn't valid XML in the MusicBrainz format
+    #[cfg(feature = "xml")]
+    XmlError {
+        source: XmlError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The body of the response isn't valid JSON for the expected type
+    #[cfg(feature = "simd-json")]
+    SimdJsonError {
+        source: simd_json::Error,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The server answered with an error status, without a MusicBrainz error in the body
+    #[snafu(display("The server answered with the status {status}"))]
+    HttpStatusError {
+        status: u16,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// A redirect to an image was expected, but the response had no `Location` header
+    #[snafu(display("Expected a redirect to the image, but got the status {status}"))]
+    MissingRedirectError {
+        status: u16,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The entity lacks a field that the includes of a [`TypedFetchQuery`](crate::api::typed_fetch::TypedFetchQuery) guarantee
+    #[snafu(display("The response is missing the included field `{field}`"))]
+    MissingIncludeError {
+        field: &'static str,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
 }
 
 impl ParsingError {
+    /// A copy of the error, if it only holds data that can be copied
+    #[cfg(feature = "async")]
+    pub(crate) fn duplicate(&self) -> Option<Self> {
+        use snafu::IntoError as _;
+
+        match self {
+            Self::MusicBrainzError { source, .. } => {
+                Some(MusicBrainzSnafu.into_error(source.clone()))
+            }
+            Self::HttpStatusError { status, .. } => {
+                Some(HttpStatusSnafu { status: *status }.build())
+            }
+            Self::MissingRedirectError { status, .. } => {
+                Some(MissingRedirectSnafu { status: *status }.build())
+            }
+            Self::MissingIncludeError { field, .. } => {
+                Some(MissingIncludeSnafu { field: *field }.build())
+            }
+            Self::ApiResponseError { .. } | Self::JsonError { .. } | Self::ReadBodyError { .. } => {
+                None
+            }
+            #[cfg(feature = "simd-json")]
+            Self::SimdJsonError { .. } => None,
+            #[cfg(feature = "xml")]
+            Self::XmlError { .. } => None,
+        }
+    }
+
+    /// Whether the body of the response couldn't be read, like when the connection was lost while receiving it
+    pub fn is_read_error(&self) -> bool {
+        match self {
+            Self::ReadBodyError { .. } => true,
+            Self::JsonError { source, .. } => source.is_io(),
+            Self::ApiResponseError { .. }
+            | Self::MusicBrainzError { .. }
+            | Self::HttpStatusError { .. }
+            | Self::MissingRedirectError { .. }
+            | Self::MissingIncludeError { .. } => false,
+            #[cfg(feature = "simd-json")]
+            Self::SimdJsonError { .. } => false,
+            #[cfg(feature = "xml")]
+            Self::XmlError { .. } => false,
+        }
+    }
+
     // If the underlying error is an [`MusicbrainzError`](crate::entity::api::MusicbrainzError), return it
     pub fn as_musicbrainz_error(&self) -> Option<&MusicbrainzError> {
         match self {
             Self::MusicBrainzError { source, .. } => Some(source),
-            _ => None,
+            Self::ApiResponseError { .. }
+            | Self::JsonError { .. }
+            | Self::ReadBodyError { .. }
+            | Self::HttpStatusError { .. }
+            | Self::MissingRedirectError { .. }
+            | Self::MissingIncludeError { .. } => None,
+            #[cfg(feature = "simd-json")]
+            Self::SimdJsonError { .. } => None,
+            #[cfg(feature = "xml")]
+            Self::XmlError { .. } => None,
         }
     }
 }
@@ -0,0 +1,908 @@
+//! Conversion of the MusicBrainz XML format ([MMD](https://musicbrainz.org/doc/MusicBrainz_XML)) into the JSON format.
+//!
+//! The XML responses are turned into the [`Value`] the JSON API would have returned for the same request,
+//! so that they can be deserialized into the same entity structs.
+use quick_xml::Reader;
+use quick_xml::events::BytesStart;
+use quick_xml::events::Event;
+use serde_json::Map;
+use serde_json::Number;
+use serde_json::Value;
+use snafu::OptionExt as _;
+use snafu::ResultExt as _;
+use snafu::Snafu;
+
+/// The lists whose items are plain strings in the JSON format. The value is taken from the `id` attribute if the item has no text.
+const STRING_LISTS: &[&str] = &[
+    "attribute",
+    "ipi",
+    "isni",
+    "isrc",
+    "iswc",
+    "iso-3166-1-code",
+    "iso-3166-2-code",
+    "iso-3166-3-code",
+    "language",
+    "secondary-type",
+];
+
+/// The lists whose items are numbers in the JSON format, like the offsets of a disc
+const NUMBER_LISTS: &[&str] = &["offset"];
+
+/// The fields holding a boolean
+const BOOL_FIELDS: &[&str] = &[
+    "approved",
+    "artwork",
+    "back",
+    "cancelled",
+    "darkened",
+    "ended",
+    "front",
+    "primary",
+    "video",
+];
+
+/// The fields holding a number. Fields ending in `-count` and `-offset` are numbers too.
+const NUMBER_FIELDS: &[&str] = &[
+    "count",
+    "label-code",
+    "length",
+    "offset",
+    "ordering-key",
+    "position",
+    "score",
+    "sectors",
+    "tracks",
+    "value",
+];
+
+/// Attributes that are named differently in the JSON format
+const RENAMED_ATTRIBUTES: &[(&str, &str)] = &[("begin-date", "begin"), ("end-date", "end")];
+
+/// An XML element, with its namespace prefixes removed
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn new(start: &BytesStart<'_>) -> Result<Self, XmlError> {
+        let mut attributes = Vec::new();
+
+        for attribute in start.attributes() {
+            let attribute = attribute
+                .map_err(quick_xml::Error::from)
+                .context(InvalidXmlSnafu)?;
+
+            // Namespace declarations aren't data
+            if attribute.key.as_ref().starts_with(b"xmlns") {
+                continue;
+            }
+
+            attributes.push((
+                String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned(),
+                attribute
+                    .unescape_value()
+                    .context(InvalidXmlSnafu)?
+                    .into_owned(),
+            ));
+        }
+
+        Ok(Self {
+            name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+            attributes,
+            ..Default::default()
+        })
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn child(&self, name: &str) -> Option<&Self> {
+        self.children.iter().find(|child| child.name == name)
+    }
+}
+
+/// Parse the XML document into its root element
+fn parse_tree(xml: &str) -> Result<Element, XmlError> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root = None;
+
+    loop {
+        let finished = match reader.read_event().context(InvalidXmlSnafu)? {
+            Event::Start(start) => {
+                stack.push(Element::new(&start)?);
+                None
+            }
+            Event::Empty(start) => Some(Element::new(&start)?),
+            Event::End(_) => stack.pop(),
+            Event::Text(text) => {
+                if let Some(element) = stack.last_mut() {
+                    element
+                        .text
+                        .push_str(&text.unescape().context(InvalidXmlSnafu)?);
+                }
+                None
+            }
+            Event::CData(data) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&String::from_utf8_lossy(&data));
+                }
+                None
+            }
+            Event::Eof => break,
+            Event::Comment(_) | Event::Decl(_) | Event::PI(_) | Event::DocType(_) => None,
+        };
+
+        if let Some(element) = finished {
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(element),
+                None => root = Some(element),
+            }
+        }
+    }
+
+    root.context(EmptyDocumentSnafu)
+}
+
+/// Convert an XML response into the JSON value the API returns for the same request
+pub(crate) fn to_json(xml: &str) -> Result<Value, XmlError> {
+    let root = parse_tree(xml)?;
+
+    match root.name.as_str() {
+        "metadata" => metadata(root),
+        "error" => Ok(error(&root)),
+        _ => UnexpectedRootSnafu { name: root.name }.fail(),
+    }
+}
+
+/// Convert the `<metadata>` root of a successful response
+fn metadata(root: Element) -> Result<Value, XmlError> {
+    let created = root.attribute("created").map(ToString::to_string);
+    let content = root
+        .children
+        .into_iter()
+        .next()
+        .context(EmptyDocumentSnafu)?;
+
+    let Some(base) = content.name.strip_suffix("-list") else {
+        let mut value = element_value(&content);
+        if let Value::Object(object) = &mut value {
+            for (key, default) in lookup_defaults(&content) {
+                object.entry(*key).or_insert_with(*default);
+            }
+        }
+
+        return Ok(value);
+    };
+
+    // Search results have a creation date, and unprefixed counts. Browse results have their counts prefixed by the entity
+    let mut result = Map::new();
+    let (count_key, offset_key) = match created {
+        Some(created) => {
+            result.insert("created".to_string(), Value::String(created));
+            ("count".to_string(), "offset".to_string())
+        }
+        None => (format!("{base}-count"), format!("{base}-offset")),
+    };
+
+    result.insert(
+        count_key,
+        typed("count", content.attribute("count").unwrap_or("0")),
+    );
+    result.insert(
+        offset_key,
+        typed("offset", content.attribute("offset").unwrap_or("0")),
+    );
+    result.insert(
+        plural(base),
+        Value::Array(
+            content
+                .children
+                .iter()
+                .map(|item| list_item(base, item))
+                .collect(),
+        ),
+    );
+
+    Ok(Value::Object(result))
+}
+
+/// Convert an `<error>` root, made of the error message followed by the help message
+fn error(root: &Element) -> Value {
+    let mut texts = root
+        .children
+        .iter()
+        .filter(|child| child.name == "text")
+        .map(|child| Value::String(child.text.clone()));
+
+    let mut error = Map::new();
+    error.insert(
+        "error".to_string(),
+        texts.next().unwrap_or_else(|| Value::String(String::new())),
+    );
+    error.insert(
+        "help".to_string(),
+        texts.next().unwrap_or_else(|| Value::String(String::new())),
+    );
+    Value::Object(error)
+}
+
+/// Convert an element that isn't directly inside a list
+fn element_value(element: &Element) -> Value {
+    if element.name == "artist-credit" {
+        return Value::Array(element.children.iter().map(name_credit).collect());
+    }
+
+    if element.children.is_empty() && element.attributes.is_empty() {
+        return typed(&element.name, &element.text);
+    }
+
+    Value::Object(object(element))
+}
+
+/// Convert an element into an object holding its attributes, its text and its children
+fn object(element: &Element) -> Map<String, Value> {
+    let mut object = Map::new();
+
+    for (key, value) in &element.attributes {
+        let key = RENAMED_ATTRIBUTES
+            .iter()
+            .find(|(xml, _)| xml == key)
+            .map_or(key.as_str(), |(_, json)| json);
+        object.insert(key.to_string(), typed(key, value));
+    }
+
+    if !element.text.is_empty() {
+        let key = match element.name.as_str() {
+            "rating" | "user-rating" => "value",
+            _ => "name",
+        };
+        object.insert(key.to_string(), typed(key, &element.text));
+    }
+
+    for child in &element.children {
+        let repeated = element
+            .children
+            .iter()
+            .filter(|other| other.name == child.name)
+            .nth(1)
+            .is_some();
+        insert_child(&mut object, child, repeated);
+    }
+
+    for (key, default) in defaults(element) {
+        object.entry(*key).or_insert_with(*default);
+    }
+
+    object
+}
+
+/// A field, and the function creating its default value
+type FieldDefault = (&'static str, fn() -> Value);
+
+/// The fields that the XML format leaves out when they are empty, but that the JSON format always has
+fn defaults(element: &Element) -> &'static [FieldDefault] {
+    match element.name.as_str() {
+        "area" | "artist" | "event" | "genre" | "instrument" | "label" | "place" | "recording"
+        | "release" | "release-group" | "series" => &[("disambiguation", empty_string)],
+        "work" => &[
+            ("disambiguation", empty_string),
+            ("attributes", empty_list),
+            ("iswcs", empty_list),
+            ("languages", empty_list),
+        ],
+        "alias" | "life-span" => &[("ended", not_ended)],
+        "medium" => &[("title", empty_string)],
+        "name-credit" => &[("joinphrase", empty_string)],
+        "relation" => &[
+            ("ended", not_ended),
+            ("direction", forward),
+            ("attributes", empty_list),
+            ("attribute-ids", empty_map),
+            ("attribute-values", empty_map),
+            ("source-credit", empty_string),
+            ("target-credit", empty_string),
+        ],
+        _ => &[],
+    }
+}
+
+/// The fields that the JSON format only has on the entity that is looked up, and not on the ones nested in it
+fn lookup_defaults(element: &Element) -> &'static [FieldDefault] {
+    match element.name.as_str() {
+        "artist" | "label" => &[("ipis", empty_list), ("isnis", empty_list)],
+        _ => &[],
+    }
+}
+
+fn empty_string() -> Value {
+    Value::String(String::new())
+}
+
+fn empty_list() -> Value {
+    Value::Array(Vec::new())
+}
+
+fn empty_map() -> Value {
+    Value::Object(Map::new())
+}
+
+fn not_ended() -> Value {
+    Value::Bool(false)
+}
+
+fn forward() -> Value {
+    Value::String("forward".to_string())
+}
+
+/// Insert a child element in the object of its parent. `repeated` is whether its parent has other children of the same name
+fn insert_child(object: &mut Map<String, Value>, child: &Element, repeated: bool) {
+    let Some(base) = child.name.strip_suffix("-list") else {
+        // An element repeated outside of a list holds all of its values, instead of only keeping the last one
+        if repeated {
+            if let Value::Array(values) =
+                object.entry(child.name.clone()).or_insert_with(empty_list)
+            {
+                values.push(element_value(child));
+            }
+            return;
+        }
+
+        // `<gender id="...">Male</gender>` is `"gender": "Male", "gender-id": "..."`
+        if let [(key, id)] = child.attributes.as_slice() {
+            if key == "id" && child.children.is_empty() && !child.text.is_empty() {
+                object.insert(child.name.clone(), Value::String(child.text.clone()));
+                object.insert(format!("{}-id", child.name), Value::String(id.clone()));
+                return;
+            }
+        }
+
+        object.insert(child.name.clone(), element_value(child));
+        return;
+    };
+
+    // The discs of a medium are only counted when they aren't listed
+    let listed_discs = base == "disc" && !child.children.is_empty();
+    if let Some(count) = child.attribute("count").filter(|_| !listed_discs) {
+        object.insert(format!("{base}-count"), typed("count", count));
+    }
+    if let Some(offset) = child.attribute("offset") {
+        object.insert(format!("{base}-offset"), typed("offset", offset));
+    }
+
+    let items = child.children.iter().map(|item| list_item(base, item));
+
+    if base != "relation" {
+        object.insert(plural(base), Value::Array(items.collect()));
+        return;
+    }
+
+    // The relations are split in one list per target type in the XML format, but are a single list in the JSON one
+    let target_type = child.attribute("target-type").unwrap_or_default();
+    let relations = object
+        .entry("relations")
+        .or_insert_with(|| Value::Array(Vec::new()));
+
+    if let Value::Array(relations) = relations {
+        relations.extend(child.children.iter().map(|relation| {
+            let mut relation = relation_value(relation);
+            relation
+                .entry("target-type")
+                .or_insert_with(|| Value::String(target_type.to_string()));
+            Value::Object(relation)
+        }));
+    }
+}
+
+/// Convert an item of a `<...-list>`
+fn list_item(base: &str, item: &Element) -> Value {
+    if STRING_LISTS.contains(&base) {
+        let value = match (item.text.is_empty(), item.attribute("id")) {
+            (true, Some(id)) => id,
+            (_, _) => item.text.as_str(),
+        };
+        return Value::String(value.to_string());
+    }
+
+    if NUMBER_LISTS.contains(&base) {
+        return typed(base, &item.text);
+    }
+
+    if base == "relation" {
+        return Value::Object(relation_value(item));
+    }
+
+    // Items are always objects, even if they only have a text, like aliases
+    Value::Object(object(item))
+}
+
+/// Convert a `<relation>`
+fn relation_value(relation: &Element) -> Map<String, Value> {
+    let mut object = object(relation);
+
+    // The target of the relations to urls is the url itself: `<target id="...">https://...</target>`
+    if let Some(target) = relation.child("target") {
+        object.remove("target");
+
+        if let Some(id) = target.attribute("id") {
+            let mut url = Map::new();
+            url.insert("id".to_string(), Value::String(id.to_string()));
+            url.insert("resource".to_string(), Value::String(target.text.clone()));
+            object.insert("url".to_string(), Value::Object(url));
+        }
+    }
+
+    // The target entity is in snake case in the JSON format
+    if let Some(release_group) = object.remove("release-group") {
+        object.insert("release_group".to_string(), release_group);
+    }
+
+    if let Some(attributes) = relation.child("attribute-list") {
+        let mut ids = Map::new();
+        let mut values = Map::new();
+
+        for attribute in &attributes.children {
+            if let Some(id) = attribute.attribute("type-id") {
+                ids.insert(attribute.text.clone(), Value::String(id.to_string()));
+            }
+            if let Some(value) = attribute.attribute("value") {
+                values.insert(attribute.text.clone(), Value::String(value.to_string()));
+            }
+        }
+
+        object.insert("attribute-ids".to_string(), Value::Object(ids));
+        object.insert("attribute-values".to_string(), Value::Object(values));
+    }
+
+    object
+}
+
+/// Convert a `<name-credit>` of an `<artist-credit>`
+fn name_credit(credit: &Element) -> Value {
+    let mut object = object(credit);
+
+    // The credited name is only given if it differs from the artist's name
+    if !object.contains_key("name") {
+        let name = object
+            .get("artist")
+            .and_then(|artist| artist.get("name"))
+            .cloned();
+        object.insert("name".to_string(), name.unwrap_or_default());
+    }
+
+    Value::Object(object)
+}
+
+/// Convert the text of a field into the JSON type of the field
+fn typed(key: &str, text: &str) -> Value {
+    if BOOL_FIELDS.contains(&key) {
+        return match text {
+            "true" | "primary" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => Value::String(text.to_string()),
+        };
+    }
+
+    if NUMBER_FIELDS.contains(&key) || key.ends_with("-count") || key.ends_with("-offset") {
+        if let Ok(number) = text.parse::<u64>() {
+            return Value::Number(number.into());
+        }
+        if let Some(number) = text.parse::<f64>().ok().and_then(Number::from_f64) {
+            return Value::Number(number);
+        }
+    }
+
+    Value::String(text.to_string())
+}
+
+/// The key of a list in the JSON format
+fn plural(base: &str) -> String {
+    match base {
+        "alias" => "aliases".to_string(),
+        "label-info" | "series" => base.to_string(),
+        "medium" => "media".to_string(),
+        _ => format!("{base}s"),
+    }
+}
+
+/// Error returned when a response in the XML format can't be converted
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum XmlError {
+    /// The body of the response isn't valid XML
+    InvalidXmlError {
+        source: quick_xml::Error,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The document has no content
+    EmptyDocumentError {
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// The root element is neither `<metadata>` nor `<error>`
+    #[snafu(display("Unexpected root element `{name}`"))]
+    UnexpectedRootError {
+        name: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use api_bindium::ureq::http::StatusCode;
+
+    use crate::ParsingError;
+    use crate::api::parser::MusicBrainzParser;
+    use crate::entity::BrowseResult;
+    use crate::entity::artist::Artist;
+    use crate::entity::release::Release;
+    use crate::entity::search::SearchResult;
+
+    fn assert_equivalent<T>(json: &str, xml: &str)
+    where
+        T: serde::de::DeserializeOwned + PartialEq + core::fmt::Debug,
+    {
+        let from_json =
+            MusicBrainzParser::<T>::parse_body(StatusCode::OK, json.as_bytes()).unwrap();
+        let from_xml =
+            MusicBrainzParser::<T>::parse_xml_body(StatusCode::OK, xml.as_bytes()).unwrap();
+
+        assert_eq!(from_json, from_xml);
+    }
+
+    const ARTIST_JSON: &str = r#"{
+        "id": "5b11f4ce-a62d-471e-81fc-a69a8278c7da",
+        "name": "Nirvana",
+        "sort-name": "Nirvana",
+        "type": "Group",
+        "type-id": "e431f5f6-b5d2-343d-8b36-72607fffb74b",
+        "gender": null,
+        "gender-id": null,
+        "country": "US",
+        "disambiguation": "1980s–1990s US grunge band",
+        "area": {
+            "id": "489ce91b-6658-3307-9877-795b68554c98",
+            "name": "United States",
+            "sort-name": "United States",
+            "type": null,
+            "type-id": null,
+            "iso-3166-1-codes": ["US"],
+            "disambiguation": ""
+        },
+        "life-span": { "begin": "1987", "end": "1994-04-05", "ended": true },
+        "ipis": [],
+        "isnis": ["0000000123486830"],
+        "aliases": [
+            {
+                "name": "Nirvana US",
+                "sort-name": "Nirvana US",
+                "type": null,
+                "type-id": null,
+                "locale": null,
+                "primary": null,
+                "begin": null,
+                "end": null,
+                "ended": false
+            }
+        ],
+        "tags": [{ "count": 20, "name": "grunge" }],
+        "rating": { "votes-count": 12, "value": 4.5 },
+        "relations": [
+            {
+                "type": "member of band",
+                "type-id": "5be4c609-9afa-4ea0-910b-12ffb71e3821",
+                "target-type": "artist",
+                "direction": "backward",
+                "begin": "1987",
+                "end": "1994-04-05",
+                "ended": true,
+                "attributes": ["guitar"],
+                "attribute-ids": { "guitar": "63021302-86cd-4aee-80df-2270d54f4978" },
+                "attribute-values": {},
+                "source-credit": "",
+                "target-credit": "",
+                "artist": {
+                    "id": "5c0b2a52-c0a6-4fd8-b3b0-f3fdc2e05e9d",
+                    "name": "Kurt Cobain",
+                    "sort-name": "Cobain, Kurt",
+                    "disambiguation": ""
+                }
+            }
+        ]
+    }"#;
+
+    const ARTIST_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <metadata xmlns="http://musicbrainz.org/ns/mmd-2.0#">
+            <artist id="5b11f4ce-a62d-471e-81fc-a69a8278c7da" type="Group" type-id="e431f5f6-b5d2-343d-8b36-72607fffb74b">
+                <name>Nirvana</name>
+                <sort-name>Nirvana</sort-name>
+                <disambiguation>1980s–1990s US grunge band</disambiguation>
+                <isni-list><isni>0000000123486830</isni></isni-list>
+                <country>US</country>
+                <area id="489ce91b-6658-3307-9877-795b68554c98">
+                    <name>United States</name>
+                    <sort-name>United States</sort-name>
+                    <iso-3166-1-code-list><iso-3166-1-code>US</iso-3166-1-code></iso-3166-1-code-list>
+                </area>
+                <life-span><begin>1987</begin><end>1994-04-05</end><ended>true</ended></life-span>
+                <alias-list count="1"><alias sort-name="Nirvana US">Nirvana US</alias></alias-list>
+                <tag-list><tag count="20"><name>grunge</name></tag></tag-list>
+                <rating votes-count="12">4.5</rating>
+                <relation-list target-type="artist">
+                    <relation type="member of band" type-id="5be4c609-9afa-4ea0-910b-12ffb71e3821">
+                        <target>5c0b2a52-c0a6-4fd8-b3b0-f3fdc2e05e9d</target>
+                        <direction>backward</direction>
+                        <attribute-list>
+                            <attribute type-id="63021302-86cd-4aee-80df-2270d54f4978">guitar</attribute>
+                        </attribute-list>
+                        <begin>1987</begin>
+                        <end>1994-04-05</end>
+                        <ended>true</ended>
+                        <artist id="5c0b2a52-c0a6-4fd8-b3b0-f3fdc2e05e9d">
+                            <name>Kurt Cobain</name>
+                            <sort-name>Cobain, Kurt</sort-name>
+                        </artist>
+                    </relation>
+                </relation-list>
+            </artist>
+        </metadata>"#;
+
+    #[test]
+    fn should_parse_xml_artist_like_json() {
+        assert_equivalent::<Artist>(ARTIST_JSON, ARTIST_XML);
+    }
+
+    const RELEASE_JSON: &str = r#"{
+        "id": "18d4e9b4-9247-4b44-914a-8ddec3502103",
+        "title": "Nevermind",
+        "status": "Official",
+        "status-id": "4e304316-386d-3409-af2e-78857eec5cfe",
+        "date": "1991-09-24",
+        "country": "US",
+        "barcode": "720642442524",
+        "disambiguation": "",
+        "packaging": "Jewel Case",
+        "packaging-id": "ec27701a-4a22-37f4-bfac-6616e0f9750a",
+        "quality": "normal",
+        "text-representation": { "language": "eng", "script": "Latn" },
+        "cover-art-archive": { "artwork": true, "count": 5, "front": true, "back": true, "darkened": false },
+        "artist-credit": [
+            {
+                "name": "Nirvana",
+                "joinphrase": "",
+                "artist": {
+                    "id": "5b11f4ce-a62d-471e-81fc-a69a8278c7da",
+                    "name": "Nirvana",
+                    "sort-name": "Nirvana",
+                    "disambiguation": "1980s–1990s US grunge band"
+                }
+            }
+        ],
+        "release-events": [
+            {
+                "date": "1991-09-24",
+                "area": {
+                    "id": "489ce91b-6658-3307-9877-795b68554c98",
+                    "name": "United States",
+                    "sort-name": "United States",
+                    "iso-3166-1-codes": ["US"],
+                    "disambiguation": ""
+                }
+            }
+        ],
+        "label-info": [
+            {
+                "catalog-number": "DGCD-24425",
+                "label": {
+                    "id": "9e4f36c8-6e2b-4a48-a7f7-d6a14a1d3a1b",
+                    "name": "DGC",
+                    "sort-name": "DGC",
+                    "label-code": 7266,
+                    "disambiguation": ""
+                }
+            }
+        ],
+        "release-group": {
+            "id": "1b022e01-4da6-387b-8658-8678046e4cef",
+            "title": "Nevermind",
+            "primary-type": "Album",
+            "primary-type-id": "f529b476-6e62-324f-b0aa-1f3e33d313fc",
+            "secondary-types": [],
+            "first-release-date": "1991-09-24",
+            "disambiguation": ""
+        },
+        "media": [
+            {
+                "id": "a6e8fc1b-0d9d-3a3e-b5e5-27bb1f6a8c1e",
+                "position": 1,
+                "format": "CD",
+                "format-id": "9712d52a-4509-3d4b-a1a2-67c88c643e31",
+                "title": "",
+                "track-count": 1,
+                "track-offset": 0,
+                "tracks": [
+                    {
+                        "id": "c1f3ac5c-9a65-3b5e-9a2e-cbf5c2a6f9e5",
+                        "number": "1",
+                        "position": 1,
+                        "title": "Smells Like Teen Spirit",
+                        "length": 301000,
+                        "recording": {
+                            "id": "5fb524f1-8cc8-4c04-a921-e34c0a911ea7",
+                            "title": "Smells Like Teen Spirit",
+                            "length": 301000,
+                            "video": false,
+                            "disambiguation": ""
+                        }
+                    }
+                ]
+            }
+        ]
+    }"#;
+
+    const RELEASE_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <metadata xmlns="http://musicbrainz.org/ns/mmd-2.0#">
+            <release id="18d4e9b4-9247-4b44-914a-8ddec3502103">
+                <title>Nevermind</title>
+                <status id="4e304316-386d-3409-af2e-78857eec5cfe">Official</status>
+                <quality>normal</quality>
+                <packaging id="ec27701a-4a22-37f4-bfac-6616e0f9750a">Jewel Case</packaging>
+                <text-representation><language>eng</language><script>Latn</script></text-representation>
+                <artist-credit>
+                    <name-credit>
+                        <artist id="5b11f4ce-a62d-471e-81fc-a69a8278c7da">
+                            <name>Nirvana</name>
+                            <sort-name>Nirvana</sort-name>
+                            <disambiguation>1980s–1990s US grunge band</disambiguation>
+                        </artist>
+                    </name-credit>
+                </artist-credit>
+                <release-group id="1b022e01-4da6-387b-8658-8678046e4cef">
+                    <title>Nevermind</title>
+                    <first-release-date>1991-09-24</first-release-date>
+                    <primary-type id="f529b476-6e62-324f-b0aa-1f3e33d313fc">Album</primary-type>
+                </release-group>
+                <date>1991-09-24</date>
+                <country>US</country>
+                <release-event-list count="1">
+                    <release-event>
+                        <date>1991-09-24</date>
+                        <area id="489ce91b-6658-3307-9877-795b68554c98">
+                            <name>United States</name>
+                            <sort-name>United States</sort-name>
+                            <iso-3166-1-code-list><iso-3166-1-code>US</iso-3166-1-code></iso-3166-1-code-list>
+                        </area>
+                    </release-event>
+                </release-event-list>
+                <barcode>720642442524</barcode>
+                <cover-art-archive>
+                    <artwork>true</artwork><count>5</count><front>true</front><back>true</back><darkened>false</darkened>
+                </cover-art-archive>
+                <label-info-list count="1">
+                    <label-info>
+                        <catalog-number>DGCD-24425</catalog-number>
+                        <label id="9e4f36c8-6e2b-4a48-a7f7-d6a14a1d3a1b">
+                            <name>DGC</name>
+                            <sort-name>DGC</sort-name>
+                            <label-code>7266</label-code>
+                        </label>
+                    </label-info>
+                </label-info-list>
+                <medium-list count="1">
+                    <medium id="a6e8fc1b-0d9d-3a3e-b5e5-27bb1f6a8c1e">
+                        <position>1</position>
+                        <format id="9712d52a-4509-3d4b-a1a2-67c88c643e31">CD</format>
+                        <track-list count="1" offset="0">
+                            <track id="c1f3ac5c-9a65-3b5e-9a2e-cbf5c2a6f9e5">
+                                <position>1</position>
+                                <number>1</number>
+                                <title>Smells Like Teen Spirit</title>
+                                <length>301000</length>
+                                <recording id="5fb524f1-8cc8-4c04-a921-e34c0a911ea7">
+                                    <title>Smells Like Teen Spirit</title>
+                                    <length>301000</length>
+                                    <video>false</video>
+                                </recording>
+                            </track>
+                        </track-list>
+                    </medium>
+                </medium-list>
+            </release>
+        </metadata>"#;
+
+    #[test]
+    fn should_parse_xml_release_like_json() {
+        assert_equivalent::<Release>(RELEASE_JSON, RELEASE_XML);
+    }
+
+    #[test]
+    fn should_parse_xml_browse_and_search_results_like_json() {
+        assert_equivalent::<BrowseResult<Artist>>(
+            r#"{"artist-count": 1, "artist-offset": 0, "artists": [{"id": "5b11f4ce-a62d-471e-81fc-a69a8278c7da", "name": "Nirvana", "sort-name": "Nirvana"}]}"#,
+            r#"<metadata><artist-list count="1" offset="0"><artist id="5b11f4ce-a62d-471e-81fc-a69a8278c7da"><name>Nirvana</name><sort-name>Nirvana</sort-name></artist></artist-list></metadata>"#,
+        );
+
+        assert_equivalent::<SearchResult<Artist>>(
+            r#"{"created": "2024-01-01T00:00:00.000Z", "count": 1, "offset": 0, "artists": [{"id": "5b11f4ce-a62d-471e-81fc-a69a8278c7da", "name": "Nirvana", "sort-name": "Nirvana", "score": 100}]}"#,
+            r#"<metadata xmlns:ns2="http://musicbrainz.org/ns/ext#-2.0" created="2024-01-01T00:00:00.000Z"><artist-list count="1" offset="0"><artist id="5b11f4ce-a62d-471e-81fc-a69a8278c7da" ns2:score="100"><name>Nirvana</name><sort-name>Nirvana</sort-name></artist></artist-list></metadata>"#,
+        );
+    }
+
+    #[test]
+    fn should_keep_all_the_values_of_repeated_elements() {
+        let value = super::to_json(
+            r#"<metadata><work id="22457dc0-ecbf-38f5-9056-11c858530a50"><title>Smells Like Teen Spirit</title><iswc>T-010.306.544-3</iswc><iswc>T-010.306.545-4</iswc></work></metadata>"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            value["iswc"],
+            serde_json::json!(["T-010.306.544-3", "T-010.306.545-4"])
+        );
+        assert_eq!(value["title"], "Smells Like Teen Spirit");
+    }
+
+    #[test]
+    fn should_convert_unknown_elements_like_the_known_ones() {
+        let value = super::to_json(
+            r#"<metadata><artist id="5b11f4ce-a62d-471e-81fc-a69a8278c7da"><name>Nirvana</name><new-field>value</new-field><new-entity id="1" kind="test"><title>Title</title></new-entity><new-item-list count="1"><new-item><name>Item</name></new-item></new-item-list></artist></metadata>"#,
+        )
+        .unwrap();
+
+        assert_eq!(value["new-field"], "value");
+        assert_eq!(
+            value["new-entity"],
+            serde_json::json!({"id": "1", "kind": "test", "title": "Title"})
+        );
+        assert_eq!(value["new-item-count"], 1);
+        assert_eq!(value["new-items"], serde_json::json!([{"name": "Item"}]));
+    }
+
+    #[test]
+    fn should_reject_unknown_roots() {
+        let error = super::to_json("<nirvana><name>Nirvana</name></nirvana>").unwrap_err();
+
+        assert!(
+            matches!(&error, super::XmlError::UnexpectedRootError { name, .. } if name == "nirvana")
+        );
+    }
+
+    #[test]
+    fn should_parse_xml_error() {
+        let error = MusicBrainzParser::<Artist>::parse_xml_body(
+            StatusCode::NOT_FOUND,
+            b"<error><text>Not Found</text><text>For usage, please see: https://musicbrainz.org/development/mmd</text></error>",
+        )
+        .unwrap_err();
+
+        assert!(
+            matches!(&error, ParsingError::MusicBrainzError { source, .. } if source.is_not_found())
+        );
+    }
+}
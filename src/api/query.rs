@@ -1,9 +1,25 @@
+use core::fmt;
+use core::fmt::Write as _;
 use core::marker::PhantomData;
+use core::time::Duration;
 
 use api_bindium::endpoints::EndpointUriBuilder;
 use api_bindium::endpoints::query::EndpointUriBuilderQuery;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use snafu::OptionExt as _;
+use snafu::ResultExt as _;
+use snafu::Snafu;
 
+use crate::APIPath;
 use crate::client::MusicBrainzClient;
+#[cfg(all(feature = "tracing", any(feature = "sync", feature = "async")))]
+use crate::client::metrics::MetricsKey;
+#[cfg(all(feature = "tracing", any(feature = "sync", feature = "async")))]
+use crate::client::metrics::QueryKind;
+use crate::client::scheduler::RequestPriority;
 use crate::entity::Include;
 
 /// The base element of a query
@@ -17,6 +33,12 @@ pub struct Query<T> {
 
     /// The resulting type of the query
     pub(crate) result_type: PhantomData<T>,
+
+    /// The priority of the query in the client's scheduler
+    pub(crate) priority: RequestPriority,
+
+    /// The maximum time the query can take. Overrides the client's timeout
+    pub(crate) timeout: Option<Duration>,
 }
 
 impl<T> Query<T> {
@@ -26,6 +48,16 @@ impl<T> Query<T> {
         self
     }
 
+    /// The value of the `inc` parameter, if the query has includes
+    pub(crate) fn include_param(&self) -> Option<String> {
+        if self.include.is_empty() {
+            return None;
+        }
+
+        let includes: Vec<&str> = self.include.iter().map(Include::as_str).collect();
+        Some(includes.join("+"))
+    }
+
     /// Create the full url path of the query
     pub(crate) fn get_endpoint(
         &self,
@@ -37,22 +69,381 @@ impl<T> Query<T> {
             .add_path_fragment("ws/2")
             .add_path_fragment(&self.path)
             .query()
-            .add_parameter("fmt", "json");
+            .add_parameter("fmt", client.response_format.as_str());
 
-        // If we don't have includes, let's return early
-        if self.include.is_empty() {
-            return url;
+        match self.include_param() {
+            Some(incl) => url.add_parameter("inc", incl),
+            None => url,
+        }
+    }
+
+    /// Create the span covering the execution of the query.
+    ///
+    /// `query` describes what is requested, like the lucene query of a search. Lookups leave it to `None` for the id in the path.
+    #[cfg(all(feature = "tracing", any(feature = "sync", feature = "async")))]
+    pub(crate) fn span(
+        &self,
+        kind: QueryKind,
+        query: Option<&str>,
+        limit: Option<u8>,
+        offset: Option<u16>,
+    ) -> tracing::Span {
+        use crate::client::spans::path_id;
+        use crate::client::spans::query_span;
+
+        query_span(
+            &MetricsKey::new(&self.path, kind),
+            query.unwrap_or_else(|| path_id(&self.path)),
+            &self.include,
+            limit,
+            offset,
+        )
+    }
+
+    /// Write the textual form of the query: its path, followed by the includes and the other parameters
+    pub(crate) fn write_text(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        params: &[(&str, String)],
+    ) -> fmt::Result {
+        f.write_str(&self.path)?;
+
+        // The `+` separating the includes is kept, but the ones of the other values are encoded
+        let include = self.include_param().map(|incl| ("inc", incl));
+        let params = params
+            .iter()
+            .map(|(name, value)| (*name, encode_param(value)));
+        for (i, (name, value)) in include.into_iter().chain(params).enumerate() {
+            let separator = if i == 0 { '?' } else { '&' };
+            write!(f, "{separator}{name}={value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Query<T>
+where
+    T: APIPath,
+{
+    /// Parse the textual form of a query, as written by its `Display` implementation.
+    ///
+    /// The `inc` parameter is read into the includes, and the other parameters are returned.
+    pub(crate) fn parse_text(text: &str) -> Result<(Self, Vec<(String, String)>), QueryParseError> {
+        let (path, params) = text.split_once('?').unwrap_or((text, ""));
+
+        let mut query = Self::from_path(path.to_string())?;
+        let mut others = Vec::new();
+
+        for param in params.split('&').filter(|param| !param.is_empty()) {
+            let (name, value) = param.split_once('=').unwrap_or((param, ""));
+            let value = decode_param(value)?;
+
+            if name == "inc" {
+                for include in value.split('+') {
+                    query.include.push(Include::from_param(include).context(
+                        UnknownIncludeSnafu {
+                            include: include.to_string(),
+                        },
+                    )?);
+                }
+            } else {
+                others.push((name.to_string(), value));
+            }
         }
 
-        let mut incl = String::new();
+        Ok((query, others))
+    }
 
-        for inc in &self.include {
-            incl.push_str(inc.as_str());
-            if Some(inc) != self.include.last() {
-                incl.push('+');
+    /// Create a query for the path, checking that it targets the entity of the query
+    fn from_path(path: String) -> Result<Self, QueryParseError> {
+        let is_entity_path = path
+            .strip_prefix(T::path())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+
+        if !is_entity_path {
+            return WrongEntitySnafu {
+                expected: T::path(),
+                path,
             }
+            .fail();
+        }
+
+        Ok(Self {
+            path,
+            include: Vec::new(),
+            result_type: PhantomData,
+            priority: RequestPriority::default(),
+            timeout: None,
+        })
+    }
+}
+
+/// The serialized form of a [`Query`]
+#[derive(Serialize, Deserialize)]
+struct QueryRepr {
+    /// The api path of the query, starting with the type of entity queried
+    path: String,
+    #[serde(default)]
+    include: Vec<Include>,
+    #[serde(default)]
+    priority: RequestPriority,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timeout: Option<Duration>,
+}
+
+impl<T> Serialize for Query<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        QueryRepr {
+            path: self.path.clone(),
+            include: self.include.clone(),
+            priority: self.priority,
+            timeout: self.timeout,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Query<T>
+where
+    T: APIPath,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let repr = QueryRepr::deserialize(deserializer)?;
+
+        let mut query = Self::from_path(repr.path).map_err(serde::de::Error::custom)?;
+        query.include = repr.include;
+        query.priority = repr.priority;
+        query.timeout = repr.timeout;
+        Ok(query)
+    }
+}
+
+/// Parse the value of a numeric parameter of the textual form of a query
+pub(crate) fn parse_number<N: core::str::FromStr<Err = core::num::ParseIntError>>(
+    name: &str,
+    value: &str,
+) -> Result<N, QueryParseError> {
+    value.parse().context(InvalidNumberSnafu {
+        name: name.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// Percent encode all the characters of the value that aren't unreserved in urls
+fn encode_param(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
         }
+    }
+
+    encoded
+}
+
+fn decode_param(value: &str) -> Result<String, QueryParseError> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut chars = value.bytes();
+
+    while let Some(byte) = chars.next() {
+        if byte != b'%' {
+            bytes.push(byte);
+            continue;
+        }
+
+        let hex = [chars.next(), chars.next()];
+        let decoded = match hex {
+            [Some(high), Some(low)] => core::str::from_utf8(&[high, low])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        bytes.push(decoded.context(InvalidEncodingSnafu {
+            value: value.to_string(),
+        })?);
+    }
+
+    String::from_utf8(bytes).ok().context(InvalidEncodingSnafu {
+        value: value.to_string(),
+    })
+}
+
+/// Error returned when reading a query from its serialized or textual form
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum QueryParseError {
+    /// The query is for another type of entity
+    #[snafu(display("Expected a query of `{expected}`, but got the path `{path}`"))]
+    WrongEntityError {
+        expected: &'static str,
+        path: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("Unknown include `{include}`"))]
+    UnknownIncludeError {
+        include: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("Unknown parameter `{name}`"))]
+    UnknownParameterError {
+        name: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("The parameter `{name}` should be a number, but is `{value}`"))]
+    InvalidNumberError {
+        name: String,
+        value: String,
+        source: core::num::ParseIntError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("The parameter value `{value}` isn't correctly percent encoded"))]
+    InvalidEncodingError {
+        value: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Browse as _;
+    use crate::BrowseQuery;
+    use crate::Fetch as _;
+    use crate::FetchQuery;
+    use crate::QueryParseError;
+    use crate::Search as _;
+    use crate::SearchQuery;
+    use crate::client::scheduler::RequestPriority;
+    use crate::entity::artist::Artist;
+    use crate::entity::release::Release;
+
+    #[test]
+    fn should_serialize_queries() {
+        let fetch = Artist::fetch()
+            .id("5b11f4ce-a62d-471e-81fc-a69a8278c7da")
+            .with_aliases()
+            .with_tags()
+            .priority(RequestPriority::Background);
+
+        let json = serde_json::to_value(&fetch).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "path": "artist/5b11f4ce-a62d-471e-81fc-a69a8278c7da",
+                "include": ["aliases", "tags"],
+                "priority": "background",
+            })
+        );
+
+        let restored: FetchQuery<Artist> = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.to_string(), fetch.to_string());
+        assert_eq!(restored.0.priority, RequestPriority::Background);
+
+        let browse = Release::browse()
+            .by_label("47e718e1-7ee4-460c-b1cc-1192a841c6e5")
+            .limit(100)
+            .offset(200);
+
+        let restored: BrowseQuery<Release> =
+            serde_json::from_str(&serde_json::to_string(&browse).unwrap()).unwrap();
+        assert_eq!(restored.to_string(), browse.to_string());
+    }
+
+    #[test]
+    fn should_read_queries_from_their_textual_form() {
+        let fetch = Artist::fetch()
+            .id("5b11f4ce-a62d-471e-81fc-a69a8278c7da")
+            .with_aliases();
+        assert_eq!(
+            fetch.to_string(),
+            "artist/5b11f4ce-a62d-471e-81fc-a69a8278c7da?inc=aliases"
+        );
+
+        let browse = Release::browse()
+            .by_label("47e718e1-7ee4-460c-b1cc-1192a841c6e5")
+            .limit(100);
+        let text = browse.to_string();
+        assert_eq!(
+            text,
+            "release?label=47e718e1-7ee4-460c-b1cc-1192a841c6e5&limit=100"
+        );
+        let restored: BrowseQuery<Release> = text.parse().unwrap();
+        assert_eq!(restored.filter_entity, "label");
+        assert_eq!(restored.limit, Some(100));
+
+        let search = Artist::search(r#"artist:"Miles Davis" AND country:US"#.to_string()).limit(10);
+        let text = search.to_string();
+        assert_eq!(
+            text,
+            "artist?query=artist%3A%22Miles%20Davis%22%20AND%20country%3AUS&limit=10"
+        );
+        let restored: SearchQuery<Artist> = text.parse().unwrap();
+        assert_eq!(
+            restored.search_query,
+            r#"artist:"Miles Davis" AND country:US"#
+        );
+        assert_eq!(restored.to_string(), text);
+
+        let search = Artist::search("artist:C++".to_string());
+        let text = search.to_string();
+        assert_eq!(text, "artist?query=artist%3AC%2B%2B");
+        let restored: SearchQuery<Artist> = text.parse().unwrap();
+        assert_eq!(restored.search_query, "artist:C++");
+    }
 
-        url.add_parameter("inc", incl)
+    #[test]
+    fn should_reject_queries_of_other_entities() {
+        assert!(matches!(
+            "release/76df3287-6cda-33eb-8e9a-044b5e15ffdd".parse::<FetchQuery<Artist>>(),
+            Err(QueryParseError::WrongEntityError { .. })
+        ));
+        assert!(matches!(
+            "artist/5b11f4ce-a62d-471e-81fc-a69a8278c7da?inc=nope".parse::<FetchQuery<Artist>>(),
+            Err(QueryParseError::UnknownIncludeError { .. })
+        ));
+        assert!(
+            serde_json::from_str::<FetchQuery<Artist>>(
+                r#"{"path": "release/76df3287-6cda-33eb-8e9a-044b5e15ffdd"}"#
+            )
+            .is_err()
+        );
     }
 }
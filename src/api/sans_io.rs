@@ -0,0 +1,110 @@
+use api_bindium::endpoints::UriBuilderError;
+use api_bindium::ureq::http;
+use api_bindium::ureq::http::Request;
+use api_bindium::ureq::http::Uri;
+use snafu::ResultExt as _;
+use snafu::Snafu;
+
+use crate::MusicBrainzClient;
+use crate::api::parser::ResponseFormat;
+
+/// Create a `GET` request to the uri, accepting the format, with the authentication headers of the client.
+///
+/// The `User-Agent` header isn't set, as it is part of the HTTP client's configuration.
+pub(crate) fn http_request(
+    client: &MusicBrainzClient,
+    uri: Uri,
+    format: ResponseFormat,
+) -> Result<Request<()>, HttpRequestError> {
+    let mut request = Request::get(&uri).header(http::header::ACCEPT, format.mime_type());
+
+    for (name, value) in client.auth_headers(&uri).unwrap_or_default() {
+        request = request.header(name, value);
+    }
+
+    request.body(()).context(InvalidRequestSnafu)
+}
+
+/// Error returned when turning a query into an [`http::Request`]
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum HttpRequestError {
+    InvalidUriError {
+        source: UriBuilderError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// One of the headers has an invalid value
+    InvalidRequestError {
+        source: http::Error,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use api_bindium::ureq::http::StatusCode;
+
+    use super::*;
+    use crate::ParsingError;
+    use crate::api::parser::MusicBrainzParser;
+    use crate::entity::api::MusicbrainzError;
+
+    #[test]
+    fn should_build_authenticated_request() {
+        let client = MusicBrainzClient::builder()
+            .bearer_token("secret_token".to_string())
+            .build();
+
+        let request = http_request(
+            &client,
+            Uri::from_static("https://musicbrainz.org/ws/2/artist"),
+            ResponseFormat::Json,
+        )
+        .unwrap();
+
+        assert_eq!(request.method(), http::Method::GET);
+        assert_eq!(
+            request.headers()[http::header::AUTHORIZATION],
+            "Bearer secret_token"
+        );
+    }
+
+    #[test]
+    fn should_parse_response_body() {
+        type Parser = MusicBrainzParser<MusicbrainzError>;
+
+        let error = br#"{"error": "Not Found", "help": "For usage, please see: https://musicbrainz.org/development/mmd"}"#;
+
+        assert!(
+            Parser::parse_body(StatusCode::NOT_FOUND, error)
+                .unwrap_err()
+                .as_musicbrainz_error()
+                .is_some_and(MusicbrainzError::is_not_found)
+        );
+        assert!(matches!(
+            Parser::parse_body(StatusCode::BAD_GATEWAY, b"<html></html>"),
+            Err(ParsingError::HttpStatusError { status: 502, .. })
+        ));
+        #[cfg(not(feature = "simd-json"))]
+        assert!(matches!(
+            Parser::parse_body(StatusCode::OK, b"{"),
+            Err(ParsingError::JsonError { .. })
+        ));
+        #[cfg(feature = "simd-json")]
+        assert!(matches!(
+            Parser::parse_body(StatusCode::OK, b"{"),
+            Err(ParsingError::SimdJsonError { .. })
+        ));
+    }
+}
@@ -1,16 +1,37 @@
+use core::fmt;
 use core::marker::PhantomData;
+use core::str::FromStr;
+use core::time::Duration;
 
 use api_bindium::ApiRequest;
 use api_bindium::endpoints::UriBuilderError;
+use api_bindium::ureq::http::HeaderMap;
+use api_bindium::ureq::http::Request;
+use api_bindium::ureq::http::StatusCode;
 use api_bindium::ureq::http::Uri;
+use serde::Deserialize;
+use serde::Serialize;
 use serde::de::DeserializeOwned;
 
 use crate::APIPath;
 use crate::MusicBrainzClient;
+use crate::ParsingError;
 #[cfg(any(feature = "sync", feature = "async"))]
 use crate::api::ApiEndpointError;
 use crate::api::parser::MusicBrainzParser;
 use crate::api::query::Query;
+use crate::api::query::QueryParseError;
+use crate::api::query::UnknownParameterSnafu;
+use crate::api::query::parse_number;
+use crate::api::sans_io::HttpRequestError;
+use crate::api::sans_io::http_request;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::metrics::MetricsKey;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::metrics::QueryKind;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::mirrors::Served;
+use crate::client::scheduler::RequestPriority;
 use crate::entity::search::SearchResult;
 use crate::entity::search::Searchable;
 
@@ -46,36 +67,98 @@ use crate::entity::search::Searchable;
 /// #   Ok(())
 /// # }
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = "T: APIPath"))]
 pub struct SearchQuery<T> {
+    #[serde(flatten)]
     pub inner: Query<T>,
 
     /// The number of results to offset the query by
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub offset: Option<u16>,
 
     /// The number of results to query
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub limit: Option<u8>,
 
     /// The search query in lucene
+    #[serde(rename = "query")]
     pub search_query: String,
 }
 
+/// The textual form of the query: the path and parameters of its request, without the format.
+/// For example, `artist?query=artist%3A%22Miles%20Davis%22&limit=10`
+impl<T> fmt::Display for SearchQuery<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut params = vec![("query", self.search_query.clone())];
+        params.extend(self.limit.map(|limit| ("limit", limit.to_string())));
+        params.extend(self.offset.map(|offset| ("offset", offset.to_string())));
+
+        self.inner.write_text(f, &params)
+    }
+}
+
+impl<T> FromStr for SearchQuery<T>
+where
+    T: APIPath,
+{
+    type Err = QueryParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (inner, params) = Query::parse_text(text)?;
+        let mut query = Self {
+            inner,
+            offset: None,
+            limit: None,
+            search_query: String::new(),
+        };
+
+        for (name, value) in params {
+            match name.as_str() {
+                "query" => query.search_query = value,
+                "limit" => query.limit = Some(parse_number(&name, &value)?),
+                "offset" => query.offset = Some(parse_number(&name, &value)?),
+                _ => return UnknownParameterSnafu { name }.fail(),
+            }
+        }
+
+        Ok(query)
+    }
+}
+
 impl<T> SearchQuery<T>
 where
     T: Search + Clone,
 {
     /// An integer value defining how many entries should be returned. Only values between 1 and 100 (both inclusive) are allowed. If not given, this defaults to 25.
-    pub fn limit(&mut self, limit: u8) -> &mut Self {
+    #[must_use]
+    pub fn limit(mut self, limit: u8) -> Self {
         self.limit = Some(limit);
         self
     }
 
     /// Return search results starting at a given offset. Used for paging through more than one page of results.
-    pub fn offset(&mut self, offset: u16) -> &mut Self {
+    #[must_use]
+    pub fn offset(mut self, offset: u16) -> Self {
         self.offset = Some(offset);
         self
     }
 
+    /// Set the priority of the request in the client's [`RequestScheduler`](crate::client::scheduler::RequestScheduler)
+    #[must_use]
+    pub fn priority(mut self, priority: RequestPriority) -> Self {
+        self.inner.priority = priority;
+        self
+    }
+
+    /// Set the maximum time the query can take, including the time spent waiting for the scheduler and rate limiter.
+    /// This overrides the client's timeout.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.inner.timeout = Some(timeout);
+        self
+    }
+
     // === Request Creation ===
 
     fn create_url(&self, client: &MusicBrainzClient) -> Result<Uri, UriBuilderError> {
@@ -92,6 +175,11 @@ where
         url.to_uri()
     }
 
+    /// The exact url the query requests with this client, for debugging or to build cache keys
+    pub fn to_url(&self, client: &crate::MusicBrainzClient) -> Result<String, UriBuilderError> {
+        self.create_url(client).map(|uri| uri.to_string())
+    }
+
     /// Turn the query into an [`api_bindium::ApiRequest`]
     pub fn as_api_request(
         &self,
@@ -100,81 +188,214 @@ where
     where
         T: Searchable + DeserializeOwned,
     {
+        let uri = self.create_url(client)?;
         Ok(ApiRequest::builder()
-            .uri(self.create_url(client)?)
+            .maybe_headers(client.auth_headers(&uri))
+            .uri(uri)
             .verb(api_bindium::HTTPVerb::Get)
             .parser(MusicBrainzParser::default())
             .build())
     }
 
+    /// Turn the query into an [`http::Request`](Request) that can be sent by any HTTP client.
+    ///
+    /// The `User-Agent` header needs to be set by the HTTP client. The response can then be parsed with [`Self::parse_response`].
+    pub fn to_http_request(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Request<()>, HttpRequestError> {
+        use snafu::ResultExt as _;
+
+        use crate::api::sans_io::InvalidUriSnafu;
+
+        http_request(
+            client,
+            self.create_url(client).context(InvalidUriSnafu)?,
+            client.response_format,
+        )
+    }
+
+    /// Parse the response to the request created by [`Self::to_http_request`]
+    pub fn parse_response(
+        &self,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<SearchResult<T>, ParsingError>
+    where
+        T: Searchable + DeserializeOwned,
+    {
+        MusicBrainzParser::parse_response(status, headers, body)
+    }
+
     #[cfg(feature = "sync")]
-    pub fn execute(&mut self) -> Result<SearchResult<T>, ApiEndpointError>
+    pub fn execute(&self) -> Result<SearchResult<T>, ApiEndpointError>
     where
-        T: Search + Searchable + DeserializeOwned + Sync,
+        T: Search + Searchable + DeserializeOwned + Send + Sync + 'static,
     {
-        use crate::client::MUSICBRAINZ_CLIENT;
+        use crate::client::global::global_client;
 
-        self.execute_with_client(&MUSICBRAINZ_CLIENT)
+        self.execute_with_client(&global_client())
     }
 
     /// Execute the query with a specific client
     #[cfg(feature = "sync")]
     pub fn execute_with_client(
-        &mut self,
+        &self,
         client: &crate::MusicBrainzClient,
     ) -> Result<SearchResult<T>, ApiEndpointError>
     where
-        T: Search + Searchable + DeserializeOwned + Sync,
+        T: Search + Searchable + DeserializeOwned + Send + Sync + 'static,
     {
-        use snafu::ResultExt as _;
+        self.execute_served(client).map(Served::into_inner)
+    }
 
-        use crate::api::ApiRequestSnafu;
-        use crate::api::InvalidUriSnafu;
-        use crate::api::ParsingSnafu;
+    /// Execute the query with a specific client, and return which server answered it
+    #[cfg(feature = "sync")]
+    pub fn execute_served(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Served<SearchResult<T>>, ApiEndpointError>
+    where
+        T: Search + Searchable + DeserializeOwned + Send + Sync + 'static,
+    {
+        use crate::client::timeout::run_with_timeout;
+
+        client.check_dry_run(|| self.to_http_request(client))?;
 
-        self.as_api_request(client)
-            .context(InvalidUriSnafu)?
-            .send(&client.api_client)
-            .context(ApiRequestSnafu)?
-            .parse()
-            .context(ParsingSnafu)
+        #[cfg(feature = "tracing")]
+        let _span = self
+            .inner
+            .span(
+                QueryKind::Search,
+                Some(&self.search_query),
+                self.limit,
+                self.offset,
+            )
+            .entered();
+
+        let result = match self.inner.timeout.or(client.timeout) {
+            Some(timeout) => run_with_timeout(client, timeout, |client| self.send_blocking(client)),
+            None => self.send_blocking(client),
+        };
+
+        client.record_result(&self.metrics_key(), &result);
+        result
+    }
+
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn metrics_key(&self) -> MetricsKey {
+        MetricsKey::new(&self.inner.path, QueryKind::Search)
+    }
+
+    #[cfg(feature = "sync")]
+    fn send_blocking(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Served<SearchResult<T>>, ApiEndpointError>
+    where
+        T: Searchable + DeserializeOwned + Sync,
+    {
+        let key = self.metrics_key();
+        client.with_failover(&key, |client| {
+            client.send_request(
+                &key,
+                self.inner.priority,
+                self.to_http_request(client),
+                |status, headers, body| {
+                    MusicBrainzParser::parse_response_reader(status, headers, body)
+                },
+            )
+        })
     }
 
     #[cfg(feature = "async")]
-    pub async fn execute_async(&mut self) -> Result<SearchResult<T>, ApiEndpointError>
+    pub async fn execute_async(&self) -> Result<SearchResult<T>, ApiEndpointError>
     where
-        T: Search + Searchable + DeserializeOwned + Sync,
+        T: Search + Searchable + DeserializeOwned + Send + Sync + 'static,
     {
-        use crate::client::MUSICBRAINZ_CLIENT;
+        use crate::client::global::global_client;
 
-        self.execute_with_client_async(&MUSICBRAINZ_CLIENT).await
+        self.execute_with_client_async(&global_client()).await
     }
 
     /// Execute the query with a specific client
     #[cfg(feature = "async")]
     pub async fn execute_with_client_async(
-        &mut self,
+        &self,
         client: &crate::MusicBrainzClient,
     ) -> Result<SearchResult<T>, ApiEndpointError>
     where
-        T: Search + Searchable + DeserializeOwned + Sync,
+        T: Search + Searchable + DeserializeOwned + Send + Sync + 'static,
+    {
+        self.execute_served_async(client)
+            .await
+            .map(Served::into_inner)
+    }
+
+    /// Execute the query with a specific client, and return which server answered it
+    #[cfg(feature = "async")]
+    pub async fn execute_served_async(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Served<SearchResult<T>>, ApiEndpointError>
+    where
+        T: Search + Searchable + DeserializeOwned + Send + Sync + 'static,
     {
         use snafu::ResultExt as _;
 
-        use crate::api::ApiRequestSnafu;
         use crate::api::InvalidUriSnafu;
-        use crate::api::ParsingSnafu;
+        use crate::client::timeout::with_timeout;
 
-        self.as_api_request(client)
-            .context(InvalidUriSnafu)?
-            .send_async(&client.api_client)
-            .await
-            .context(ApiRequestSnafu)?
-            .parse()
-            .context(ParsingSnafu)
+        client.check_dry_run(|| self.to_http_request(client))?;
+
+        #[cfg(feature = "tracing")]
+        let span = self.inner.span(
+            QueryKind::Search,
+            Some(&self.search_query),
+            self.limit,
+            self.offset,
+        );
+
+        let key = self.metrics_key();
+        let execution = async {
+            let uri = self.create_url(client).context(InvalidUriSnafu)?;
+            let query = &*self;
+            let key = &key;
+            let request = client.coalesce(key, uri.to_string(), async {
+                let endpoints = client.endpoint_clients();
+
+                client
+                    .with_failover_async(key, &endpoints, |client| async move {
+                        client
+                            .send_request_async(
+                                key,
+                                query.inner.priority,
+                                query.to_http_request(client),
+                                |status, headers, body| {
+                                    MusicBrainzParser::parse_response_reader(status, headers, body)
+                                },
+                            )
+                            .await
+                    })
+                    .await
+            });
+
+            with_timeout(self.inner.timeout.or(client.timeout), request).await
+        };
+
+        #[cfg(feature = "tracing")]
+        let execution = tracing::Instrument::instrument(execution, span);
+
+        let result = execution.await;
+        client.record_result(&key, &result);
+        result
     }
 }
 
+#[cfg(feature = "async")]
+crate::api::into_future::impl_into_future!(SearchQuery<T>, Result<SearchResult<T>, ApiEndpointError>, T: Search + Searchable + Clone + DeserializeOwned + Send + Sync + 'static);
+
 /// Implemented by all searchable entities (see [`SearchQuery`])
 pub trait Search {
     fn search(query: String) -> SearchQuery<Self>
@@ -185,6 +406,8 @@ pub trait Search {
             inner: Query {
                 path: Self::path().to_string(),
                 result_type: PhantomData,
+                priority: RequestPriority::default(),
+                timeout: None,
                 include: vec![],
             },
             search_query: query,
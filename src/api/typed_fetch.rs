@@ -0,0 +1,410 @@
+//! Fetch queries tracking their includes in the type of the result.
+//!
+//! Each include method of a [`TypedFetchQuery`] adds the field it guarantees to the type of the query.
+//! The fetched entity is then wrapped in an [`Included`], whose accessors for these fields don't return an `Option`.
+//! Forgetting an include is caught at compile time:
+//!
+//! ```rust
+//! # use musicbrainz_rs::prelude::*;
+//! # #[cfg(feature = "sync")]
+//! # fn main() -> Result<(), musicbrainz_rs::ApiEndpointError> {
+//! # use musicbrainz_rs::entity::release::Release;
+//! let justice_cross = Release::fetch()
+//!         .id("4642ee19-7790-3c8d-ab5e-d133de942db6")
+//!         .typed()
+//!         .with_recordings()
+//!         .with_labels()
+//!         .execute()?;
+//!
+//! assert!(!justice_cross.media().is_empty());
+//! assert!(!justice_cross.label_info().is_empty());
+//! // `justice_cross.aliases()` doesn't compile, as the aliases weren't included
+//! #   Ok(())
+//! # }
+//! # #[cfg(not(feature = "sync"))]
+//! # fn main() {}
+//! ```
+
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Deref;
+
+use api_bindium::ureq::http::HeaderMap;
+use api_bindium::ureq::http::StatusCode;
+use serde::de::DeserializeOwned;
+
+use crate::FetchQuery;
+use crate::ParsingError;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::api::ApiEndpointError;
+use crate::api::parser::parsing_error::MissingIncludeSnafu;
+use crate::entity::Include;
+
+/// The fields guaranteed by the includes of a [`TypedFetchQuery`]
+pub mod fields {
+    pub enum Aliases {}
+    pub enum ArtistCredit {}
+    pub enum Genres {}
+    pub enum Isrcs {}
+    pub enum LabelInfo {}
+    pub enum Media {}
+    pub enum Rating {}
+    pub enum Recordings {}
+    pub enum ReleaseGroup {}
+    pub enum ReleaseGroups {}
+    pub enum Releases {}
+    pub enum Tags {}
+    pub enum Works {}
+}
+
+/// The field is the first one of the list
+pub struct Here;
+
+/// The field is further in the list
+pub struct There<Index>(PhantomData<Index>);
+
+/// Implemented by the lists of fields containing `Field`.
+///
+/// The `Index` is inferred by the compiler, and never needs to be written.
+/// A field included twice makes it ambiguous, so each field should only be included once.
+pub trait HasField<Field, Index> {}
+
+impl<Field, Tail> HasField<Field, Here> for (Field, Tail) {}
+
+impl<Field, Head, Tail, Index> HasField<Field, There<Index>> for (Head, Tail) where
+    Tail: HasField<Field, Index>
+{
+}
+
+/// A field of the entity `T` that an include guarantees to be present
+pub trait IncludedField<T> {
+    /// The name of the field in the entity
+    const NAME: &'static str;
+
+    type Value;
+
+    fn get(entity: &T) -> Option<&Self::Value>;
+}
+
+/// A list of fields that the response must contain
+pub trait FieldList<T> {
+    /// The name of the first field missing from the entity, if any
+    fn missing(entity: &T) -> Option<&'static str>;
+}
+
+impl<T> FieldList<T> for () {
+    fn missing(_entity: &T) -> Option<&'static str> {
+        None
+    }
+}
+
+impl<T, Head, Tail> FieldList<T> for (Head, Tail)
+where
+    Head: IncludedField<T>,
+    Tail: FieldList<T>,
+{
+    fn missing(entity: &T) -> Option<&'static str> {
+        match Head::get(entity) {
+            Some(_) => Tail::missing(entity),
+            None => Some(Head::NAME),
+        }
+    }
+}
+
+/// A [`FetchQuery`] whose includes are part of its type. See the [module documentation](self).
+///
+/// Created with [`FetchQuery::typed`]. The includes already set on the original query are still requested,
+/// but don't get any accessors on the result.
+pub struct TypedFetchQuery<T, Fields = ()> {
+    query: FetchQuery<T>,
+    fields: PhantomData<fn() -> Fields>,
+}
+
+impl<T> FetchQuery<T>
+where
+    T: Clone,
+{
+    /// Switch to the typed API, where the include methods change the type of the result
+    pub fn typed(&self) -> TypedFetchQuery<T> {
+        TypedFetchQuery {
+            query: self.clone(),
+            fields: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone, Fields> Clone for TypedFetchQuery<T, Fields> {
+    fn clone(&self) -> Self {
+        Self {
+            query: self.query.clone(),
+            fields: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug, Fields> fmt::Debug for TypedFetchQuery<T, Fields> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TypedFetchQuery").field(&self.query).finish()
+    }
+}
+
+impl<T, Fields> TypedFetchQuery<T, Fields>
+where
+    T: Clone,
+    Fields: FieldList<T>,
+{
+    /// The underlying untyped query
+    pub fn query(&self) -> &FetchQuery<T> {
+        &self.query
+    }
+
+    pub fn into_query(self) -> FetchQuery<T> {
+        self.query
+    }
+
+    /// Request an include, and add the field it guarantees to the type of the query
+    pub(crate) fn include<Field>(
+        mut self,
+        include: Include,
+    ) -> TypedFetchQuery<T, (Field, Fields)> {
+        self.query.0.include(include);
+        TypedFetchQuery {
+            query: self.query,
+            fields: PhantomData,
+        }
+    }
+
+    /// Parse the response to the request created by [`FetchQuery::to_http_request`]
+    pub fn parse_response(
+        &self,
+        status: StatusCode,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<Included<T, Fields>, ParsingError>
+    where
+        T: DeserializeOwned,
+    {
+        Included::new(self.query.parse_response(status, headers, body)?)
+    }
+
+    #[cfg(feature = "sync")]
+    pub fn execute(&self) -> Result<Included<T, Fields>, ApiEndpointError>
+    where
+        T: crate::Fetch + DeserializeOwned + Send + Sync + 'static,
+    {
+        Self::check(self.query.execute())
+    }
+
+    /// Execute the query with a specific client
+    #[cfg(feature = "sync")]
+    pub fn execute_with_client(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Included<T, Fields>, ApiEndpointError>
+    where
+        T: crate::Fetch + DeserializeOwned + Send + Sync + 'static,
+    {
+        Self::check(self.query.execute_with_client(client))
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn execute_async(&self) -> Result<Included<T, Fields>, ApiEndpointError>
+    where
+        T: crate::Fetch + DeserializeOwned + Send + Sync + 'static,
+    {
+        Self::check(self.query.execute_async().await)
+    }
+
+    /// Execute the query with a specific client
+    #[cfg(feature = "async")]
+    pub async fn execute_with_client_async(
+        &self,
+        client: &crate::MusicBrainzClient,
+    ) -> Result<Included<T, Fields>, ApiEndpointError>
+    where
+        T: crate::Fetch + DeserializeOwned + Send + Sync + 'static,
+    {
+        Self::check(self.query.execute_with_client_async(client).await)
+    }
+
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn check(result: Result<T, ApiEndpointError>) -> Result<Included<T, Fields>, ApiEndpointError> {
+        use snafu::ResultExt as _;
+
+        use crate::api::ParsingSnafu;
+
+        Included::new(result?).context(ParsingSnafu)
+    }
+}
+
+/// An entity fetched by a [`TypedFetchQuery`], with accessors for the fields guaranteed by its includes.
+///
+/// It dereferences to the entity, so all the other fields are still available.
+pub struct Included<T, Fields> {
+    entity: T,
+    fields: PhantomData<fn() -> Fields>,
+}
+
+impl<T, Fields> Included<T, Fields>
+where
+    Fields: FieldList<T>,
+{
+    /// Wrap the entity, if it has all the fields of the list
+    pub fn new(entity: T) -> Result<Self, ParsingError> {
+        if let Some(field) = Fields::missing(&entity) {
+            return MissingIncludeSnafu { field }.fail();
+        }
+
+        Ok(Self {
+            entity,
+            fields: PhantomData,
+        })
+    }
+}
+
+impl<T, Fields> Included<T, Fields> {
+    pub fn into_inner(self) -> T {
+        self.entity
+    }
+
+    /// The value of a field guaranteed by the includes
+    pub fn get<Field, Index>(&self) -> &Field::Value
+    where
+        Field: IncludedField<T>,
+        Fields: HasField<Field, Index>,
+    {
+        Field::get(&self.entity)
+            .expect("The fields of the list are checked when wrapping the entity")
+    }
+}
+
+impl<T, Fields> Deref for Included<T, Fields> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.entity
+    }
+}
+
+impl<T, Fields> AsRef<T> for Included<T, Fields> {
+    fn as_ref(&self) -> &T {
+        &self.entity
+    }
+}
+
+impl<T: Clone, Fields> Clone for Included<T, Fields> {
+    fn clone(&self) -> Self {
+        Self {
+            entity: self.entity.clone(),
+            fields: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug, Fields> fmt::Debug for Included<T, Fields> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.entity.fmt(f)
+    }
+}
+
+impl<T: PartialEq, Fields> PartialEq for Included<T, Fields> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entity == other.entity
+    }
+}
+
+/// Implement the typed includes of an entity: the fields they guarantee, their accessors on [`Included`],
+/// and the include methods of [`TypedFetchQuery`]
+macro_rules! impl_typed_includes {
+    (
+        $ty: ty,
+        fields: [$(($field: ident, $getter: ident: $value: ty)),+ $(,)?],
+        includes: [$(($method: ident, $inc: expr_2021, $guaranteed: ident)),+ $(,)?]
+    ) => {
+        $(impl crate::api::typed_fetch::IncludedField<$ty> for crate::api::typed_fetch::fields::$field {
+            const NAME: &'static str = stringify!($getter);
+
+            type Value = $value;
+
+            fn get(entity: &$ty) -> Option<&$value> {
+                entity.$getter.as_ref()
+            }
+        })+
+
+        impl<Fields> crate::api::typed_fetch::Included<$ty, Fields> {
+            $(#[doc = concat!("The `", stringify!($getter), "` field, guaranteed by the includes of the query")]
+            pub fn $getter<Index>(&self) -> &$value
+            where
+                Fields: crate::api::typed_fetch::HasField<crate::api::typed_fetch::fields::$field, Index>,
+            {
+                self.get::<crate::api::typed_fetch::fields::$field, Index>()
+            })+
+        }
+
+        impl<Fields> crate::api::typed_fetch::TypedFetchQuery<$ty, Fields>
+        where
+            Fields: crate::api::typed_fetch::FieldList<$ty>,
+        {
+            $(#[doc = concat!("Include the `", stringify!($guaranteed), "` field in the result")]
+            pub fn $method(
+                self,
+            ) -> crate::api::typed_fetch::TypedFetchQuery<$ty, (crate::api::typed_fetch::fields::$guaranteed, Fields)> {
+                self.include($inc)
+            })+
+        }
+    };
+}
+
+pub(crate) use impl_typed_includes;
+
+#[cfg(test)]
+mod test {
+    use api_bindium::ureq::http::HeaderMap;
+    use api_bindium::ureq::http::StatusCode;
+
+    use super::*;
+    use crate::Fetch as _;
+    use crate::entity::release::Release;
+
+    const RELEASE: &str = r#"{"id":"4642ee19-7790-3c8d-ab5e-d133de942db6","title":"†","media":[{"id":"0c0e1a64-0b63-3a03-b4f1-7e8ef2c8e2c4","track-count":12,"tracks":[]}]}"#;
+
+    #[test]
+    fn should_give_access_to_the_included_fields() {
+        let query = Release::fetch()
+            .id("4642ee19-7790-3c8d-ab5e-d133de942db6")
+            .typed()
+            .with_recordings();
+
+        let release = query
+            .parse_response(StatusCode::OK, &HeaderMap::new(), RELEASE.as_bytes())
+            .unwrap();
+
+        assert_eq!(release.media()[0].track_count, 12);
+        assert_eq!(release.title, "†");
+        assert_eq!(
+            query.query().to_string(),
+            "release/4642ee19-7790-3c8d-ab5e-d133de942db6?inc=recordings"
+        );
+    }
+
+    #[test]
+    fn should_fail_when_an_included_field_is_missing() {
+        let query = Release::fetch()
+            .id("4642ee19-7790-3c8d-ab5e-d133de942db6")
+            .typed()
+            .with_recordings()
+            .with_labels();
+
+        let error = query
+            .parse_response(StatusCode::OK, &HeaderMap::new(), RELEASE.as_bytes())
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            ParsingError::MissingIncludeError {
+                field: "label_info",
+                ..
+            }
+        ));
+    }
+}
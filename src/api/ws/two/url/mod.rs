@@ -1,10 +1,10 @@
 use core::fmt::Display;
-use core::fmt::Write as _;
 
 use api_bindium::ApiRequest;
 use api_bindium::Parser;
 use api_bindium::api_response::ureq_response::UreqResponseInner;
 use api_bindium::endpoints::UriBuilderError;
+use api_bindium::ureq::http::Uri;
 
 use crate::ParsingError;
 use crate::api::endpoints::MusicBrainzAPIEnpoints;
@@ -26,6 +26,61 @@ impl MusicBrainzAPIEnpoints {
         #[builder(default)] url_rels: bool,
         #[builder(default)] work_rels: bool,
     ) -> Result<ApiRequest<UrlResponseParser>, UriBuilderError> {
+        let uri = self.ws_2_url_uri(
+            ressources,
+            [
+                (artist_rels, "artist-rels"),
+                (label_rels, "label-rels"),
+                (release_group_rels, "release-group-rels"),
+                (release_rels, "release-rels"),
+                (recording_rels, "recording-rels"),
+                (url_rels, "url-rels"),
+                (work_rels, "work-rels"),
+            ],
+        )?;
+
+        Ok(ApiRequest::builder()
+            .uri(uri)
+            .maybe_headers(self.auth_headers())
+            .verb(api_bindium::HTTPVerb::Get)
+            .parser(UrlResponseParser)
+            .build())
+    }
+
+    /// The exact url requested by [`Self::ws_2_url`] with the same parameters, for debugging or to build cache keys
+    #[builder]
+    pub fn ws_2_url_to_url(
+        &self,
+        ressources: Vec<impl Display>,
+        #[builder(default)] artist_rels: bool,
+        #[builder(default)] label_rels: bool,
+        #[builder(default)] release_group_rels: bool,
+        #[builder(default)] release_rels: bool,
+        #[builder(default)] recording_rels: bool,
+        #[builder(default)] url_rels: bool,
+        #[builder(default)] work_rels: bool,
+    ) -> Result<String, UriBuilderError> {
+        self.ws_2_url_uri(
+            ressources,
+            [
+                (artist_rels, "artist-rels"),
+                (label_rels, "label-rels"),
+                (release_group_rels, "release-group-rels"),
+                (release_rels, "release-rels"),
+                (recording_rels, "recording-rels"),
+                (url_rels, "url-rels"),
+                (work_rels, "work-rels"),
+            ],
+        )
+        .map(|uri| uri.to_string())
+    }
+
+    /// Create the uri of a `ws/2/url` request, with the includes that are enabled
+    fn ws_2_url_uri(
+        &self,
+        ressources: Vec<impl Display>,
+        includes: [(bool, &str); 7],
+    ) -> Result<Uri, UriBuilderError> {
         let mut endpoint = self
             .endpoint_builder()
             .set_path("/ws/2/url")
@@ -35,44 +90,13 @@ impl MusicBrainzAPIEnpoints {
             endpoint = endpoint.add_parameter("resource", ressource)
         }
 
-        // There's gotta be a better way to do this, but for now it works
-        let mut incs = String::new();
-
-        if artist_rels {
-            write!(incs, "artist-rels+").unwrap();
-        }
-
-        if label_rels {
-            write!(incs, "label-rels+").unwrap();
-        }
-
-        if release_group_rels {
-            write!(incs, "release-group-rels+").unwrap();
-        }
-
-        if release_rels {
-            write!(incs, "release-rels+").unwrap();
-        }
-
-        if recording_rels {
-            write!(incs, "recording-rels+").unwrap();
-        }
-
-        if url_rels {
-            write!(incs, "url-rels+").unwrap();
-        }
-
-        if work_rels {
-            write!(incs, "work-rels+").unwrap();
-        }
-
-        if incs.ends_with("+") {
-            incs.pop();
-        }
+        let incs: Vec<&str> = includes
+            .into_iter()
+            .filter(|(enabled, _)| *enabled)
+            .map(|(_, include)| include)
+            .collect();
 
-        endpoint
-            .add_parameter("inc", incs)
-            .into_api_request(api_bindium::HTTPVerb::Get, UrlResponseParser)
+        endpoint.add_parameter("inc", incs.join("+")).to_uri()
     }
 }
 
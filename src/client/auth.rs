@@ -0,0 +1,104 @@
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use std::collections::HashMap;
+
+use api_bindium::ureq::http::Uri;
+use api_bindium::ureq::http::uri::Authority;
+#[cfg(feature = "basic_auth")]
+use base64::Engine as _;
+#[cfg(feature = "basic_auth")]
+use base64::engine::general_purpose::STANDARD;
+
+use crate::MusicBrainzClient;
+
+impl MusicBrainzClient {
+    /// Return the headers authenticating a request to this uri, if any credentials are set for its host.
+    ///
+    /// Credentials are looked up in this order:
+    /// - The `.netrc` entry of the request's host
+    /// - The basic auth credentials
+    /// - The OAuth bearer token
+    ///
+    /// Only the `.netrc` credentials are sent to other hosts than `musicbrainz_authority`, like the cover art archive.
+    pub fn auth_headers(&self, uri: &Uri) -> Option<HashMap<String, String>> {
+        uri.authority()
+            .and_then(|authority| self.authorization(authority))
+            .map(authorization_headers)
+    }
+
+    /// Return the value of the `Authorization` header for a request to this authority
+    pub(crate) fn authorization(&self, authority: &Authority) -> Option<String> {
+        #[cfg(feature = "netrc")]
+        if let Some(auth) = self
+            .netrc
+            .as_deref()
+            .and_then(|nrc| nrc.hosts.get(authority.host()))
+        {
+            return Some(basic_authorization(&auth.login, &auth.password));
+        }
+
+        // Don't leak the credentials to other servers
+        if authority.as_str() != self.musicbrainz_authority {
+            return None;
+        }
+
+        #[cfg(feature = "basic_auth")]
+        if let Some((username, password)) = &self.basic_auth_credentials {
+            return Some(basic_authorization(username, password));
+        }
+
+        self.bearer_token
+            .as_ref()
+            .map(|token| format!("Bearer {token}"))
+    }
+}
+
+#[cfg(feature = "basic_auth")]
+fn basic_authorization(username: &str, password: &str) -> String {
+    format!(
+        "Basic {}",
+        STANDARD.encode(format!("{username}:{password}"))
+    )
+}
+
+pub(crate) fn authorization_headers(authorization: String) -> HashMap<String, String> {
+    HashMap::from([("Authorization".to_string(), authorization)])
+}
+
+/// Formats an optional secret without showing its value
+pub(crate) struct Redacted<'a, T>(pub &'a Option<T>);
+
+impl<T> Debug for Redacted<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.0 {
+            Some(_) => f.write_str("Some(<redacted>)"),
+            None => f.write_str("None"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_only_send_credentials_to_musicbrainz() {
+        let client = MusicBrainzClient::builder()
+            .musicbrainz_authority("localhost:5000".to_string())
+            .bearer_token("secret_token".to_string())
+            .build();
+
+        let musicbrainz: Uri = "http://localhost:5000/ws/2/artist".parse().unwrap();
+        let coverart: Uri = "http://coverartarchive.org/release".parse().unwrap();
+
+        assert_eq!(
+            client
+                .auth_headers(&musicbrainz)
+                .and_then(|headers| headers.get("Authorization").cloned())
+                .as_deref(),
+            Some("Bearer secret_token")
+        );
+        assert_eq!(client.auth_headers(&coverart), None);
+        assert!(!format!("{client:?}").contains("secret_token"));
+    }
+}
@@ -0,0 +1,361 @@
+use core::any::Any;
+use core::any::TypeId;
+use core::pin::Pin;
+use core::task::Context;
+use core::task::Poll;
+use core::task::Waker;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use crate::ApiEndpointError;
+
+type SharedValue = Arc<dyn Any + Send + Sync>;
+type RequestKey = (TypeId, String);
+
+/// The errors that can be shared with the requests waiting for the same result
+pub(crate) trait ShareableError: Sized + Send + Sync + 'static {
+    /// Whether the waiting requests should send their own request instead of receiving this error
+    fn should_retry(&self) -> bool;
+
+    /// A copy of the error for the waiting requests. If it can't be copied, they send their own request instead
+    fn try_copy(&self) -> Option<Self>;
+
+    /// Wrap the error shared by another request
+    fn from_shared(error: Arc<Self>) -> Self;
+}
+
+impl ShareableError for ApiEndpointError {
+    fn should_retry(&self) -> bool {
+        self.is_transient()
+    }
+
+    fn try_copy(&self) -> Option<Self> {
+        self.duplicate()
+    }
+
+    fn from_shared(error: Arc<Self>) -> Self {
+        use snafu::IntoError as _;
+
+        crate::api::SharedSnafu.into_error(error)
+    }
+}
+
+/// The requests currently being sent by a client, keyed by their URI.
+///
+/// The first task requesting an URI becomes the "leader" and sends the request.
+/// Every other task requesting the same URI in the meantime waits for the leader's result,
+/// and receives a clone of the parsed value, or a [copy](ShareableError::try_copy) of the leader's error.
+/// The leader keeps its original error.
+///
+/// If the leader fails with a [transient](ShareableError::should_retry) error or one that can't be copied,
+/// or gets cancelled, the waiting tasks elect a new leader that retries the request.
+#[derive(Debug, Default)]
+pub(crate) struct InFlightRequests {
+    requests: Mutex<HashMap<RequestKey, Arc<InFlightRequest>>>,
+}
+
+impl InFlightRequests {
+    /// Run the request, or wait for the result of an identical request already in flight.
+    ///
+    /// Also return whether the result was shared by another request.
+    pub(crate) async fn run<T, E>(
+        &self,
+        uri: String,
+        request: impl Future<Output = Result<T, E>>,
+    ) -> (Result<T, E>, bool)
+    where
+        T: Clone + Send + Sync + 'static,
+        E: ShareableError,
+    {
+        let key = (TypeId::of::<Result<T, E>>(), uri);
+
+        let leader = loop {
+            match self.join(&key) {
+                Joined::Leader(leader) => break leader,
+                Joined::Waiter(in_flight) => {
+                    let shared = WaitForRequest(in_flight).await;
+
+                    match shared
+                        .as_deref()
+                        .and_then(|val| val.downcast_ref::<Result<T, Arc<E>>>())
+                    {
+                        Some(Ok(value)) => return (Ok(value.clone()), true),
+                        Some(Err(error)) => return (Err(E::from_shared(error.clone())), true),
+                        None => {}
+                    }
+                }
+            }
+        };
+
+        let result = match request.await {
+            Ok(value) => {
+                leader.complete(Arc::new(Ok::<T, Arc<E>>(value.clone())));
+                Ok(value)
+            }
+            Err(error) if error.should_retry() || !leader.has_waiters() => Err(error),
+            Err(error) => {
+                if let Some(copy) = error.try_copy() {
+                    leader.complete(Arc::new(Err::<T, Arc<E>>(Arc::new(copy))));
+                }
+                Err(error)
+            }
+        };
+
+        (result, false)
+    }
+
+    fn join(&self, key: &RequestKey) -> Joined<'_> {
+        let mut requests = self.requests.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(in_flight) = requests.get(key) {
+            return Joined::Waiter(in_flight.clone());
+        }
+
+        let in_flight = Arc::new(InFlightRequest::default());
+        requests.insert(key.clone(), in_flight.clone());
+
+        Joined::Leader(LeaderGuard {
+            requests: self,
+            key: key.clone(),
+            in_flight,
+            value: None,
+        })
+    }
+}
+
+enum Joined<'a> {
+    Leader(LeaderGuard<'a>),
+    Waiter(Arc<InFlightRequest>),
+}
+
+#[derive(Debug)]
+struct InFlightRequest {
+    state: Mutex<RequestState>,
+}
+
+impl Default for InFlightRequest {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(RequestState::Pending(Vec::new())),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum RequestState {
+    Pending(Vec<Waker>),
+
+    /// The request is finished. `None` means that the leader failed with an error that can't be shared, or was cancelled
+    Done(Option<SharedValue>),
+}
+
+/// Held by the task sending the request. Once dropped, the waiting tasks are released,
+/// even if the leader's future got cancelled midway.
+struct LeaderGuard<'a> {
+    requests: &'a InFlightRequests,
+    key: RequestKey,
+    in_flight: Arc<InFlightRequest>,
+    value: Option<SharedValue>,
+}
+
+impl LeaderGuard<'_> {
+    fn complete(mut self, value: SharedValue) {
+        self.value = Some(value);
+    }
+
+    /// Whether other tasks wait for the result. Once called, new callers don't wait for this request anymore
+    fn has_waiters(&self) -> bool {
+        self.detach();
+        Arc::strong_count(&self.in_flight) > 1
+    }
+
+    /// Remove the request from the ones in flight
+    fn detach(&self) {
+        self.requests
+            .requests
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.key);
+    }
+}
+
+impl Drop for LeaderGuard<'_> {
+    fn drop(&mut self) {
+        // Remove the request first, so new callers don't wait for a finished request
+        self.detach();
+
+        let mut state = self
+            .in_flight
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        if let RequestState::Pending(wakers) =
+            core::mem::replace(&mut *state, RequestState::Done(self.value.take()))
+        {
+            wakers.into_iter().for_each(Waker::wake);
+        }
+    }
+}
+
+struct WaitForRequest(Arc<InFlightRequest>);
+
+impl Future for WaitForRequest {
+    type Output = Option<SharedValue>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.0.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match &mut *state {
+            RequestState::Done(value) => Poll::Ready(value.clone()),
+            RequestState::Pending(wakers) => {
+                if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                    wakers.push(cx.waker().clone());
+                }
+
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::sync::atomic::AtomicUsize;
+    use core::sync::atomic::Ordering;
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum TestError {
+        Transient,
+        NotFound,
+        /// An error that can't be copied, like an I/O error
+        Unique,
+        Shared(Arc<TestError>),
+    }
+
+    impl ShareableError for TestError {
+        fn should_retry(&self) -> bool {
+            *self == Self::Transient
+        }
+
+        fn try_copy(&self) -> Option<Self> {
+            (*self != Self::Unique).then(|| self.clone())
+        }
+
+        fn from_shared(error: Arc<Self>) -> Self {
+            Self::Shared(error)
+        }
+    }
+
+    #[tokio::test]
+    async fn should_send_identical_requests_once() {
+        let in_flight = InFlightRequests::default();
+        let sent = AtomicUsize::new(0);
+
+        let request = || async {
+            sent.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            Ok::<_, TestError>("Nirvana".to_string())
+        };
+
+        let (first, second) = tokio::join!(
+            in_flight.run("artist/1".to_string(), request()),
+            in_flight.run("artist/1".to_string(), request())
+        );
+
+        assert_eq!(first, (Ok("Nirvana".to_string()), false));
+        assert_eq!(second, (Ok("Nirvana".to_string()), true));
+        assert_eq!(sent.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn should_share_errors() {
+        let in_flight = InFlightRequests::default();
+        let sent = AtomicUsize::new(0);
+
+        let request = || async {
+            sent.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            Err::<u8, _>(TestError::NotFound)
+        };
+
+        let (first, second) = tokio::join!(
+            in_flight.run("artist/1".to_string(), request()),
+            in_flight.run("artist/1".to_string(), request())
+        );
+
+        let shared = TestError::Shared(Arc::new(TestError::NotFound));
+        assert_eq!(first, (Err(TestError::NotFound), false));
+        assert_eq!(second, (Err(shared), true));
+        assert_eq!(sent.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn should_retry_on_errors_that_cannot_be_copied() {
+        let in_flight = InFlightRequests::default();
+        let sent = AtomicUsize::new(0);
+
+        let request = || async {
+            sent.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            Err::<u8, _>(TestError::Unique)
+        };
+
+        let (first, second) = tokio::join!(
+            in_flight.run("artist/1".to_string(), request()),
+            in_flight.run("artist/1".to_string(), request())
+        );
+
+        assert_eq!(first, (Err(TestError::Unique), false));
+        assert_eq!(second, (Err(TestError::Unique), false));
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn should_retry_on_transient_errors() {
+        let in_flight = InFlightRequests::default();
+        let sent = AtomicUsize::new(0);
+
+        let request = || async {
+            let attempt = sent.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            if attempt == 0 {
+                Err(TestError::Transient)
+            } else {
+                Ok(1)
+            }
+        };
+
+        let (first, second) = tokio::join!(
+            in_flight.run("artist/1".to_string(), request()),
+            in_flight.run("artist/1".to_string(), request())
+        );
+
+        assert_eq!(first, (Err(TestError::Transient), false));
+        assert_eq!(second, (Ok(1), false));
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn should_retry_when_leader_is_cancelled() {
+        let in_flight = InFlightRequests::default();
+
+        let mut leader = Box::pin(in_flight.run(
+            "artist/1".to_string(),
+            core::future::pending::<Result<u8, TestError>>(),
+        ));
+        assert!(futures::poll!(&mut leader).is_pending());
+
+        let mut waiter =
+            Box::pin(in_flight.run("artist/1".to_string(), async { Ok::<u8, TestError>(1) }));
+        assert!(futures::poll!(&mut waiter).is_pending());
+
+        drop(leader);
+
+        assert_eq!(waiter.await, (Ok(1), false));
+    }
+}
@@ -0,0 +1,365 @@
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::str::FromStr as _;
+use std::env::VarError;
+
+use api_bindium::ureq::http::Uri;
+use api_bindium::ureq::http::uri::Authority;
+use api_bindium::ureq::http::uri::InvalidUri;
+
+use serde::Deserialize;
+use serde::Serialize;
+use snafu::ResultExt as _;
+use snafu::Snafu;
+
+use crate::MusicBrainzClient;
+use crate::client::auth::Redacted;
+
+/// Environment variable holding the authority (host and optional port) of the MusicBrainz API
+pub const MUSICBRAINZ_AUTHORITY: &str = "MUSICBRAINZ_AUTHORITY";
+/// Environment variable holding whether to use HTTPS for the MusicBrainz API (`true`/`false`/`1`/`0`)
+pub const MUSICBRAINZ_HTTPS: &str = "MUSICBRAINZ_HTTPS";
+/// Environment variable holding the user agent of the requests
+pub const MUSICBRAINZ_USER_AGENT: &str = "MUSICBRAINZ_USER_AGENT";
+/// Environment variable holding the url of the cover art archive api
+pub const COVERART_ARCHIVE_URL: &str = "COVERART_ARCHIVE_URL";
+/// Environment variable holding the OAuth access token
+pub const MUSICBRAINZ_BEARER_TOKEN: &str = "MUSICBRAINZ_BEARER_TOKEN";
+/// Environment variable holding the basic auth username
+#[cfg(feature = "basic_auth")]
+pub const MUSICBRAINZ_USERNAME: &str = "MUSICBRAINZ_USERNAME";
+/// Environment variable holding the basic auth password
+#[cfg(feature = "basic_auth")]
+pub const MUSICBRAINZ_PASSWORD: &str = "MUSICBRAINZ_PASSWORD";
+
+/// The settings of a [`MusicBrainzClient`], as they can be loaded from a config file or the environment.
+///
+/// Every setting is optional. The ones that aren't set keep the defaults of [`MusicBrainzClient::builder`].
+///
+/// ## Example
+/// ```rust
+/// # use musicbrainz_rs::client::MusicBrainzClient;
+/// # use musicbrainz_rs::client::config::MusicBrainzClientConfig;
+/// let config: MusicBrainzClientConfig = serde_json::from_str(
+///     r#"{ "musicbrainz_authority": "beta.musicbrainz.org", "user_agent": "MyApp/1.0.0" }"#,
+/// )
+/// .unwrap();
+///
+/// let client = MusicBrainzClient::try_from(config).unwrap();
+/// assert_eq!(client.musicbrainz_authority, "beta.musicbrainz.org");
+/// assert!(client.musicbrainz_use_https);
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MusicBrainzClientConfig {
+    /// Authority (host and optional port) of the API.
+    pub musicbrainz_authority: Option<String>,
+
+    /// Whether to use HTTPS for the MusicBrainz API.
+    pub musicbrainz_use_https: Option<bool>,
+
+    /// Domain of the cover art archive api
+    pub coverart_archive_url: Option<String>,
+
+    /// The User-Agent header sent with each request
+    pub user_agent: Option<String>,
+
+    #[cfg(feature = "basic_auth")]
+    /// Basic auth username for the API. Must be set along with `password`.
+    pub username: Option<String>,
+
+    #[cfg(feature = "basic_auth")]
+    /// Basic auth password for the API. Must be set along with `username`.
+    pub password: Option<String>,
+
+    /// OAuth access token for the API
+    pub bearer_token: Option<String>,
+}
+
+impl Debug for MusicBrainzClientConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let mut debug = f.debug_struct("MusicBrainzClientConfig");
+        debug
+            .field("musicbrainz_authority", &self.musicbrainz_authority)
+            .field("musicbrainz_use_https", &self.musicbrainz_use_https)
+            .field("coverart_archive_url", &self.coverart_archive_url)
+            .field("user_agent", &self.user_agent);
+
+        #[cfg(feature = "basic_auth")]
+        debug
+            .field("username", &self.username)
+            .field("password", &Redacted(&self.password));
+
+        debug
+            .field("bearer_token", &Redacted(&self.bearer_token))
+            .finish()
+    }
+}
+
+impl MusicBrainzClientConfig {
+    /// Read the config from the environment variables. Unset variables are left as `None`.
+    /// The config is [validated](Self::validate) before being returned.
+    ///
+    /// See the constants of [this module](crate::client::config) for the names of the variables.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_vars(std::env::var)
+    }
+
+    fn from_vars(
+        vars: impl Fn(&'static str) -> Result<String, VarError>,
+    ) -> Result<Self, ConfigError> {
+        let get = |name: &'static str| match vars(name) {
+            Ok(value) => Ok(Some(value)),
+            Err(VarError::NotPresent) => Ok(None),
+            Err(err) => Err(err).context(EnvVarSnafu { name }),
+        };
+
+        let musicbrainz_use_https = match get(MUSICBRAINZ_HTTPS)? {
+            Some(value) => Some(parse_bool(&value).context(InvalidBoolSnafu {
+                name: MUSICBRAINZ_HTTPS,
+                value,
+            })?),
+            None => None,
+        };
+
+        let config = Self {
+            musicbrainz_authority: get(MUSICBRAINZ_AUTHORITY)?,
+            musicbrainz_use_https,
+            coverart_archive_url: get(COVERART_ARCHIVE_URL)?,
+            user_agent: get(MUSICBRAINZ_USER_AGENT)?,
+            #[cfg(feature = "basic_auth")]
+            username: get(MUSICBRAINZ_USERNAME)?,
+            #[cfg(feature = "basic_auth")]
+            password: get(MUSICBRAINZ_PASSWORD)?,
+            bearer_token: get(MUSICBRAINZ_BEARER_TOKEN)?,
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that the settings can be used by a client:
+    /// - `musicbrainz_authority` is a valid host with an optional port
+    /// - `coverart_archive_url` is an absolute `http` or `https` url
+    /// - `username` and `password` are either both set, or both unset
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(authority) = &self.musicbrainz_authority {
+            Authority::from_str(authority).context(InvalidAuthoritySnafu { value: authority })?;
+        }
+
+        if let Some(url) = &self.coverart_archive_url {
+            let uri = Uri::from_str(url).context(InvalidUrlSnafu { value: url })?;
+            let is_http = matches!(uri.scheme_str(), Some("http" | "https"));
+            if !is_http || uri.authority().is_none() {
+                return UnsupportedUrlSnafu { value: url }.fail();
+            }
+        }
+
+        #[cfg(feature = "basic_auth")]
+        if self.username.is_some() != self.password.is_some() {
+            return IncompleteCredentialsSnafu.fail();
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<MusicBrainzClientConfig> for MusicBrainzClient {
+    type Error = ConfigError;
+
+    fn try_from(config: MusicBrainzClientConfig) -> Result<Self, Self::Error> {
+        config.validate()?;
+
+        #[cfg_attr(
+            not(feature = "basic_auth"),
+            expect(unused_mut, reason = "Only the credentials are set after building")
+        )]
+        let mut client = Self::builder()
+            .maybe_musicbrainz_authority(config.musicbrainz_authority)
+            .maybe_musicbrainz_use_https(config.musicbrainz_use_https)
+            .maybe_coverart_archive_url(config.coverart_archive_url)
+            .maybe_user_agent(config.user_agent)
+            .maybe_bearer_token(config.bearer_token)
+            .build();
+
+        #[cfg(feature = "basic_auth")]
+        if let (Some(username), Some(password)) = (config.username, config.password) {
+            client.basic_auth_credentials = Some((username, password));
+        }
+
+        Ok(client)
+    }
+}
+
+impl MusicBrainzClient {
+    /// Create a client configured from the environment variables. See [`MusicBrainzClientConfig::from_env`]
+    ///
+    /// ## Example
+    /// ```rust
+    /// # use musicbrainz_rs::client::MusicBrainzClient;
+    /// let client = MusicBrainzClient::from_env().unwrap();
+    /// ```
+    pub fn from_env() -> Result<Self, ConfigError> {
+        MusicBrainzClientConfig::from_env().and_then(Self::try_from)
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, core::str::ParseBoolError> {
+    match value.trim() {
+        "1" => Ok(true),
+        "0" => Ok(false),
+        value => value.to_lowercase().parse(),
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum ConfigError {
+    #[snafu(display("Couldn't read the environment variable `{name}`"))]
+    EnvVarError {
+        name: &'static str,
+        source: VarError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("The environment variable `{name}` should be a boolean, but is `{value}`"))]
+    InvalidBoolError {
+        name: &'static str,
+        value: String,
+        source: core::str::ParseBoolError,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("`{value}` isn't a valid authority (host and optional port)"))]
+    InvalidAuthorityError {
+        value: String,
+        source: InvalidUri,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("`{value}` isn't a valid url"))]
+    InvalidUrlError {
+        value: String,
+        source: InvalidUri,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("`{value}` should be an absolute `http` or `https` url"))]
+    UnsupportedUrlError {
+        value: String,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    /// Only one of the basic auth username and password is set
+    #[cfg(feature = "basic_auth")]
+    #[snafu(display("The basic auth username and password should be set together"))]
+    IncompleteCredentialsError {
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn should_read_config_from_vars() {
+        let vars = HashMap::from([
+            (MUSICBRAINZ_AUTHORITY, "localhost:5000"),
+            (MUSICBRAINZ_HTTPS, "0"),
+        ]);
+
+        let config = MusicBrainzClientConfig::from_vars(|name| {
+            vars.get(name)
+                .map(ToString::to_string)
+                .ok_or(VarError::NotPresent)
+        })
+        .unwrap();
+
+        assert_eq!(
+            config.musicbrainz_authority.as_deref(),
+            Some("localhost:5000")
+        );
+        assert_eq!(config.musicbrainz_use_https, Some(false));
+        assert_eq!(config.user_agent, None);
+
+        let client = MusicBrainzClient::try_from(config).unwrap();
+        assert!(!client.musicbrainz_use_https);
+        assert_eq!(client.coverart_archive_url, "http://coverartarchive.org");
+    }
+
+    #[test]
+    fn should_reject_invalid_bool() {
+        let config = MusicBrainzClientConfig::from_vars(|name| match name {
+            MUSICBRAINZ_HTTPS => Ok("maybe".to_string()),
+            _ => Err(VarError::NotPresent),
+        });
+
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn should_reject_invalid_urls() {
+        let config = MusicBrainzClientConfig::from_vars(|name| match name {
+            COVERART_ARCHIVE_URL => Ok("coverartarchive.org".to_string()),
+            _ => Err(VarError::NotPresent),
+        });
+        assert!(matches!(
+            config,
+            Err(ConfigError::UnsupportedUrlError { .. })
+        ));
+
+        let config = MusicBrainzClientConfig {
+            musicbrainz_authority: Some("not a host".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            MusicBrainzClient::try_from(config),
+            Err(ConfigError::InvalidAuthorityError { .. })
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "basic_auth")]
+    fn should_reject_incomplete_credentials() {
+        let config = MusicBrainzClientConfig {
+            username: Some("user".to_string()),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            MusicBrainzClient::try_from(config),
+            Err(ConfigError::IncompleteCredentialsError { .. })
+        ));
+    }
+}
@@ -0,0 +1,175 @@
+use core::cell::RefCell;
+use core::pin::Pin;
+use core::task::Context;
+use core::task::Poll;
+use std::sync::Arc;
+use std::sync::OnceLock;
+
+use snafu::Snafu;
+
+use crate::MusicBrainzClient;
+
+/// The client used by the `execute` methods of the queries
+static GLOBAL_CLIENT: OnceLock<Arc<MusicBrainzClient>> = OnceLock::new();
+
+std::thread_local! {
+    /// The clients set by [`with_global_client`], the last one taking precedence
+    static SCOPED_CLIENTS: RefCell<Vec<Arc<MusicBrainzClient>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Set the client used by the `execute` methods of the queries.
+///
+/// This can only be done once, and before any query is executed with the global client.
+/// Otherwise, an error is returned. If it is never set, [`MusicBrainzClient::default`] is used.
+///
+/// ## Example
+/// ```rust
+/// # use musicbrainz_rs::client::MusicBrainzClient;
+/// # use musicbrainz_rs::client::global::set_global_client;
+/// set_global_client(MusicBrainzClient::new(
+///     "MyAwesomeTagger/1.2.0 ( http://myawesometagger.example.com )",
+/// ))
+/// .unwrap();
+///
+/// assert!(set_global_client(MusicBrainzClient::default()).is_err());
+/// ```
+pub fn set_global_client(client: MusicBrainzClient) -> Result<(), GlobalClientError> {
+    GLOBAL_CLIENT
+        .set(Arc::new(client))
+        .map_err(|_| AlreadySetSnafu.build())
+}
+
+/// Return the client used by the `execute` methods of the queries.
+///
+/// This is the client of the innermost [`with_global_client`] scope if any, or the one set with [`set_global_client`].
+pub fn global_client() -> Arc<MusicBrainzClient> {
+    if let Some(client) = SCOPED_CLIENTS.with_borrow(|clients| clients.last().cloned()) {
+        return client;
+    }
+
+    GLOBAL_CLIENT
+        .get_or_init(|| Arc::new(MusicBrainzClient::default()))
+        .clone()
+}
+
+/// The global client of the `execute` methods, dereferencing to the one set with [`set_global_client`].
+///
+/// This only exists for [`MUSICBRAINZ_CLIENT`](crate::client::MUSICBRAINZ_CLIENT). As it can't borrow the client of a
+/// [`with_global_client`] scope, it ignores them.
+#[derive(Debug)]
+pub struct LegacyGlobalClient(());
+
+impl LegacyGlobalClient {
+    pub(crate) const fn new() -> Self {
+        Self(())
+    }
+}
+
+impl core::ops::Deref for LegacyGlobalClient {
+    type Target = MusicBrainzClient;
+
+    fn deref(&self) -> &Self::Target {
+        GLOBAL_CLIENT.get_or_init(|| Arc::new(MusicBrainzClient::default()))
+    }
+}
+
+/// Run the function with a different global client on the current thread. This is mostly useful for tests.
+///
+/// ## Example
+/// ```rust
+/// # use musicbrainz_rs::client::MusicBrainzClient;
+/// # use musicbrainz_rs::client::global::global_client;
+/// # use musicbrainz_rs::client::global::with_global_client;
+/// let client = MusicBrainzClient::builder()
+///     .musicbrainz_authority("localhost:5000".to_string())
+///     .build();
+///
+/// with_global_client(client, || {
+///     assert_eq!(global_client().musicbrainz_authority, "localhost:5000");
+/// });
+/// ```
+pub fn with_global_client<R>(client: MusicBrainzClient, f: impl FnOnce() -> R) -> R {
+    let _scope = ScopeGuard::enter(Arc::new(client));
+    f()
+}
+
+/// Run the future with a different global client. Unlike [`with_global_client`],
+/// the client is used even if the future is polled on different threads.
+pub async fn with_global_client_async<F: Future>(
+    client: MusicBrainzClient,
+    future: F,
+) -> F::Output {
+    ScopedFuture {
+        client: Arc::new(client),
+        future: Box::pin(future),
+    }
+    .await
+}
+
+/// Set the scoped client until dropped
+struct ScopeGuard;
+
+impl ScopeGuard {
+    fn enter(client: Arc<MusicBrainzClient>) -> Self {
+        SCOPED_CLIENTS.with_borrow_mut(|clients| clients.push(client));
+        Self
+    }
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        SCOPED_CLIENTS.with_borrow_mut(|clients| clients.pop());
+    }
+}
+
+struct ScopedFuture<F> {
+    client: Arc<MusicBrainzClient>,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for ScopedFuture<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let _scope = ScopeGuard::enter(self.client.clone());
+        self.future.as_mut().poll(cx)
+    }
+}
+
+#[derive(Debug, Snafu)]
+pub enum GlobalClientError {
+    #[snafu(display("The global client can only be set once, and before being used by any query"))]
+    AlreadySet {
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_override_global_client_in_scope() {
+        let client = |authority: &str| {
+            MusicBrainzClient::builder()
+                .musicbrainz_authority(authority.to_string())
+                .build()
+        };
+
+        with_global_client(client("localhost:5000"), || {
+            assert_eq!(global_client().musicbrainz_authority, "localhost:5000");
+
+            with_global_client(client("localhost:5001"), || {
+                assert_eq!(global_client().musicbrainz_authority, "localhost:5001");
+            });
+
+            assert_eq!(global_client().musicbrainz_authority, "localhost:5000");
+        });
+
+        assert!(SCOPED_CLIENTS.with_borrow(Vec::is_empty));
+    }
+}
@@ -0,0 +1,400 @@
+use core::time::Duration;
+use std::collections::HashMap;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::sync::Mutex;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::sync::PoisonError;
+
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::ureq::http::Response;
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::ureq::http::StatusCode;
+
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::MusicBrainzClient;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::api::ApiEndpointError;
+use crate::api::ErrorKind;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::transport::RateLimitWait;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::transport::ResponseBody;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::transport::TransportError;
+
+/// The upper bounds of the buckets of [`LatencyHistogram`]. Slower requests go in a last, unbounded bucket.
+pub const LATENCY_BUCKETS: [Duration; 8] = [
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_millis(2500),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+];
+
+/// The kind of query a request was sent for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryKind {
+    Fetch,
+    Browse,
+    Search,
+    Coverart,
+}
+
+/// The entity type and query kind the statistics of a [`ClientStats`] are kept for
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MetricsKey {
+    /// The entity type, as found in the path of the API (ex: `artist`, `release-group`)
+    pub entity: String,
+    pub kind: QueryKind,
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+impl MetricsKey {
+    /// Create the key of a query from the path of its endpoint
+    pub(crate) fn new(path: &str, kind: QueryKind) -> Self {
+        Self {
+            entity: path.split('/').next().unwrap_or_default().to_string(),
+            kind,
+        }
+    }
+}
+
+/// Distribution of the response times of requests, in the buckets of [`LATENCY_BUCKETS`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    counts: [u64; LATENCY_BUCKETS.len() + 1],
+    total: Duration,
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, latency: Duration) {
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| latency <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+
+        self.counts[bucket] += 1;
+        self.total += latency;
+    }
+
+    /// The number of recorded requests
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The sum of the recorded response times
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// The mean response time, if any request got recorded
+    pub fn mean(&self) -> Option<Duration> {
+        let count = u32::try_from(self.count())
+            .ok()
+            .filter(|count| *count != 0)?;
+        Some(self.total / count)
+    }
+
+    /// Return each bucket's upper bound and number of requests. The last bucket is unbounded.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        LATENCY_BUCKETS
+            .iter()
+            .copied()
+            .map(Some)
+            .chain(core::iter::once(None))
+            .zip(self.counts.iter().copied())
+    }
+
+    fn merge(&mut self, other: &Self) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts) {
+            *count += other;
+        }
+        self.total += other.total;
+    }
+}
+
+/// Statistics of the requests sent for a [`MetricsKey`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryStats {
+    /// The number of requests sent to a server, including retries
+    pub requests: u64,
+
+    /// The number of queries that failed, by kind of error
+    pub errors: HashMap<ErrorKind, u64>,
+
+    /// The number of requests retried on a fallback mirror
+    pub retries: u64,
+
+    /// The number of queries that got the result of an identical request already in flight instead of sending their own
+    pub cache_hits: u64,
+
+    /// The number of bytes of the response bodies read by the parser
+    pub bytes_received: u64,
+
+    /// The time taken to send the requests and receive the responses, once the rate limiter let them through.
+    ///
+    /// The wait for the rate limiter is only told apart if the transport reports a [`RateLimitWait`](crate::client::transport::RateLimitWait).
+    /// Otherwise, it is included here.
+    pub latency: LatencyHistogram,
+
+    /// The time spent waiting for the rate limiter of the transport, as reported by its [`RateLimitWait`](crate::client::transport::RateLimitWait)
+    pub rate_limit_wait: Duration,
+
+    /// The time spent waiting for the [`RequestScheduler`](crate::client::scheduler::RequestScheduler) to let the requests through
+    pub scheduler_wait: Duration,
+}
+
+impl QueryStats {
+    /// The total number of failed queries
+    pub fn error_count(&self) -> u64 {
+        self.errors.values().sum()
+    }
+
+    fn merge(&mut self, other: &Self) {
+        self.requests += other.requests;
+        for (kind, count) in &other.errors {
+            *self.errors.entry(*kind).or_default() += count;
+        }
+        self.retries += other.retries;
+        self.cache_hits += other.cache_hits;
+        self.bytes_received += other.bytes_received;
+        self.latency.merge(&other.latency);
+        self.rate_limit_wait += other.rate_limit_wait;
+        self.scheduler_wait += other.scheduler_wait;
+    }
+}
+
+/// Snapshot of the statistics of a [`MusicBrainzClient`], returned by [`MusicBrainzClient::stats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientStats {
+    pub queries: HashMap<MetricsKey, QueryStats>,
+}
+
+impl ClientStats {
+    /// Return the statistics of an entity type and query kind
+    pub fn get(&self, entity: &str, kind: QueryKind) -> Option<&QueryStats> {
+        self.queries.get(&MetricsKey {
+            entity: entity.to_string(),
+            kind,
+        })
+    }
+
+    /// Return the statistics of all the queries combined
+    pub fn total(&self) -> QueryStats {
+        let mut total = QueryStats::default();
+        for stats in self.queries.values() {
+            total.merge(stats);
+        }
+        total
+    }
+}
+
+/// The counters of a client, shared with its clones
+#[cfg(any(feature = "sync", feature = "async"))]
+#[derive(Debug, Default)]
+pub(crate) struct ClientMetrics {
+    queries: Mutex<HashMap<MetricsKey, QueryStats>>,
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+impl ClientMetrics {
+    fn update(&self, key: &MetricsKey, update: impl FnOnce(&mut QueryStats)) {
+        let mut queries = self.queries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match queries.get_mut(key) {
+            Some(stats) => update(stats),
+            None => update(queries.entry(key.clone()).or_default()),
+        }
+    }
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+impl MusicBrainzClient {
+    /// Return a snapshot of the statistics of the queries executed with this client.
+    ///
+    /// The statistics are shared between the clones of the client.
+    ///
+    /// ## Example
+    /// ```rust
+    /// # use musicbrainz_rs::client::MusicBrainzClient;
+    /// # use musicbrainz_rs::client::metrics::QueryKind;
+    /// let client = MusicBrainzClient::default();
+    /// let stats = client.stats();
+    ///
+    /// assert!(stats.get("artist", QueryKind::Fetch).is_none());
+    /// assert_eq!(stats.total().requests, 0);
+    /// ```
+    pub fn stats(&self) -> ClientStats {
+        ClientStats {
+            queries: self
+                .metrics
+                .queries
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        }
+    }
+
+    /// Record a request that was sent, and the response it got. Its body is counted apart, once read
+    pub(crate) fn record_response(
+        &self,
+        key: &MetricsKey,
+        response: Result<&Response<ResponseBody>, &TransportError>,
+        elapsed: Duration,
+    ) {
+        let response = response.ok();
+        let rate_limit_wait = response
+            .and_then(|response| response.extensions().get::<RateLimitWait>())
+            .map_or(Duration::ZERO, |wait| wait.0);
+
+        self.metrics
+            .update(key, |stats| stats.rate_limit_wait += rate_limit_wait);
+
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record(
+            "rate_limit_wait_ms",
+            u64::try_from(rate_limit_wait.as_millis()).unwrap_or(u64::MAX),
+        );
+        self.record_request(
+            key,
+            response.map(Response::status),
+            elapsed.saturating_sub(rate_limit_wait),
+        );
+    }
+
+    /// Record the size of the body of a response, once read
+    pub(crate) fn record_bytes_received(&self, key: &MetricsKey, bytes: u64) {
+        self.metrics
+            .update(key, |stats| stats.bytes_received += bytes);
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: "musicbrainz_rs::metrics",
+            entity = key.entity,
+            kind = ?key.kind,
+            bytes,
+            "Response read"
+        );
+    }
+
+    #[cfg_attr(
+        not(feature = "tracing"),
+        expect(unused_variables, reason = "The status is only traced")
+    )]
+    fn record_request(&self, key: &MetricsKey, status: Option<StatusCode>, elapsed: Duration) {
+        self.metrics.update(key, |stats| {
+            stats.requests += 1;
+            stats.latency.record(elapsed);
+        });
+
+        #[cfg(feature = "tracing")]
+        if let Some(status) = status {
+            tracing::Span::current().record("status", status.as_u16());
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            target: "musicbrainz_rs::metrics",
+            entity = key.entity,
+            kind = ?key.kind,
+            status = status.map(|status| status.as_u16()),
+            elapsed_ms = elapsed.as_millis(),
+            "Request sent"
+        );
+    }
+
+    /// Record the outcome of a query
+    pub(crate) fn record_result<T>(&self, key: &MetricsKey, result: &Result<T, ApiEndpointError>) {
+        if let Err(err) = result {
+            self.metrics.update(key, |stats| {
+                *stats.errors.entry(err.kind()).or_default() += 1;
+            });
+
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("error", tracing::field::debug(err.kind()));
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                target: "musicbrainz_rs::metrics",
+                entity = key.entity,
+                kind = ?key.kind,
+                error = ?err.kind(),
+                "Query failed"
+            );
+        }
+    }
+
+    pub(crate) fn record_retry(&self, key: &MetricsKey) {
+        self.metrics.update(key, |stats| stats.retries += 1);
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn record_cache_hit(&self, key: &MetricsKey) {
+        self.metrics.update(key, |stats| stats.cache_hits += 1);
+    }
+
+    pub(crate) fn record_scheduler_wait(&self, key: &MetricsKey, wait: Duration) {
+        self.metrics
+            .update(key, |stats| stats.scheduler_wait += wait);
+
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record(
+            "scheduler_wait_ms",
+            u64::try_from(wait.as_millis()).unwrap_or(u64::MAX),
+        );
+    }
+}
+
+#[cfg(all(test, any(feature = "sync", feature = "async")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_aggregate_stats() {
+        let client = MusicBrainzClient::default();
+        let artist = MetricsKey::new("artist", QueryKind::Fetch);
+        let release = MetricsKey::new("release/", QueryKind::Search);
+
+        client.metrics.update(&artist, |stats| {
+            stats.requests += 2;
+            stats.latency.record(Duration::from_millis(30));
+            stats.latency.record(Duration::from_secs(30));
+        });
+        client.clone().record_retry(&release);
+        client.record_scheduler_wait(&release, Duration::from_millis(200));
+
+        let stats = client.stats();
+        let artist_stats = stats.get("artist", QueryKind::Fetch).unwrap();
+        assert_eq!(artist_stats.latency.count(), 2);
+        assert_eq!(
+            artist_stats.latency.buckets().next(),
+            Some((Some(Duration::from_millis(50)), 1))
+        );
+        assert_eq!(artist_stats.latency.buckets().last(), Some((None, 1)));
+        assert_eq!(stats.get("release", QueryKind::Search).unwrap().retries, 1);
+
+        let total = stats.total();
+        assert_eq!(total.requests, 2);
+        assert_eq!(total.retries, 1);
+        assert_eq!(total.scheduler_wait, Duration::from_millis(200));
+    }
+
+    #[test]
+    fn should_count_the_rate_limit_wait_apart() {
+        let client = MusicBrainzClient::default();
+        let key = MetricsKey::new("artist", QueryKind::Fetch);
+        let mut response = Response::new(ResponseBody::from("{}"));
+        response
+            .extensions_mut()
+            .insert(RateLimitWait(Duration::from_millis(100)));
+
+        client.record_response(&key, Ok(&response), Duration::from_millis(300));
+
+        let stats = client.stats().total();
+        assert_eq!(stats.rate_limit_wait, Duration::from_millis(100));
+        assert_eq!(stats.latency.total(), Duration::from_millis(200));
+    }
+}
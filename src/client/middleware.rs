@@ -0,0 +1,137 @@
+use core::time::Duration;
+
+use api_bindium::ureq::http::HeaderMap;
+use api_bindium::ureq::http::Request;
+use api_bindium::ureq::http::StatusCode;
+use api_bindium::ureq::http::Uri;
+
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::MusicBrainzClient;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::api::ApiEndpointError;
+
+/// The error returned by a [`Middleware`] to cancel a request
+pub type MiddlewareError = Box<dyn core::error::Error + Send + Sync>;
+
+/// Hooks called around each request sent by a [`MusicBrainzClient`], on both the sync and async paths,
+/// whatever its [`Transport`](crate::client::transport::Transport) is.
+///
+/// The middlewares of a client are called in the order they were added.
+///
+/// ## Example
+/// ```rust
+/// # use std::sync::Arc;
+/// # use musicbrainz_rs::client::MusicBrainzClient;
+/// # use musicbrainz_rs::client::middleware::Middleware;
+/// # use musicbrainz_rs::client::middleware::ResponseInfo;
+/// struct Logger;
+///
+/// impl Middleware for Logger {
+///     fn after_receive(&self, response: &ResponseInfo<'_>) {
+///         println!("{} {} in {:?}", response.status, response.uri, response.elapsed);
+///     }
+/// }
+///
+/// let client = MusicBrainzClient::builder()
+///     .middlewares(vec![Arc::new(Logger) as Arc<dyn Middleware>])
+///     .build();
+/// ```
+pub trait Middleware: Send + Sync {
+    /// Called before a request is given to the client's [`Transport`](crate::client::transport::Transport).
+    /// Returning an error cancels the request.
+    fn before_send(&self, _request: &mut Request<()>) -> Result<(), MiddlewareError> {
+        Ok(())
+    }
+
+    /// Called once the response to a request has been received, before it is parsed
+    fn after_receive(&self, _response: &ResponseInfo<'_>) {}
+
+    /// Called when the [`Transport`](crate::client::transport::Transport) failed to send a request, without receiving a response
+    fn on_error(&self, _error: &(dyn core::error::Error + Send + Sync), _elapsed: Duration) {}
+}
+
+/// The response of a request, given to [`Middleware::after_receive`]
+#[derive(Debug)]
+pub struct ResponseInfo<'a> {
+    /// The uri the request was sent to
+    pub uri: &'a Uri,
+    pub status: StatusCode,
+    pub headers: &'a HeaderMap,
+
+    /// The time taken to send the request and receive the response. This includes the time spent in the rate limiter.
+    pub elapsed: Duration,
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+impl MusicBrainzClient {
+    /// Run the `before_send` hooks of the middlewares on the request
+    pub(crate) fn before_send(
+        &self,
+        mut request: Request<()>,
+    ) -> Result<Request<()>, ApiEndpointError> {
+        use snafu::ResultExt as _;
+
+        use crate::api::MiddlewareSnafu;
+
+        for middleware in &self.middlewares {
+            middleware
+                .before_send(&mut request)
+                .context(MiddlewareSnafu)?;
+        }
+
+        Ok(request)
+    }
+}
+
+#[cfg(all(test, any(feature = "sync", feature = "async")))]
+mod test {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    struct Recorder {
+        name: &'static str,
+        calls: Arc<Mutex<Vec<&'static str>>>,
+        fail: bool,
+    }
+
+    impl Middleware for Recorder {
+        fn before_send(&self, _request: &mut Request<()>) -> Result<(), MiddlewareError> {
+            self.calls.lock().unwrap().push(self.name);
+
+            if self.fail {
+                return Err("injected fault".into());
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn should_run_middlewares_in_order() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let middleware = |name, fail| -> Arc<dyn Middleware> {
+            Arc::new(Recorder {
+                name,
+                calls: calls.clone(),
+                fail,
+            })
+        };
+
+        let client = MusicBrainzClient::builder()
+            .middlewares(vec![
+                middleware("first", false),
+                middleware("second", true),
+                middleware("third", false),
+            ])
+            .build();
+
+        let request = Request::get("https://musicbrainz.org/ws/2/artist")
+            .body(())
+            .unwrap();
+
+        assert!(client.before_send(request).is_err());
+        assert_eq!(*calls.lock().unwrap(), vec!["first", "second"]);
+    }
+}
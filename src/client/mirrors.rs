@@ -0,0 +1,446 @@
+use core::fmt::Debug;
+use core::fmt::Formatter;
+#[cfg(any(feature = "sync", feature = "async"))]
+use core::time::Duration;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::borrow::Cow;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::collections::HashMap;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::sync::Mutex;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::sync::PoisonError;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::time::Instant;
+
+use api_bindium::ApiClient;
+
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::MusicBrainzClient;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::api::ApiEndpointError;
+use crate::client::auth::Redacted;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::metrics::MetricsKey;
+
+/// A MusicBrainz server to fall back to when the main one of a [`MusicBrainzClient`](crate::MusicBrainzClient) fails.
+///
+/// ## Example
+/// ```rust
+/// # use musicbrainz_rs::client::MusicBrainzClient;
+/// # use musicbrainz_rs::client::mirrors::MusicBrainzMirror;
+/// let client = MusicBrainzClient::builder()
+///     .musicbrainz_authority("localhost:5000".to_string())
+///     .musicbrainz_use_https(false)
+///     .fallback_mirrors(vec![
+///         MusicBrainzMirror::builder().authority("musicbrainz.org").build(),
+///     ])
+///     .build();
+/// ```
+#[derive(Clone, bon::Builder)]
+pub struct MusicBrainzMirror {
+    /// Authority (host and optional port) of the mirror.
+    #[builder(into)]
+    pub authority: String,
+
+    /// Whether to use HTTPS for this mirror. Defaults to `true`.
+    #[builder(default = true)]
+    pub use_https: bool,
+
+    /// The API client sending the requests to this mirror, with its own rate limit.
+    /// Defaults to `None`, where the main `api_client` (and its rate limit) is used.
+    pub api_client: Option<ApiClient>,
+
+    #[cfg(feature = "basic_auth")]
+    /// Basic auth credentials for this mirror. Defaults to `None`, where the main credentials are used.
+    pub basic_auth_credentials: Option<(String, String)>,
+
+    /// OAuth access token for this mirror. Defaults to `None`, where the main token is used.
+    pub bearer_token: Option<String>,
+}
+
+impl Debug for MusicBrainzMirror {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let mut debug = f.debug_struct("MusicBrainzMirror");
+        debug
+            .field("authority", &self.authority)
+            .field("use_https", &self.use_https)
+            .field("api_client", &self.api_client);
+
+        #[cfg(feature = "basic_auth")]
+        debug.field(
+            "basic_auth_credentials",
+            &Redacted(&self.basic_auth_credentials),
+        );
+
+        debug
+            .field("bearer_token", &Redacted(&self.bearer_token))
+            .finish()
+    }
+}
+
+/// The result of a query, along with the host that served it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Served<T> {
+    pub value: T,
+
+    /// The authority of the server that answered the query
+    pub host: String,
+}
+
+impl<T> Served<T> {
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+/// Keeps track of the servers that recently failed
+#[cfg(any(feature = "sync", feature = "async"))]
+#[derive(Debug, Default)]
+pub(crate) struct MirrorHealth {
+    /// When each failing server can be tried again
+    cooldowns: Mutex<HashMap<String, Instant>>,
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+impl MirrorHealth {
+    fn is_cooling_down(&self, authority: &str) -> bool {
+        self.cooldowns
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(authority)
+            .is_some_and(|until| *until > Instant::now())
+    }
+
+    fn mark_failed(&self, authority: &str, cooldown: Duration) {
+        self.cooldowns
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(authority.to_string(), Instant::now() + cooldown);
+    }
+
+    fn mark_ok(&self, authority: &str) {
+        self.cooldowns
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(authority);
+    }
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+impl MusicBrainzClient {
+    /// Send the request to each server in turn until one of them answers.
+    #[cfg(feature = "sync")]
+    pub(crate) fn with_failover<T>(
+        &self,
+        key: &MetricsKey,
+        send: impl Fn(&Self) -> Result<T, ApiEndpointError>,
+    ) -> Result<Served<T>, ApiEndpointError> {
+        let mut last_error = None;
+
+        for (attempt, client) in self.endpoint_clients().iter().enumerate() {
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("attempt", attempt);
+
+            if attempt != 0 {
+                self.record_retry(key);
+            }
+
+            match send(client) {
+                Ok(value) => return Ok(self.served_by(client, value)),
+                Err(err) if should_failover(&err) => {
+                    self.failed_on(client);
+                    last_error = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(last_error.expect("There is always at least one endpoint to try"))
+    }
+
+    /// Send the request to each server of `endpoints` in turn until one of them answers.
+    ///
+    /// The endpoints are taken from [`Self::endpoint_clients`] by the caller, so that the request futures can borrow them.
+    #[cfg(feature = "async")]
+    pub(crate) async fn with_failover_async<'a, T, Fut>(
+        &self,
+        key: &MetricsKey,
+        endpoints: &'a [Cow<'a, Self>],
+        send: impl Fn(&'a Self) -> Fut,
+    ) -> Result<Served<T>, ApiEndpointError>
+    where
+        Fut: Future<Output = Result<T, ApiEndpointError>>,
+    {
+        let mut last_error = None;
+
+        for (attempt, client) in endpoints.iter().enumerate() {
+            #[cfg(feature = "tracing")]
+            tracing::Span::current().record("attempt", attempt);
+
+            if attempt != 0 {
+                self.record_retry(key);
+            }
+
+            match send(client).await {
+                Ok(value) => return Ok(self.served_by(client, value)),
+                Err(err) if should_failover(&err) => {
+                    self.failed_on(client);
+                    last_error = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Err(last_error.expect("There is always at least one endpoint to try"))
+    }
+
+    /// Return an error if a server error (5xx) should be retried on the next server
+    pub(crate) fn check_server_error(
+        &self,
+        status: api_bindium::ureq::http::StatusCode,
+    ) -> Result<(), ApiEndpointError> {
+        use snafu::OptionExt as _;
+
+        use crate::api::ServerSnafu;
+
+        if self.fail_on_server_error && status.is_server_error() {
+            return None.context(ServerSnafu {
+                status: status.as_u16(),
+                host: self.musicbrainz_authority.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// The clients of each server, in the order they should be tried. The servers in cooldown are tried last.
+    pub(crate) fn endpoint_clients(&self) -> Vec<Cow<'_, Self>> {
+        if self.fallback_mirrors.is_empty() {
+            return vec![Cow::Borrowed(self)];
+        }
+
+        let mut main = self.clone();
+        main.fallback_mirrors = Vec::new();
+
+        let (mut clients, cooling_down): (Vec<_>, Vec<_>) = core::iter::once(main)
+            .chain(
+                self.fallback_mirrors
+                    .iter()
+                    .map(|mirror| self.with_mirror(mirror)),
+            )
+            .partition(|client| {
+                !self
+                    .mirror_health
+                    .is_cooling_down(&client.musicbrainz_authority)
+            });
+        clients.extend(cooling_down);
+
+        let last = clients.len() - 1;
+        clients
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut client)| {
+                client.fail_on_server_error = i != last;
+                Cow::Owned(client)
+            })
+            .collect()
+    }
+
+    fn with_mirror(&self, mirror: &MusicBrainzMirror) -> Self {
+        let mut client = self.clone();
+        client.fallback_mirrors = Vec::new();
+        client.musicbrainz_authority = mirror.authority.clone();
+        client.musicbrainz_use_https = mirror.use_https;
+
+        if let Some(api_client) = &mirror.api_client {
+            client.api_client = api_client.clone();
+        }
+
+        #[cfg(feature = "basic_auth")]
+        if mirror.basic_auth_credentials.is_some() {
+            client.basic_auth_credentials = mirror.basic_auth_credentials.clone();
+        }
+
+        if mirror.bearer_token.is_some() {
+            client.bearer_token = mirror.bearer_token.clone();
+        }
+
+        client
+    }
+
+    fn served_by<T>(&self, client: &Self, value: T) -> Served<T> {
+        if !self.fallback_mirrors.is_empty() {
+            self.mirror_health.mark_ok(&client.musicbrainz_authority);
+        }
+
+        Served {
+            value,
+            host: client.musicbrainz_authority.clone(),
+        }
+    }
+
+    fn failed_on(&self, client: &Self) {
+        if !self.fallback_mirrors.is_empty() {
+            self.mirror_health
+                .mark_failed(&client.musicbrainz_authority, self.mirror_cooldown);
+        }
+    }
+}
+
+/// Whether the error comes from the server being unreachable or failing, and another server should be tried.
+///
+/// Any transient error of the transport fails over, except when the transport can't send the request at all.
+#[cfg(any(feature = "sync", feature = "async"))]
+fn should_failover(err: &ApiEndpointError) -> bool {
+    use crate::client::transport::TransportProviderError;
+
+    if !err.is_transient() {
+        return false;
+    }
+
+    let mut source: Option<&dyn core::error::Error> = Some(err);
+    while let Some(err) = source {
+        if err.is::<TransportProviderError>() {
+            return false;
+        }
+
+        source = err.source();
+    }
+
+    true
+}
+
+#[cfg(all(test, any(feature = "sync", feature = "async")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_try_mirrors_in_cooldown_last() {
+        let client = MusicBrainzClient::builder()
+            .musicbrainz_authority("localhost:5000".to_string())
+            .fallback_mirrors(vec![
+                MusicBrainzMirror::builder()
+                    .authority("mirror.example")
+                    .build(),
+                MusicBrainzMirror::builder()
+                    .authority("musicbrainz.org")
+                    .build(),
+            ])
+            .build();
+
+        client.failed_on(&client);
+
+        let hosts = client
+            .endpoint_clients()
+            .iter()
+            .map(|client| {
+                (
+                    client.musicbrainz_authority.clone(),
+                    client.fail_on_server_error,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            hosts,
+            vec![
+                ("mirror.example".to_string(), true),
+                ("musicbrainz.org".to_string(), true),
+                ("localhost:5000".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sync")]
+    fn should_failover_on_server_errors() {
+        let client = MusicBrainzClient::builder()
+            .fallback_mirrors(vec![
+                MusicBrainzMirror::builder()
+                    .authority("mirror.example")
+                    .build(),
+            ])
+            .build();
+
+        let key = MetricsKey::new("artist", crate::client::metrics::QueryKind::Fetch);
+        let served = client.with_failover(&key, |client| {
+            client.check_server_error(api_bindium::ureq::http::StatusCode::BAD_GATEWAY)?;
+            Ok(client.musicbrainz_authority.clone())
+        });
+
+        assert_eq!(served.unwrap().host, "mirror.example");
+        assert_eq!(
+            client
+                .stats()
+                .get("artist", crate::client::metrics::QueryKind::Fetch)
+                .unwrap()
+                .retries,
+            1
+        );
+    }
+
+    /// A transport that can't connect to the main server
+    #[cfg(feature = "sync")]
+    struct Unreachable;
+
+    #[cfg(feature = "sync")]
+    impl crate::client::transport::Transport for Unreachable {
+        fn send(
+            &self,
+            request: api_bindium::ureq::http::Request<()>,
+        ) -> Result<
+            api_bindium::ureq::http::Response<crate::client::transport::ResponseBody>,
+            crate::client::transport::TransportError,
+        > {
+            if request.uri().host() == Some("localhost") {
+                return Err(std::io::Error::from(std::io::ErrorKind::ConnectionRefused).into());
+            }
+
+            Ok(api_bindium::ureq::http::Response::new(
+                r#"{"name": "Nirvana"}"#.into(),
+            ))
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "sync")]
+    fn should_failover_on_custom_transport_errors() {
+        use std::sync::Arc;
+
+        use crate::client::scheduler::RequestPriority;
+
+        let client = MusicBrainzClient::builder()
+            .musicbrainz_authority("localhost:5000".to_string())
+            .transport(Arc::new(Unreachable))
+            .fallback_mirrors(vec![
+                MusicBrainzMirror::builder()
+                    .authority("mirror.example")
+                    .build(),
+            ])
+            .build();
+
+        let key = MetricsKey::new("artist", crate::client::metrics::QueryKind::Fetch);
+        let served = client.with_failover(&key, |client| {
+            let request = api_bindium::ureq::http::Request::get(format!(
+                "https://{}/ws/2/artist",
+                client.musicbrainz_authority
+            ))
+            .body(())
+            .unwrap();
+
+            client.send_request(
+                &key,
+                RequestPriority::default(),
+                Ok(request),
+                |status, _, body| {
+                    crate::MusicBrainzParser::<serde_json::Value>::parse_reader(status, body)
+                },
+            )
+        });
+
+        let served = served.unwrap();
+        assert_eq!(served.host, "mirror.example");
+        assert_eq!(served.value["name"], "Nirvana");
+    }
+}
@@ -1,22 +1,69 @@
-#[cfg(feature = "netrc")]
+use core::fmt::Debug;
+use core::fmt::Formatter;
+use core::time::Duration;
 use std::sync::Arc;
-use std::sync::LazyLock;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::time::Instant;
+
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::ureq::http::Request;
 
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::ApiEndpointError;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::HttpRequestError;
 use crate::api::endpoints::MusicBrainzAPIEnpoints;
+use crate::api::parser::ResponseFormat;
+use crate::client::auth::Redacted;
+#[cfg(feature = "async")]
+use crate::client::coalescing::InFlightRequests;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::metrics::ClientMetrics;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::metrics::MetricsKey;
+use crate::client::middleware::Middleware;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::mirrors::MirrorHealth;
+use crate::client::mirrors::MusicBrainzMirror;
 use crate::client::music_brainz_client_builder::State;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::scheduler::RequestPriority;
+use crate::client::scheduler::RequestScheduler;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::scheduler::SchedulerPermit;
+#[cfg(feature = "sync")]
+use crate::client::timeout::Deadline;
+use crate::client::transport::Transport;
 use api_bindium::ApiClient;
 use api_bindium::ureq::Agent;
+use api_bindium::ureq::Proxy;
 use api_bindium::ureq::config::Config;
 #[cfg(feature = "netrc")]
 use bon::__::IsUnset;
 #[cfg(feature = "netrc")]
 use netrc::Netrc;
 
+pub(crate) mod auth;
+#[cfg(feature = "async")]
+pub(crate) mod coalescing;
+pub mod config;
+pub mod global;
+pub mod metrics;
+pub mod middleware;
+pub mod mirrors;
+pub mod scheduler;
+#[cfg(all(feature = "tracing", any(feature = "sync", feature = "async")))]
+pub(crate) mod spans;
+#[cfg(any(feature = "sync", feature = "async"))]
+pub(crate) mod timeout;
+pub mod transport;
+
 pub(crate) const DEFAULT_USER_AGENT: &str =
     concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
-pub static MUSICBRAINZ_CLIENT: LazyLock<MusicBrainzClient> =
-    LazyLock::new(MusicBrainzClient::default);
+/// The client used by the `execute` methods of the queries. Scoped clients of [`global::with_global_client`] are ignored.
+#[deprecated(note = "Use `client::global::global_client` instead")]
+pub static MUSICBRAINZ_CLIENT: global::LegacyGlobalClient = global::LegacyGlobalClient::new();
 
 /// Api client for the MB api
 ///
@@ -27,10 +74,33 @@ pub static MUSICBRAINZ_CLIENT: LazyLock<MusicBrainzClient> =
 ///
 /// This allows "bursts" of 5 requests before limiting yourself to the API's classic rate.
 /// So you may keep it in mind when designing your apps that you have 5 "free" requests
-#[derive(Debug, Clone, bon::Builder)]
+#[derive(Clone, bon::Builder)]
 pub struct MusicBrainzClient {
-    /// The inner API client.
-    #[builder(default = MusicBrainzClient::default_api_client())]
+    /// The maximum time a query can take, including the time spent waiting for the scheduler and rate limiter.
+    /// Queries can override it with their own `timeout`. Defaults to `None`, where queries never time out.
+    ///
+    /// Blocking requests are cancelled by the transport, which receives the time left in a [`RequestTimeout`](transport::RequestTimeout).
+    pub timeout: Option<Duration>,
+
+    /// The User-Agent sent with the requests. Defaults to the name and version of this crate.
+    ///
+    /// See [`Self::new`] for what it should contain.
+    /// This is applied on the default `api_client`. If you provide your own, you need to configure it yourself.
+    #[builder(into, default = DEFAULT_USER_AGENT.to_string())]
+    pub user_agent: String,
+
+    /// The maximum time to wait for the connection to the server. Defaults to `None`, where the connection never times out.
+    ///
+    /// This is applied on the default `api_client`. If you provide your own, you need to configure it yourself.
+    pub connect_timeout: Option<Duration>,
+
+    /// The proxy to send the requests through. Defaults to `None`.
+    ///
+    /// This is applied on the default `api_client`. If you provide your own, you need to configure it yourself.
+    pub proxy: Option<Proxy>,
+
+    /// The inner API client, sending the requests when no `transport` is set.
+    #[builder(default = MusicBrainzClient::create_api_client(&user_agent, connect_timeout, proxy.clone()))]
     pub api_client: ApiClient,
 
     /// Whether to use HTTPS for the MusicBrainz API. Defaults to `true`.
@@ -45,6 +115,31 @@ pub struct MusicBrainzClient {
     #[builder(default = "http://coverartarchive.org".to_string())]
     pub coverart_archive_url: String,
 
+    /// The format of the responses requested to the MusicBrainz API. Defaults to JSON.
+    ///
+    /// The cover art archive always answers in JSON.
+    #[builder(default)]
+    pub response_format: ResponseFormat,
+
+    /// Other MusicBrainz servers to try, in order, when the main one can't be reached or answers with a server error (5xx).
+    /// Defaults to none.
+    #[builder(default)]
+    pub fallback_mirrors: Vec<MusicBrainzMirror>,
+
+    /// How long a failing server is tried last, after the other ones. Defaults to 60 seconds.
+    #[builder(default = Duration::from_secs(60))]
+    pub mirror_cooldown: Duration,
+
+    #[cfg(any(feature = "sync", feature = "async"))]
+    #[builder(skip)]
+    pub(crate) mirror_health: Arc<MirrorHealth>,
+
+    /// Whether a server error should be returned as [`ApiEndpointError::ServerError`](crate::ApiEndpointError::ServerError)
+    /// so that the next server gets tried
+    #[cfg(any(feature = "sync", feature = "async"))]
+    #[builder(skip)]
+    pub(crate) fail_on_server_error: bool,
+
     #[cfg(feature = "basic_auth")]
     /// Basic auth credentials for the API. May be required for some musicbrainz_domains.
     pub basic_auth_credentials: Option<(String, String)>,
@@ -52,6 +147,94 @@ pub struct MusicBrainzClient {
     #[cfg(feature = "netrc")]
     /// Parsed `.netrc` file for per-host credential lookup at request time.
     pub netrc: Option<Arc<Netrc>>,
+
+    /// OAuth access token, sent as a `Bearer` authorization if no basic auth credentials are set.
+    pub bearer_token: Option<String>,
+
+    /// Hooks called around each request, in order. Defaults to none.
+    #[builder(default)]
+    pub middlewares: Vec<Arc<dyn Middleware>>,
+
+    /// The HTTP backend sending the requests. Defaults to `None`, where the requests are sent by a
+    /// [`UreqTransport`](crate::client::transport::UreqTransport) over `api_client`.
+    ///
+    /// See [`Transport`] for what a transport needs to handle.
+    pub transport: Option<Arc<dyn Transport>>,
+
+    /// Whether the queries should fail with [`ApiEndpointError::DryRunError`](crate::ApiEndpointError::DryRunError),
+    /// holding the request they would have sent, instead of sending it. Defaults to `false`.
+    ///
+    /// Nothing is sent, so the scheduler, rate limiter and metrics aren't involved.
+    #[builder(default)]
+    pub dry_run: bool,
+
+    /// Scheduler ordering the requests by priority before they get sent.
+    /// Defaults to `None`, where requests are sent in the order they are made.
+    pub scheduler: Option<Arc<RequestScheduler>>,
+
+    #[cfg(feature = "async")]
+    /// Whether identical async requests should share a single API call. Defaults to `false`.
+    ///
+    /// When enabled, tasks executing a query with the same URI as a request already in flight will wait for its response
+    /// instead of sending their own, and receive a clone of the parsed result.
+    ///
+    /// The error of the request is shared too, as an [`ApiEndpointError::SharedError`](crate::ApiEndpointError::SharedError),
+    /// unless it is [transient](crate::ApiEndpointError::is_transient). The waiting tasks then send the request again.
+    #[builder(default)]
+    pub coalesce_requests: bool,
+
+    #[cfg(feature = "async")]
+    #[builder(skip)]
+    pub(crate) in_flight: Arc<InFlightRequests>,
+
+    /// When the blocking query being sent times out
+    #[cfg(feature = "sync")]
+    #[builder(skip)]
+    pub(crate) deadline: Option<Deadline>,
+
+    /// The counters behind [`Self::stats`]
+    #[cfg(any(feature = "sync", feature = "async"))]
+    #[builder(skip)]
+    pub(crate) metrics: Arc<ClientMetrics>,
+}
+
+impl Debug for MusicBrainzClient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let mut debug = f.debug_struct("MusicBrainzClient");
+        debug
+            .field("timeout", &self.timeout)
+            .field("user_agent", &self.user_agent)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("proxy", &Redacted(&self.proxy))
+            .field("api_client", &self.api_client)
+            .field("musicbrainz_use_https", &self.musicbrainz_use_https)
+            .field("musicbrainz_authority", &self.musicbrainz_authority)
+            .field("coverart_archive_url", &self.coverart_archive_url)
+            .field("response_format", &self.response_format)
+            .field("fallback_mirrors", &self.fallback_mirrors)
+            .field("mirror_cooldown", &self.mirror_cooldown);
+
+        #[cfg(feature = "basic_auth")]
+        debug.field(
+            "basic_auth_credentials",
+            &Redacted(&self.basic_auth_credentials),
+        );
+
+        #[cfg(feature = "netrc")]
+        debug.field("netrc", &Redacted(&self.netrc));
+
+        debug
+            .field("bearer_token", &Redacted(&self.bearer_token))
+            .field("middlewares", &self.middlewares.len())
+            .field("transport", &self.transport.is_some())
+            .field("dry_run", &self.dry_run)
+            .field("scheduler", &self.scheduler);
+
+        #[cfg(feature = "async")]
+        debug.field("coalesce_requests", &self.coalesce_requests);
+
+        debug.finish_non_exhaustive()
+    }
 }
 
 impl<S: State> MusicBrainzClientBuilder<S> {
@@ -81,16 +264,27 @@ impl MusicBrainzClient {
     /// let client = MusicBrainzClient::new("MyApp/1.0.0 (http://myapp.example.com)");
     /// ```
     pub fn new(user_agent: &str) -> Self {
-        let agent_conf = Config::builder().user_agent(user_agent).build();
-        let agent = Agent::new_with_config(agent_conf);
-
-        Self::builder()
-            .api_client(ApiClient::builder().agent(agent).build())
-            .build()
+        Self::builder().user_agent(user_agent).build()
     }
 
     pub fn default_api_client() -> ApiClient {
-        let agent_conf = Config::builder().user_agent(DEFAULT_USER_AGENT).build();
+        Self::create_api_client(DEFAULT_USER_AGENT, None, None)
+    }
+
+    /// Create an [`ApiClient`] with the given user agent, connection timeout and proxy.
+    ///
+    /// Its agent reports when the requests leave the rate limiter, so that the time waited for it is [measured](metrics::QueryStats::rate_limit_wait).
+    pub fn create_api_client(
+        user_agent: &str,
+        connect_timeout: Option<Duration>,
+        proxy: Option<Proxy>,
+    ) -> ApiClient {
+        let agent_conf = Config::builder()
+            .user_agent(user_agent)
+            .timeout_connect(connect_timeout)
+            .proxy(proxy)
+            .middleware(transport::record_sent_at)
+            .build();
         let agent = Agent::new_with_config(agent_conf);
 
         ApiClient::builder().agent(agent).build()
@@ -101,10 +295,104 @@ impl MusicBrainzClient {
         MusicBrainzAPIEnpoints::builder()
             .use_https(self.musicbrainz_use_https)
             .authority(self.musicbrainz_authority.clone())
+            .maybe_authorization(
+                self.musicbrainz_authority
+                    .parse()
+                    .ok()
+                    .and_then(|authority| self.authorization(&authority)),
+            )
             .build()
     }
 }
 
+#[cfg(any(feature = "sync", feature = "async"))]
+impl MusicBrainzClient {
+    /// In [dry run](Self::dry_run) mode, fail with the request that would have been sent
+    pub(crate) fn check_dry_run(
+        &self,
+        request: impl FnOnce() -> Result<Request<()>, HttpRequestError>,
+    ) -> Result<(), ApiEndpointError> {
+        use snafu::ResultExt as _;
+
+        use crate::api::DryRunSnafu;
+        use crate::api::HttpRequestSnafu;
+
+        if !self.dry_run {
+            return Ok(());
+        }
+
+        let request = request().context(HttpRequestSnafu)?;
+        DryRunSnafu {
+            request: Box::new(request),
+        }
+        .fail()
+    }
+
+    /// Wait for the scheduler to let a request of this priority through
+    #[cfg(feature = "sync")]
+    pub(crate) fn wait_for_turn(
+        &self,
+        key: &MetricsKey,
+        priority: RequestPriority,
+    ) -> Result<Option<SchedulerPermit<'_>>, ApiEndpointError> {
+        let Some(scheduler) = self.scheduler.as_deref() else {
+            return Ok(None);
+        };
+
+        let started = Instant::now();
+        let permit = match self.deadline {
+            Some(deadline) => scheduler
+                .acquire_timeout(priority, deadline.remaining()?)
+                .ok_or_else(|| deadline.timed_out()),
+            None => Ok(scheduler.acquire(priority)),
+        };
+        self.record_scheduler_wait(key, started.elapsed());
+
+        permit.map(Some)
+    }
+
+    /// Wait for the scheduler to let a request of this priority through
+    #[cfg(feature = "async")]
+    pub(crate) async fn wait_for_turn_async(
+        &self,
+        key: &MetricsKey,
+        priority: RequestPriority,
+    ) -> Option<SchedulerPermit<'_>> {
+        let scheduler = self.scheduler.as_deref()?;
+
+        let started = Instant::now();
+        let permit = scheduler.acquire_async(priority).await;
+        self.record_scheduler_wait(key, started.elapsed());
+
+        Some(permit)
+    }
+}
+
+#[cfg(feature = "async")]
+impl MusicBrainzClient {
+    /// Run the request. If request coalescing is enabled, share the result with identical requests sent at the same time.
+    pub(crate) async fn coalesce<T>(
+        &self,
+        key: &MetricsKey,
+        uri: String,
+        request: impl Future<Output = Result<T, ApiEndpointError>>,
+    ) -> Result<T, ApiEndpointError>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        if !self.coalesce_requests {
+            return request.await;
+        }
+
+        let (result, shared) = self.in_flight.run(uri, request).await;
+        if shared {
+            self.record_cache_hit(key);
+        }
+
+        result
+    }
+}
+
 impl Default for MusicBrainzClient {
     fn default() -> Self {
         Self::builder().build()
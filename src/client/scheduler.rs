@@ -0,0 +1,397 @@
+use core::num::NonZeroUsize;
+use core::pin::Pin;
+use core::task::Context;
+use core::task::Poll;
+use core::task::Waker;
+use core::time::Duration;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+use std::time::Instant;
+
+/// The priority class of a request in the [`RequestScheduler`]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Default, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestPriority {
+    /// Requests a user is actively waiting on
+    Interactive,
+
+    #[default]
+    Normal,
+
+    /// Bulk jobs that can wait for the other requests
+    Background,
+}
+
+impl RequestPriority {
+    const ALL: [Self; 3] = [Self::Interactive, Self::Normal, Self::Background];
+
+    fn index(self) -> usize {
+        match self {
+            Self::Interactive => 0,
+            Self::Normal => 1,
+            Self::Background => 2,
+        }
+    }
+}
+
+/// Orders the requests of a [`MusicBrainzClient`](crate::MusicBrainzClient) by priority before they reach the rate limiter.
+///
+/// Only `max_in_flight` requests can be sent at once. The others are queued, and the next request to be sent
+/// is picked from the highest priority queue.
+///
+/// To make sure that lower priorities still make progress, a queue that has been passed over `max_skips` times in a row
+/// will have its next request sent, whatever the priority of the other queues is.
+///
+/// ## Example
+/// ```rust
+/// # use std::sync::Arc;
+/// # use musicbrainz_rs::client::MusicBrainzClient;
+/// # use musicbrainz_rs::client::scheduler::RequestScheduler;
+/// let client = MusicBrainzClient::builder()
+///     .scheduler(Arc::new(RequestScheduler::builder().max_skips(8).build()))
+///     .build();
+/// ```
+#[derive(Debug, bon::Builder)]
+pub struct RequestScheduler {
+    /// The number of requests that can be sent at the same time. Defaults to `1`
+    #[builder(default = NonZeroUsize::MIN)]
+    max_in_flight: NonZeroUsize,
+
+    /// How many times in a row a queue can be passed over by higher priority requests. Defaults to `4`
+    #[builder(default = 4)]
+    max_skips: u32,
+
+    #[builder(skip)]
+    state: Mutex<SchedulerState>,
+
+    #[builder(skip)]
+    condvar: Condvar,
+}
+
+impl RequestScheduler {
+    /// Wait until a request of this priority can be sent
+    pub fn acquire(&self, priority: RequestPriority) -> SchedulerPermit<'_> {
+        let mut state = self.lock();
+        let ticket = state.enqueue(priority, None);
+        self.dispatch(&mut state);
+
+        while !state.granted.remove(&ticket) {
+            state = self
+                .condvar
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+
+        SchedulerPermit { scheduler: self }
+    }
+
+    /// Wait until a request of this priority can be sent, or return `None` once the timeout is reached
+    pub fn acquire_timeout(
+        &self,
+        priority: RequestPriority,
+        timeout: Duration,
+    ) -> Option<SchedulerPermit<'_>> {
+        let deadline = Instant::now() + timeout;
+
+        let mut state = self.lock();
+        let ticket = state.enqueue(priority, None);
+        self.dispatch(&mut state);
+
+        while !state.granted.remove(&ticket) {
+            let Some(remaining) = deadline.checked_duration_since(Instant::now()) else {
+                state.queues[priority.index()].retain(|(queued, _)| *queued != ticket);
+                return None;
+            };
+
+            state = self
+                .condvar
+                .wait_timeout(state, remaining)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+
+        Some(SchedulerPermit { scheduler: self })
+    }
+
+    /// Wait until a request of this priority can be sent
+    pub async fn acquire_async(&self, priority: RequestPriority) -> SchedulerPermit<'_> {
+        Acquire {
+            scheduler: self,
+            priority,
+            ticket: None,
+        }
+        .await
+    }
+
+    /// Return the statistics of each priority queue
+    pub fn stats(&self) -> SchedulerStats {
+        let state = self.lock();
+        let class_stats = |priority: RequestPriority| PriorityStats {
+            queued: state.queues[priority.index()].len(),
+            ..state.stats[priority.index()]
+        };
+
+        SchedulerStats {
+            interactive: class_stats(RequestPriority::Interactive),
+            normal: class_stats(RequestPriority::Normal),
+            background: class_stats(RequestPriority::Background),
+            in_flight: state.in_flight,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SchedulerState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Grant the free slots to the queued requests
+    fn dispatch(&self, state: &mut SchedulerState) {
+        let mut granted_any = false;
+
+        while state.in_flight < self.max_in_flight.get() {
+            let Some(priority) = self.next_priority(state) else {
+                break;
+            };
+
+            let Some((ticket, waker)) = state.queues[priority.index()].pop_front() else {
+                break;
+            };
+
+            for other in RequestPriority::ALL {
+                if other == priority {
+                    state.skips[other.index()] = 0;
+                } else if !state.queues[other.index()].is_empty() {
+                    state.skips[other.index()] += 1;
+                }
+            }
+
+            state.in_flight += 1;
+            state.stats[priority.index()].sent += 1;
+            state.granted.insert(ticket);
+            granted_any = true;
+
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+
+        if granted_any {
+            self.condvar.notify_all();
+        }
+    }
+
+    /// Find the queue of the next request to send
+    fn next_priority(&self, state: &SchedulerState) -> Option<RequestPriority> {
+        let waiting = RequestPriority::ALL
+            .into_iter()
+            .filter(|priority| !state.queues[priority.index()].is_empty());
+
+        waiting
+            .clone()
+            .find(|priority| state.skips[priority.index()] >= self.max_skips)
+            .or_else(|| waiting.clone().next())
+    }
+
+    fn release(&self) {
+        let mut state = self.lock();
+        state.in_flight -= 1;
+        self.dispatch(&mut state);
+    }
+}
+
+impl Default for RequestScheduler {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+#[derive(Debug, Default)]
+struct SchedulerState {
+    next_ticket: u64,
+    in_flight: usize,
+    queues: [VecDeque<(u64, Option<Waker>)>; 3],
+    skips: [u32; 3],
+    granted: HashSet<u64>,
+    stats: [PriorityStats; 3],
+}
+
+impl SchedulerState {
+    fn enqueue(&mut self, priority: RequestPriority, waker: Option<Waker>) -> u64 {
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+
+        let queue = &mut self.queues[priority.index()];
+        queue.push_back((ticket, waker));
+
+        let stats = &mut self.stats[priority.index()];
+        stats.peak_queued = stats.peak_queued.max(queue.len());
+
+        ticket
+    }
+}
+
+/// Allows a request to be sent. The next request is let through once dropped.
+#[derive(Debug)]
+pub struct SchedulerPermit<'a> {
+    scheduler: &'a RequestScheduler,
+}
+
+impl Drop for SchedulerPermit<'_> {
+    fn drop(&mut self) {
+        self.scheduler.release();
+    }
+}
+
+struct Acquire<'a> {
+    scheduler: &'a RequestScheduler,
+    priority: RequestPriority,
+    ticket: Option<u64>,
+}
+
+impl<'a> Future for Acquire<'a> {
+    type Output = SchedulerPermit<'a>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let scheduler = self.scheduler;
+        let mut state = scheduler.lock();
+
+        let Some(ticket) = self.ticket else {
+            let ticket = state.enqueue(self.priority, Some(cx.waker().clone()));
+            self.ticket = Some(ticket);
+            scheduler.dispatch(&mut state);
+
+            if state.granted.remove(&ticket) {
+                self.ticket = None;
+                return Poll::Ready(SchedulerPermit { scheduler });
+            }
+
+            return Poll::Pending;
+        };
+
+        if state.granted.remove(&ticket) {
+            self.ticket = None;
+            return Poll::Ready(SchedulerPermit { scheduler });
+        }
+
+        // Still queued. Refresh the waker in case the task moved
+        if let Some((_, waker)) = state.queues[self.priority.index()]
+            .iter_mut()
+            .find(|(queued, _)| *queued == ticket)
+        {
+            *waker = Some(cx.waker().clone());
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        let Some(ticket) = self.ticket else {
+            return;
+        };
+
+        let mut state = self.scheduler.lock();
+
+        // The slot may have been granted right before the future got cancelled. Give it back
+        if state.granted.remove(&ticket) {
+            state.in_flight -= 1;
+            self.scheduler.dispatch(&mut state);
+            return;
+        }
+
+        state.queues[self.priority.index()].retain(|(queued, _)| *queued != ticket);
+    }
+}
+
+/// Snapshot of the queues of a [`RequestScheduler`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchedulerStats {
+    pub interactive: PriorityStats,
+    pub normal: PriorityStats,
+    pub background: PriorityStats,
+
+    /// The number of requests currently being sent
+    pub in_flight: usize,
+}
+
+impl SchedulerStats {
+    /// Return the statistics of a priority class
+    pub fn get(&self, priority: RequestPriority) -> &PriorityStats {
+        match priority {
+            RequestPriority::Interactive => &self.interactive,
+            RequestPriority::Normal => &self.normal,
+            RequestPriority::Background => &self.background,
+        }
+    }
+}
+
+/// Statistics of a priority class of a [`RequestScheduler`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PriorityStats {
+    /// The number of requests waiting to be sent
+    pub queued: usize,
+
+    /// The highest number of requests that have been waiting at the same time
+    pub peak_queued: usize,
+
+    /// The number of requests that have been let through
+    pub sent: u64,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_send_higher_priority_first() {
+        let scheduler = RequestScheduler::default();
+        let mut state = scheduler.lock();
+        state.in_flight = 1;
+
+        let background = state.enqueue(RequestPriority::Background, None);
+        let interactive = state.enqueue(RequestPriority::Interactive, None);
+
+        state.in_flight = 0;
+        scheduler.dispatch(&mut state);
+
+        assert!(state.granted.contains(&interactive));
+        assert!(!state.granted.contains(&background));
+        assert_eq!(state.stats[RequestPriority::Interactive.index()].sent, 1);
+    }
+
+    #[test]
+    fn should_not_starve_lower_priorities() {
+        let scheduler = RequestScheduler::builder().max_skips(2).build();
+        let mut state = scheduler.lock();
+        let background = state.enqueue(RequestPriority::Background, None);
+
+        for _ in 0..2 {
+            state.enqueue(RequestPriority::Interactive, None);
+            state.in_flight = 0;
+            scheduler.dispatch(&mut state);
+        }
+
+        state.enqueue(RequestPriority::Interactive, None);
+        state.in_flight = 0;
+        scheduler.dispatch(&mut state);
+
+        assert!(state.granted.contains(&background));
+    }
+
+    #[test]
+    fn should_stop_waiting_after_timeout() {
+        let scheduler = RequestScheduler::default();
+        let _permit = scheduler.acquire(RequestPriority::Normal);
+
+        let permit = scheduler.acquire_timeout(RequestPriority::Normal, Duration::from_millis(10));
+
+        assert!(permit.is_none());
+        assert_eq!(scheduler.stats().normal.queued, 0);
+    }
+}
@@ -0,0 +1,73 @@
+use tracing::Span;
+use tracing::field::Empty;
+
+use crate::client::metrics::MetricsKey;
+use crate::entity::Include;
+
+/// Create the span covering the execution of a query.
+///
+/// The fields left empty are recorded by the client as the request goes:
+/// - `attempt`: The number of the server tried, starting at 0 for the main one
+/// - `scheduler_wait_ms`: The time spent waiting for the scheduler
+/// - `rate_limit_wait_ms`: The time spent waiting for the rate limiter of the transport, if it reports it
+/// - `status`: The HTTP status of the last response
+/// - `error`: The kind of error the query failed with, if any
+pub(crate) fn query_span(
+    key: &MetricsKey,
+    query: &str,
+    includes: &[Include],
+    limit: Option<u8>,
+    offset: Option<u16>,
+) -> Span {
+    tracing::info_span!(
+        "musicbrainz_query",
+        entity = key.entity,
+        kind = ?key.kind,
+        query,
+        includes = %includes
+            .iter()
+            .map(Include::as_str)
+            .collect::<Vec<_>>()
+            .join("+"),
+        limit,
+        offset,
+        attempt = Empty,
+        scheduler_wait_ms = Empty,
+        rate_limit_wait_ms = Empty,
+        status = Empty,
+        error = Empty,
+    )
+}
+
+/// The id of the entity in the path of a lookup (ex: `artist/<id>`), or an empty string if there is none
+pub(crate) fn path_id(path: &str) -> &str {
+    path.split_once('/').map_or("", |(_, id)| id)
+}
+
+/// Create the span of an operation sending multiple requests, with a progress bar of `total` steps.
+/// The total can be updated with [`set_total`] if it isn't known yet.
+///
+/// The progress bar is only shown if a [`tracing_indicatif::IndicatifLayer`] is registered.
+pub(crate) fn progress_span(name: &'static str, entity: &str, total: usize) -> Span {
+    use tracing_indicatif::span_ext::IndicatifSpanExt as _;
+
+    let span = tracing::info_span!("musicbrainz_progress", operation = name, entity, total);
+    span.pb_set_length(u64::try_from(total).unwrap_or(u64::MAX));
+    span.pb_set_message(name);
+    span
+}
+
+/// Set the number of steps done in the progress bar of a [`progress_span`]
+pub(crate) fn advance(span: &Span, done: usize) {
+    use tracing_indicatif::span_ext::IndicatifSpanExt as _;
+
+    span.pb_set_position(u64::try_from(done).unwrap_or(u64::MAX));
+}
+
+/// Set the number of steps of the progress bar of a [`progress_span`], once it is known
+pub(crate) fn set_total(span: &Span, total: usize) {
+    use tracing_indicatif::span_ext::IndicatifSpanExt as _;
+
+    span.record("total", total);
+    span.pb_set_length(u64::try_from(total).unwrap_or(u64::MAX));
+}
@@ -0,0 +1,266 @@
+use core::time::Duration;
+#[cfg(feature = "sync")]
+use std::time::Instant;
+
+use crate::api::ApiEndpointError;
+#[cfg(feature = "sync")]
+use crate::client::MusicBrainzClient;
+
+/// The time a blocking query has left before timing out
+#[cfg(feature = "sync")]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Deadline {
+    at: Instant,
+    timeout: Duration,
+}
+
+#[cfg(feature = "sync")]
+impl Deadline {
+    /// Return the time left, or a timeout error if there's none
+    pub(crate) fn remaining(&self) -> Result<Duration, ApiEndpointError> {
+        self.at
+            .checked_duration_since(Instant::now())
+            .filter(|remaining| !remaining.is_zero())
+            .ok_or_else(|| self.timed_out())
+    }
+
+    pub(crate) fn timed_out(&self) -> ApiEndpointError {
+        use crate::api::TimeoutSnafu;
+
+        TimeoutSnafu {
+            timeout: self.timeout,
+        }
+        .build()
+    }
+
+    fn is_reached(&self) -> bool {
+        Instant::now() >= self.at
+    }
+}
+
+/// Run a blocking request, giving up once the timeout is reached.
+///
+/// Blocking IO can't be interrupted from the outside, so the request is sent on the current thread with the deadline set on the client.
+/// The scheduler stops waiting once it is reached, and the transport gets the time left in a
+/// [`RequestTimeout`](crate::client::transport::RequestTimeout) to cancel the request itself.
+/// A response received after the deadline is dropped, for the transports that don't honor it.
+#[cfg(feature = "sync")]
+pub(crate) fn run_with_timeout<T>(
+    client: &MusicBrainzClient,
+    timeout: Duration,
+    request: impl FnOnce(&MusicBrainzClient) -> Result<T, ApiEndpointError>,
+) -> Result<T, ApiEndpointError> {
+    let deadline = Deadline {
+        at: Instant::now() + timeout,
+        timeout,
+    };
+
+    let mut client = client.clone();
+    client.deadline = Some(deadline);
+
+    match request(&client) {
+        // The transport gave up because the deadline was reached
+        Err(ApiEndpointError::TransportError { .. }) if deadline.is_reached() => {
+            Err(deadline.timed_out())
+        }
+        result => result,
+    }
+}
+
+/// Run a request future, cancelling it once the timeout is reached.
+///
+/// The deadline is tracked by a [`futures_timer::Delay`], like the async waits of the rate limiter, so this doesn't depend on
+/// any async runtime.
+#[cfg(feature = "async")]
+pub(crate) async fn with_timeout<T>(
+    timeout: Option<Duration>,
+    request: impl Future<Output = Result<T, ApiEndpointError>>,
+) -> Result<T, ApiEndpointError> {
+    use core::pin::Pin;
+    use core::task::Poll;
+
+    use crate::api::TimeoutSnafu;
+
+    let Some(timeout) = timeout else {
+        return request.await;
+    };
+
+    let mut request = core::pin::pin!(request);
+    let mut delay = futures_timer::Delay::new(timeout);
+
+    core::future::poll_fn(|cx| {
+        if let Poll::Ready(output) = request.as_mut().poll(cx) {
+            return Poll::Ready(output);
+        }
+
+        match Pin::new(&mut delay).poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(TimeoutSnafu { timeout }.build())),
+            Poll::Pending => Poll::Pending,
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn should_time_out() {
+        let result = with_timeout(
+            Some(Duration::from_millis(10)),
+            core::future::pending::<Result<(), ApiEndpointError>>(),
+        )
+        .await;
+
+        assert!(result.is_err_and(|err| err.is_timeout()));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn should_finish_before_timeout() {
+        let result = with_timeout(Some(Duration::from_secs(10)), async { Ok(1) }).await;
+
+        assert!(result.is_ok_and(|val| val == 1));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn should_time_out_concurrent_requests() {
+        let short = with_timeout(
+            Some(Duration::from_millis(10)),
+            core::future::pending::<Result<(), ApiEndpointError>>(),
+        );
+        let long = with_timeout(
+            Some(Duration::from_millis(50)),
+            core::future::pending::<Result<(), ApiEndpointError>>(),
+        );
+
+        let (short, long) = tokio::join!(short, long);
+
+        assert!(short.is_err_and(|err| err.is_timeout()));
+        assert!(long.is_err_and(|err| err.is_timeout()));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn should_stop_waiting_for_the_scheduler_after_timeout() {
+        use std::sync::Arc;
+
+        use crate::client::metrics::MetricsKey;
+        use crate::client::metrics::QueryKind;
+        use crate::client::scheduler::RequestPriority;
+        use crate::client::scheduler::RequestScheduler;
+
+        let scheduler = Arc::new(RequestScheduler::default());
+        let client = MusicBrainzClient::builder()
+            .scheduler(scheduler.clone())
+            .build();
+        let key = MetricsKey::new("artist", QueryKind::Fetch);
+        let _permit = scheduler.acquire(RequestPriority::Normal);
+
+        let result = run_with_timeout(&client, Duration::from_millis(10), |client| {
+            client
+                .wait_for_turn(&key, RequestPriority::Normal)
+                .map(|_| ())
+        });
+
+        assert!(result.is_err_and(|err| err.is_timeout()));
+        assert_eq!(scheduler.stats().normal.queued, 0);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn should_give_the_time_left_to_the_transport() {
+        use std::sync::Arc;
+
+        use api_bindium::ureq::http::Request;
+        use api_bindium::ureq::http::Response;
+
+        use crate::client::metrics::MetricsKey;
+        use crate::client::metrics::QueryKind;
+        use crate::client::scheduler::RequestPriority;
+        use crate::client::transport::RequestTimeout;
+        use crate::client::transport::ResponseBody;
+        use crate::client::transport::Transport;
+        use crate::client::transport::TransportError;
+
+        /// Gives up once the time left is elapsed, like a transport cancelling the request
+        struct Slow;
+
+        impl Transport for Slow {
+            fn send(&self, request: Request<()>) -> Result<Response<ResponseBody>, TransportError> {
+                let RequestTimeout(timeout) = *request
+                    .extensions()
+                    .get()
+                    .ok_or("No timeout for the request")?;
+                std::thread::sleep(timeout);
+
+                Err("timed out".into())
+            }
+        }
+
+        let client = MusicBrainzClient::builder()
+            .transport(Arc::new(Slow))
+            .build();
+        let key = MetricsKey::new("artist", QueryKind::Fetch);
+        let request = Request::get("https://musicbrainz.org/ws/2/artist")
+            .body(())
+            .unwrap();
+
+        let result = run_with_timeout(&client, Duration::from_millis(10), |client| {
+            client.send_request(&key, RequestPriority::default(), Ok(request), |_, _, _| {
+                Ok(())
+            })
+        });
+
+        assert!(result.is_err_and(|err| err.is_timeout()));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn should_time_out_when_the_transport_ignores_the_time_left() {
+        use std::sync::Arc;
+
+        use api_bindium::ureq::http::Request;
+        use api_bindium::ureq::http::Response;
+
+        use crate::client::metrics::MetricsKey;
+        use crate::client::metrics::QueryKind;
+        use crate::client::scheduler::RequestPriority;
+        use crate::client::transport::ResponseBody;
+        use crate::client::transport::Transport;
+        use crate::client::transport::TransportError;
+
+        /// Answers after the deadline, without looking at the `RequestTimeout`
+        struct Late;
+
+        impl Transport for Late {
+            fn send(
+                &self,
+                _request: Request<()>,
+            ) -> Result<Response<ResponseBody>, TransportError> {
+                std::thread::sleep(Duration::from_millis(50));
+
+                Ok(Response::new(ResponseBody::empty()))
+            }
+        }
+
+        let client = MusicBrainzClient::builder()
+            .transport(Arc::new(Late))
+            .build();
+        let key = MetricsKey::new("artist", QueryKind::Fetch);
+        let request = Request::get("https://musicbrainz.org/ws/2/artist")
+            .body(())
+            .unwrap();
+
+        let result = run_with_timeout(&client, Duration::from_millis(10), |client| {
+            client.send_request(&key, RequestPriority::default(), Ok(request), |_, _, _| {
+                Ok(())
+            })
+        });
+
+        assert!(result.is_err_and(|err| err.is_timeout()));
+    }
+}
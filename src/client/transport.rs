@@ -0,0 +1,682 @@
+use core::fmt;
+use core::pin::Pin;
+use core::time::Duration;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::io::Read;
+#[cfg(any(feature = "sync", feature = "async"))]
+use std::sync::Arc;
+use std::time::Instant;
+
+use api_bindium::ApiClient;
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::ApiRequest;
+use api_bindium::ureq::Body;
+use api_bindium::ureq::SendBody;
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::ureq::http::HeaderMap;
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::ureq::http::HeaderValue;
+use api_bindium::ureq::http::Method;
+use api_bindium::ureq::http::Request;
+use api_bindium::ureq::http::Response;
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::ureq::http::StatusCode;
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::ureq::http::Uri;
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::ureq::http::header::CONTENT_LOCATION;
+#[cfg(any(feature = "sync", feature = "async"))]
+use api_bindium::ureq::http::header::CONTENT_TYPE;
+use api_bindium::ureq::middleware::MiddlewareNext;
+use snafu::Snafu;
+
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::MusicBrainzClient;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::ParsingError;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::api::ApiEndpointError;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::api::sans_io::HttpRequestError;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::metrics::MetricsKey;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::middleware::ResponseInfo;
+#[cfg(any(feature = "sync", feature = "async"))]
+use crate::client::scheduler::RequestPriority;
+
+/// The error returned by a [`Transport`]
+pub type TransportError = Box<dyn core::error::Error + Send + Sync>;
+
+/// The future returned by [`Transport::send_async`]
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Response<ResponseBody>, TransportError>> + Send + 'a>>;
+
+/// The body of a response received by a [`Transport`].
+///
+/// It is read by the parser as it deserializes the response, so the transports can hand over the stream of the connection
+/// instead of buffering large responses first.
+pub struct ResponseBody(Box<dyn Read + Send>);
+
+impl ResponseBody {
+    /// A body read from a stream, like the one of a connection
+    pub fn from_reader(reader: impl Read + Send + 'static) -> Self {
+        Self(Box::new(reader))
+    }
+
+    /// A body without content
+    pub fn empty() -> Self {
+        Self::from(Vec::new())
+    }
+}
+
+impl Read for ResponseBody {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl From<Vec<u8>> for ResponseBody {
+    fn from(body: Vec<u8>) -> Self {
+        Self::from_reader(Cursor::new(body))
+    }
+}
+
+impl From<String> for ResponseBody {
+    fn from(body: String) -> Self {
+        Self::from(body.into_bytes())
+    }
+}
+
+impl From<&'static [u8]> for ResponseBody {
+    fn from(body: &'static [u8]) -> Self {
+        Self::from_reader(body)
+    }
+}
+
+impl From<&'static str> for ResponseBody {
+    fn from(body: &'static str) -> Self {
+        Self::from(body.as_bytes())
+    }
+}
+
+impl fmt::Debug for ResponseBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseBody").finish_non_exhaustive()
+    }
+}
+
+/// Count the bytes read from the body of a response, for the metrics
+#[cfg(any(feature = "sync", feature = "async"))]
+struct CountingReader<R> {
+    inner: R,
+    read: u64,
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.read = self
+            .read
+            .saturating_add(u64::try_from(read).unwrap_or(u64::MAX));
+        Ok(read)
+    }
+}
+
+/// The time a blocking request has left before its query times out, set in the extensions of the request.
+///
+/// Blocking IO can't be interrupted from the outside, so the [`Transport`] needs to give up by itself once it is elapsed.
+/// The client only checks the deadline once the transport returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestTimeout(pub Duration);
+
+/// The time a request waited for the rate limiter of the transport, set in the extensions of its response.
+///
+/// The statistics of the client count it apart from the latency of the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitWait(pub Duration);
+
+/// When the agent of an [`ApiClient`] actually sent the request, once its rate limiter let it through
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(
+    not(any(feature = "sync", feature = "async")),
+    expect(dead_code, reason = "Only read when sending requests")
+)]
+struct SentAt(Instant);
+
+/// Agent middleware recording when the requests actually get sent, so that [`UreqTransport`] can tell the rate limiter's wait apart
+pub(crate) fn record_sent_at(
+    request: Request<SendBody>,
+    next: MiddlewareNext,
+) -> Result<Response<Body>, api_bindium::ureq::Error> {
+    let sent_at = Instant::now();
+    let mut response = next.handle(request)?;
+    response.extensions_mut().insert(SentAt(sent_at));
+
+    Ok(response)
+}
+
+/// The HTTP backend sending the requests of a [`MusicBrainzClient`](crate::MusicBrainzClient).
+///
+/// By default, the requests are sent by a [`UreqTransport`] over the client's `api_client`. Setting a transport on the client
+/// replaces it, while the scheduler, fallback mirrors, timeouts, middlewares and statistics keep working.
+///
+/// The requests are created with the queries' `to_http_request`, and the responses parsed with their `parse_response`.
+/// The body of the responses is a [`ResponseBody`], that can stream the content of the connection.
+/// This means that:
+/// - The transport needs to set the `User-Agent` header itself
+/// - Blocking requests of queries with a timeout have a [`RequestTimeout`] extension, after which the transport needs to give up.
+///   The client can't interrupt a blocking request: a transport ignoring it only gets its late response dropped for a timeout error
+/// - A transport with a rate limiter can report the time it waited for it in a [`RateLimitWait`] extension of the response
+/// - The cover art queries of images read the url of the image from the redirect's `Location` header.
+///   If the transport follows redirects, it needs to set the `Content-Location` header of the response to the final url instead
+///
+/// ## Example
+/// ```rust
+/// # use std::sync::Arc;
+/// # use musicbrainz_rs::api_bindium::ureq::http::Request;
+/// # use musicbrainz_rs::api_bindium::ureq::http::Response;
+/// # use musicbrainz_rs::client::MusicBrainzClient;
+/// # use musicbrainz_rs::client::transport::ResponseBody;
+/// # use musicbrainz_rs::client::transport::Transport;
+/// # use musicbrainz_rs::client::transport::TransportError;
+/// /// Answers every request with the same body
+/// struct InMemory(&'static str);
+///
+/// impl Transport for InMemory {
+///     fn send(&self, _request: Request<()>) -> Result<Response<ResponseBody>, TransportError> {
+///         Ok(Response::new(self.0.into()))
+///     }
+/// }
+///
+/// let client = MusicBrainzClient::builder()
+///     .transport(Arc::new(InMemory(r#"{"id": "5b11f4ce-a62d-471e-81fc-a69a8278c7da"}"#)))
+///     .build();
+/// ```
+pub trait Transport: Send + Sync {
+    /// Send a request, blocking until the response is received.
+    ///
+    /// By default, this returns an error, for the transports that only support async requests.
+    fn send(&self, _request: Request<()>) -> Result<Response<ResponseBody>, TransportError> {
+        Err(BlockingUnsupportedSnafu.build().into())
+    }
+
+    /// Send a request asynchronously.
+    ///
+    /// By default, this calls [`Self::send`], blocking the task until the response is received.
+    fn send_async(&self, request: Request<()>) -> TransportFuture<'_> {
+        Box::pin(core::future::ready(self.send(request)))
+    }
+}
+
+/// A [`Transport`] sending the requests with an [`ApiClient`]. This is the transport of the clients without one.
+///
+/// This is useful to wrap the default behavior in another transport. Only `GET` requests are supported.
+///
+/// The redirects followed by the agent are reported in the `Content-Location` header,
+/// and the body of images isn't downloaded, as only their url is used. The other bodies are streamed from the connection.
+/// Blocking requests with a [`RequestTimeout`] are sent by the agent directly, with its global timeout set to the time left.
+/// The time waited for the rate limiter is reported in a [`RateLimitWait`] if the agent was created with
+/// [`MusicBrainzClient::create_api_client`].
+#[derive(Debug, Clone)]
+pub struct UreqTransport {
+    pub api_client: ApiClient,
+}
+
+impl UreqTransport {
+    pub fn new(api_client: ApiClient) -> Self {
+        Self { api_client }
+    }
+
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn check_method(request: &Request<()>) -> Result<(), TransportError> {
+        if request.method() != Method::GET {
+            return Err(UnsupportedMethodSnafu {
+                method: request.method().clone(),
+            }
+            .build()
+            .into());
+        }
+
+        Ok(())
+    }
+
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn api_request(request: &Request<()>) -> Result<ApiRequest<()>, TransportError> {
+        Self::check_method(request)?;
+
+        let headers = request
+            .headers()
+            .iter()
+            .map(|(name, value)| Ok((name.to_string(), value.to_str()?.to_string())))
+            .collect::<Result<HashMap<_, _>, TransportError>>()?;
+
+        Ok(ApiRequest::builder()
+            .uri(request.uri().clone())
+            .headers(headers)
+            .verb(api_bindium::HTTPVerb::Get)
+            .parser(())
+            .build())
+    }
+
+    /// Convert the response of the agent. If it followed redirects, the final url is set as the `Content-Location`.
+    #[cfg(any(feature = "sync", feature = "async"))]
+    fn read_response(
+        request_uri: &Uri,
+        started: Instant,
+        response: Response<Body>,
+    ) -> Result<Response<ResponseBody>, TransportError> {
+        use api_bindium::ureq::ResponseExt as _;
+
+        let final_uri = response.get_uri().clone();
+        let (mut parts, body) = response.into_parts();
+
+        if final_uri != *request_uri {
+            parts.headers.insert(
+                CONTENT_LOCATION,
+                HeaderValue::try_from(final_uri.to_string())?,
+            );
+        }
+
+        if let Some(&SentAt(sent_at)) = parts.extensions.get() {
+            parts
+                .extensions
+                .insert(RateLimitWait(sent_at.saturating_duration_since(started)));
+        }
+
+        // Only the url of the images is used, so they aren't downloaded
+        let is_image = parts
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("image/"));
+        if is_image {
+            return Ok(Response::from_parts(parts, ResponseBody::empty()));
+        }
+
+        Ok(Response::from_parts(
+            parts,
+            ResponseBody::from_reader(body.into_reader()),
+        ))
+    }
+}
+
+impl Transport for UreqTransport {
+    #[cfg(feature = "sync")]
+    fn send(&self, request: Request<()>) -> Result<Response<ResponseBody>, TransportError> {
+        // The agent can only cancel a blocking request with its own timeouts, so the timed requests are sent with it directly
+        let started = Instant::now();
+
+        if let Some(&RequestTimeout(timeout)) = request.extensions().get() {
+            Self::check_method(&request)?;
+
+            let uri = request.uri().clone();
+            let agent = &self.api_client.agent;
+            let request = agent
+                .configure_request(request)
+                .timeout_global(Some(timeout))
+                .build();
+
+            return Self::read_response(&uri, started, agent.run(request)?);
+        }
+
+        let response = Self::api_request(&request)?.send(&self.api_client)?;
+
+        Self::read_response(request.uri(), started, response.inner.data)
+    }
+
+    #[cfg(feature = "async")]
+    fn send_async(&self, request: Request<()>) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let started = Instant::now();
+            let response = Self::api_request(&request)?
+                .send_async(&self.api_client)
+                .await?;
+
+            Self::read_response(request.uri(), started, response.inner.data)
+        })
+    }
+}
+
+#[cfg(any(feature = "sync", feature = "async"))]
+impl MusicBrainzClient {
+    /// The transport sending the requests: the one set on the client, or a [`UreqTransport`] over its `api_client`
+    pub(crate) fn transport(&self) -> Arc<dyn Transport> {
+        match &self.transport {
+            Some(transport) => transport.clone(),
+            None => Arc::new(UreqTransport::new(self.api_client.clone())),
+        }
+    }
+
+    /// Wait for the scheduler, then send the request with the client's transport and parse its response
+    #[cfg(feature = "sync")]
+    pub(crate) fn send_request<T>(
+        &self,
+        key: &MetricsKey,
+        priority: RequestPriority,
+        request: Result<Request<()>, HttpRequestError>,
+        parse: impl FnOnce(StatusCode, &HeaderMap, &mut dyn Read) -> Result<T, ParsingError>,
+    ) -> Result<T, ApiEndpointError> {
+        use snafu::ResultExt as _;
+
+        use crate::api::HttpRequestSnafu;
+        use crate::api::TransportSnafu;
+
+        let request = request.context(HttpRequestSnafu)?;
+        let _permit = self.wait_for_turn(key, priority)?;
+
+        let mut request = self.before_send(request)?;
+        if let Some(deadline) = self.deadline {
+            request
+                .extensions_mut()
+                .insert(RequestTimeout(deadline.remaining()?));
+        }
+        let uri = request.uri().clone();
+
+        let started = Instant::now();
+        let response = self.transport().send(request);
+        self.after_transport(key, &uri, response.as_ref(), started.elapsed());
+
+        // The transport may not have honored the `RequestTimeout`. The response is too late either way
+        if let Some(deadline) = self.deadline {
+            deadline.remaining()?;
+        }
+
+        self.parse_transport_response(key, response.context(TransportSnafu)?, parse)
+    }
+
+    /// Wait for the scheduler, then send the request with the client's transport and parse its response
+    #[cfg(feature = "async")]
+    pub(crate) async fn send_request_async<T>(
+        &self,
+        key: &MetricsKey,
+        priority: RequestPriority,
+        request: Result<Request<()>, HttpRequestError>,
+        parse: impl FnOnce(StatusCode, &HeaderMap, &mut dyn Read) -> Result<T, ParsingError>,
+    ) -> Result<T, ApiEndpointError> {
+        use snafu::ResultExt as _;
+
+        use crate::api::HttpRequestSnafu;
+        use crate::api::TransportSnafu;
+
+        let request = request.context(HttpRequestSnafu)?;
+        let _permit = self.wait_for_turn_async(key, priority).await;
+
+        let request = self.before_send(request)?;
+        let uri = request.uri().clone();
+
+        let transport = self.transport();
+        let started = Instant::now();
+        let response = transport.send_async(request).await;
+        self.after_transport(key, &uri, response.as_ref(), started.elapsed());
+
+        self.parse_transport_response(key, response.context(TransportSnafu)?, parse)
+    }
+
+    fn parse_transport_response<T>(
+        &self,
+        key: &MetricsKey,
+        response: Response<ResponseBody>,
+        parse: impl FnOnce(StatusCode, &HeaderMap, &mut dyn Read) -> Result<T, ParsingError>,
+    ) -> Result<T, ApiEndpointError> {
+        use snafu::ResultExt as _;
+
+        use crate::api::ParsingSnafu;
+        use crate::api::TransportSnafu;
+
+        self.check_server_error(response.status())?;
+
+        // The body is parsed as it is received, and only counted on the way
+        let (parts, body) = response.into_parts();
+        let mut body = CountingReader {
+            inner: body,
+            read: 0,
+        };
+        let parsed = parse(parts.status, &parts.headers, &mut body);
+        self.record_bytes_received(key, body.read);
+
+        match parsed {
+            Ok(value) => Ok(value),
+            // Losing the connection while reading the body is a failure of the transport, not of the response
+            Err(source) if source.is_read_error() => {
+                Err(TransportError::from(source)).context(TransportSnafu)
+            }
+            Err(source) => Err(source).context(ParsingSnafu),
+        }
+    }
+
+    /// Record the response in the metrics, then run the `after_receive` or `on_error` hooks of the middlewares
+    fn after_transport(
+        &self,
+        key: &MetricsKey,
+        uri: &Uri,
+        response: Result<&Response<ResponseBody>, &TransportError>,
+        elapsed: Duration,
+    ) {
+        self.record_response(key, response, elapsed);
+
+        for middleware in &self.middlewares {
+            match response {
+                Ok(response) => middleware.after_receive(&ResponseInfo {
+                    uri,
+                    status: response.status(),
+                    headers: response.headers(),
+                    elapsed,
+                }),
+                Err(error) => middleware.on_error(&**error, elapsed),
+            }
+        }
+    }
+}
+
+/// The errors of the transports provided by this crate
+#[derive(Debug, Snafu)]
+pub enum TransportProviderError {
+    #[snafu(display("This transport doesn't support blocking requests"))]
+    BlockingUnsupported {
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+
+    #[snafu(display("This transport doesn't support `{method}` requests"))]
+    UnsupportedMethod {
+        method: Method,
+
+        #[snafu(implicit)]
+        location: snafu::Location,
+
+        #[cfg(feature = "backtrace")]
+        backtrace: snafu::Backtrace,
+    },
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::client::metrics::QueryKind;
+    use crate::client::middleware::Middleware;
+    use crate::client::middleware::MiddlewareError;
+
+    struct InMemory {
+        status: StatusCode,
+        body: &'static str,
+    }
+
+    impl Transport for InMemory {
+        fn send(&self, _request: Request<()>) -> Result<Response<ResponseBody>, TransportError> {
+            let mut response = Response::new(self.body.into());
+            *response.status_mut() = self.status;
+            Ok(response)
+        }
+    }
+
+    #[test]
+    fn should_send_with_custom_transport() {
+        let transport = InMemory {
+            status: StatusCode::OK,
+            body: r#"{"name": "Nirvana"}"#,
+        };
+        let client = MusicBrainzClient::builder()
+            .transport(Arc::new(transport))
+            .build();
+        let key = MetricsKey::new("artist", QueryKind::Fetch);
+
+        let request = Request::get("https://musicbrainz.org/ws/2/artist")
+            .body(())
+            .unwrap();
+        let value = client
+            .send_request(
+                &key,
+                RequestPriority::default(),
+                Ok(request),
+                |status, _, body| {
+                    crate::MusicBrainzParser::<serde_json::Value>::parse_reader(status, body)
+                },
+            )
+            .unwrap();
+
+        assert_eq!(value["name"], "Nirvana");
+        assert_eq!(
+            client
+                .stats()
+                .get("artist", QueryKind::Fetch)
+                .unwrap()
+                .requests,
+            1
+        );
+    }
+
+    /// A connection that is lost after sending the start of the body
+    struct Interrupted;
+
+    impl Transport for Interrupted {
+        fn send(&self, _request: Request<()>) -> Result<Response<ResponseBody>, TransportError> {
+            let lost = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "connection lost");
+            let body = Read::chain(br#"{"name": "Nir"#.as_slice(), FailingRead(Some(lost)));
+            Ok(Response::new(ResponseBody::from_reader(body)))
+        }
+    }
+
+    struct FailingRead(Option<std::io::Error>);
+
+    impl Read for FailingRead {
+        fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+            self.0.take().map_or(Ok(0), Err)
+        }
+    }
+
+    #[test]
+    fn should_parse_the_body_as_it_is_read() {
+        let client = MusicBrainzClient::builder()
+            .transport(Arc::new(Interrupted))
+            .build();
+        let key = MetricsKey::new("artist", QueryKind::Fetch);
+
+        let request = Request::get("https://musicbrainz.org/ws/2/artist")
+            .body(())
+            .unwrap();
+        let result = client.send_request(
+            &key,
+            RequestPriority::default(),
+            Ok(request),
+            |status, _, body| {
+                crate::MusicBrainzParser::<serde_json::Value>::parse_reader(status, body)
+            },
+        );
+
+        // The connection was lost while parsing, which is an error of the transport
+        assert!(matches!(
+            result,
+            Err(ApiEndpointError::TransportError { .. })
+        ));
+        assert_eq!(
+            client
+                .stats()
+                .get("artist", QueryKind::Fetch)
+                .unwrap()
+                .bytes_received,
+            13
+        );
+    }
+
+    struct Traced(Arc<Mutex<Vec<String>>>);
+
+    impl Middleware for Traced {
+        fn before_send(&self, request: &mut Request<()>) -> Result<(), MiddlewareError> {
+            request
+                .headers_mut()
+                .insert("x-trace", HeaderValue::from_static("1"));
+            self.0.lock().unwrap().push("before_send".to_string());
+            Ok(())
+        }
+
+        fn after_receive(&self, response: &ResponseInfo<'_>) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("after_receive {}", response.status));
+        }
+
+        fn on_error(&self, error: &(dyn core::error::Error + Send + Sync), _elapsed: Duration) {
+            self.0.lock().unwrap().push(format!("on_error {error}"));
+        }
+    }
+
+    /// Answers the requests traced by the middleware, and fails on the others
+    struct RequireTrace;
+
+    impl Transport for RequireTrace {
+        fn send(&self, request: Request<()>) -> Result<Response<ResponseBody>, TransportError> {
+            if !request.headers().contains_key("x-trace") {
+                return Err("missing trace".into());
+            }
+            if request.uri().path() == "/unreachable" {
+                return Err("connection refused".into());
+            }
+
+            Ok(Response::new("{}".into()))
+        }
+    }
+
+    #[test]
+    fn should_run_middlewares_around_custom_transport() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let client = MusicBrainzClient::builder()
+            .transport(Arc::new(RequireTrace))
+            .middlewares(vec![Arc::new(Traced(calls.clone())) as Arc<dyn Middleware>])
+            .build();
+        let key = MetricsKey::new("artist", QueryKind::Fetch);
+        let send = |uri: &str| {
+            client.send_request(
+                &key,
+                RequestPriority::default(),
+                Ok(Request::get(uri).body(()).unwrap()),
+                |status, _, body| {
+                    crate::MusicBrainzParser::<serde_json::Value>::parse_reader(status, body)
+                },
+            )
+        };
+
+        assert!(send("https://musicbrainz.org/ws/2/artist").is_ok());
+        assert!(send("https://musicbrainz.org/unreachable").is_err());
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "before_send",
+                "after_receive 200 OK",
+                "before_send",
+                "on_error connection refused"
+            ]
+        );
+    }
+}
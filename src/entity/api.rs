@@ -11,6 +11,18 @@ pub struct MusicbrainzError {
 }
 
 impl MusicbrainzError {
+    pub fn new(error: String, help: String) -> Self {
+        Self { error, help }
+    }
+
+    /// The error returned when the requested entity doesn't exist
+    pub fn not_found() -> Self {
+        Self::new(
+            "Not Found".to_string(),
+            "For usage, please see: https://musicbrainz.org/development/mmd".to_string(),
+        )
+    }
+
     pub fn is_not_found(&self) -> bool {
         self.error == "Not Found"
     }
@@ -6,6 +6,7 @@ use crate::entity::Subquery;
 use crate::entity::alias::Alias;
 use crate::entity::genre::Genre;
 use crate::entity::lifespan::LifeSpan;
+use crate::entity::merge::impl_merge;
 use crate::entity::relations::Relation;
 use crate::entity::tag::Tag;
 
@@ -51,6 +52,15 @@ pub struct Area {
     pub score: Option<u8>,
 }
 
+impl_merge!(
+    Area,
+    required: [id, name, disambiguation, sort_name],
+    optional: [
+        area_type, relations, iso_3166_1_codes, aliases, annotation, type_id, life_span,
+        tags, genres, score,
+    ],
+);
+
 /// The type of a MusicBrainz area entity.
 /// Note that this enum is `non_exhaustive`; The list of area types is subject to change and these
 /// changes are only reflected in the DB, not in actual MB code.
@@ -1,11 +1,14 @@
 use super::{Include, Subquery};
+use crate::api::complete_lists::impl_included_lists;
 use crate::api::impl_browse_includes::impl_browse_includes;
 use crate::api::impl_relations_includes::impl_relations_includes;
+use crate::api::typed_fetch::impl_typed_includes;
 use crate::entity::BrowseBy;
 use crate::entity::alias::Alias;
 use crate::entity::area::Area;
 use crate::entity::genre::Genre;
 use crate::entity::lifespan::LifeSpan;
+use crate::entity::merge::impl_merge;
 use crate::entity::rating::Rating;
 use crate::entity::recording::Recording;
 use crate::entity::relations::Relation;
@@ -126,6 +129,16 @@ pub struct Artist {
     pub score: Option<u8>,
 }
 
+impl_merge!(
+    Artist,
+    required: [id, name, sort_name, disambiguation],
+    optional: [
+        artist_type_id, artist_type, gender_id, gender, area, begin_area, end_area, ipis,
+        isnis, relations, releases, works, release_groups, recordings, aliases, tags,
+        genres, rating, country, annotation, life_span, score,
+    ],
+);
+
 /// The type of a MusicBrainz artist entity.
 /// Note that this enum is `non_exhaustive`; The list of artist types is subject to change and these
 /// changes are only reflected in the DB, not in actual MB code.
@@ -250,6 +263,38 @@ impl_includes!(
 // Relationships includes
 impl_relations_includes!(Artist);
 
+impl_typed_includes!(
+    Artist,
+    fields: [
+        (Aliases, aliases: Vec<Alias>),
+        (Genres, genres: Vec<Genre>),
+        (Rating, rating: Rating),
+        (Recordings, recordings: Vec<Recording>),
+        (ReleaseGroups, release_groups: Vec<ReleaseGroup>),
+        (Releases, releases: Vec<Release>),
+        (Tags, tags: Vec<Tag>),
+        (Works, works: Vec<Work>),
+    ],
+    includes: [
+        (with_recordings, Include::Subquery(Subquery::Recordings), Recordings),
+        (with_releases, Include::Subquery(Subquery::Releases), Releases),
+        (with_release_groups, Include::Subquery(Subquery::ReleaseGroups), ReleaseGroups),
+        (with_aliases, Include::Subquery(Subquery::Aliases), Aliases),
+        (with_works, Include::Subquery(Subquery::Works), Works),
+        (with_tags, Include::Subquery(Subquery::Tags), Tags),
+        (with_rating, Include::Subquery(Subquery::Rating), Rating),
+        (with_genres, Include::Subquery(Subquery::Genres), Genres),
+    ]
+);
+
+impl_included_lists!(
+    Artist,
+    (releases, Release, by_artist),
+    (release_groups, ReleaseGroup, by_artist),
+    (recordings, Recording, by_artist),
+    (works, Work, by_artist)
+);
+
 impl_browse! {
 Artist,
    (by_area, BrowseBy::Area),
@@ -1,12 +1,24 @@
 use crate::entity::artist::Artist;
+#[cfg(feature = "interning")]
+use crate::entity::interning::deserialize_shared;
+use crate::entity::interning::{Shared, shared_eq};
 use serde::{Deserialize, Serialize};
 
 /// Artist credits indicate who is the main credited artist (or artists) for releases, release groups,
 /// tracks and recordings, and how they are credited. They consist of artists, with (optionally)
 /// their names as credited in the specific release, track, etc., and join phrases between them.
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ArtistCredit {
     pub name: String,
     pub joinphrase: Option<String>,
-    pub artist: Artist,
+    #[cfg_attr(feature = "interning", serde(deserialize_with = "deserialize_shared"))]
+    pub artist: Shared<Artist>,
+}
+
+impl PartialEq for ArtistCredit {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.joinphrase == other.joinphrase
+            && shared_eq(&self.artist, &other.artist)
+    }
 }
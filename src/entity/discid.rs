@@ -1,3 +1,4 @@
+use crate::entity::merge::impl_merge;
 use crate::entity::release::Release;
 use crate::entity::{Include, Relationship, Subquery};
 use serde::{Deserialize, Serialize};
@@ -18,6 +19,12 @@ pub struct Discid {
     pub releases: Option<Vec<Release>>,
 }
 
+impl_merge!(
+    Discid,
+    required: [id, offset_count, sectors, offsets],
+    optional: [releases],
+);
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[cfg_attr(
     feature = "legacy_serialize",
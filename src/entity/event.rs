@@ -5,6 +5,7 @@ use crate::entity::BrowseBy;
 use crate::entity::alias::Alias;
 use crate::entity::genre::Genre;
 use crate::entity::lifespan::LifeSpan;
+use crate::entity::merge::impl_merge;
 use crate::entity::rating::Rating;
 use crate::entity::relations::Relation;
 use crate::entity::tag::Tag;
@@ -136,6 +137,15 @@ pub struct Event {
     pub score: Option<u8>,
 }
 
+impl_merge!(
+    Event,
+    required: [id, name],
+    optional: [
+        event_type, cancelled, time, setlist, tags, relations, rating, aliases, annotation,
+        genres, life_span, disambiguation, type_id, score,
+    ],
+);
+
 #[derive(Debug, Default, Serialize, Deserialize, QueryBuilder)]
 pub struct EventSearchQuery {
     /// (part of) any alias attached to the artist (diacritics are ignored)
@@ -4,6 +4,7 @@ use crate::api::impl_relations_includes::impl_relations_includes;
 use crate::entity::BrowseBy;
 use crate::entity::alias::Alias;
 use crate::entity::genre::Genre;
+use crate::entity::merge::impl_merge;
 use crate::entity::relations::Relation;
 use crate::entity::tag::Tag;
 use serde::{Deserialize, Serialize};
@@ -50,6 +51,14 @@ pub struct Instrument {
     pub score: Option<u8>,
 }
 
+impl_merge!(
+    Instrument,
+    required: [id, name, instrument_type, type_id],
+    optional: [
+        description, disambiguation, relations, tags, aliases, genres, annotation, score,
+    ],
+);
+
 #[derive(Debug, Default, Serialize, Deserialize, QueryBuilder)]
 pub struct InstrumentSearchQuery {
     /// (part of) any alias attached to the instrument (diacritics are ignored)
@@ -0,0 +1,247 @@
+//! Sharing of the entities repeated within a response.
+//!
+//! In a release with a hundred tracks, the artist of the credits is usually the same on every track.
+//! With the `interning` feature, the artists of the [`ArtistCredit`](crate::entity::artist_credit::ArtistCredit)s,
+//! the areas of the [`ReleaseEvent`](crate::entity::release::ReleaseEvent)s and the labels of the
+//! [`LabelInfo`](crate::entity::label::LabelInfo)s are held in [`Shared`] values, which are then [`Arc`]s:
+//! all the identical instances with the same MBID in a response point to the same allocation.
+
+#[cfg(feature = "interning")]
+use core::any::Any;
+#[cfg(feature = "interning")]
+use core::any::TypeId;
+#[cfg(feature = "interning")]
+use core::cell::RefCell;
+#[cfg(feature = "interning")]
+use std::collections::HashMap;
+#[cfg(feature = "interning")]
+use std::sync::Arc;
+
+#[cfg(feature = "interning")]
+use serde::Deserialize;
+#[cfg(feature = "interning")]
+use serde::Deserializer;
+
+use crate::entity::area::Area;
+use crate::entity::artist::Artist;
+use crate::entity::label::Label;
+
+/// An entity that may be shared with the other identical entities of the response.
+///
+/// Without the `interning` feature, this is the entity itself.
+#[cfg(not(feature = "interning"))]
+pub type Shared<T> = T;
+
+/// An entity that may be shared with the other identical entities of the response.
+///
+/// With the `interning` feature, this is an [`Arc`]. Interning only happens while parsing a response,
+/// or inside [`with_interning`]. Otherwise, each deserialized entity is allocated on its own.
+#[cfg(feature = "interning")]
+pub type Shared<T> = Arc<T>;
+
+/// An entity that can be interned by its MBID
+pub trait Internable: PartialEq + Send + Sync + 'static {
+    fn mbid(&self) -> &str;
+}
+
+impl Internable for Artist {
+    fn mbid(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Internable for Area {
+    fn mbid(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Internable for Label {
+    fn mbid(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Compare two shared entities. Two instances pointing to the same allocation don't need to compare their fields.
+pub(crate) fn shared_eq<T: PartialEq>(this: &Shared<T>, other: &Shared<T>) -> bool {
+    #[cfg(feature = "interning")]
+    if Arc::ptr_eq(this, other) {
+        return true;
+    }
+
+    this == other
+}
+
+/// Compare two optional shared entities, like [`shared_eq`]
+pub(crate) fn shared_option_eq<T: PartialEq>(
+    this: Option<&Shared<T>>,
+    other: Option<&Shared<T>>,
+) -> bool {
+    match (this, other) {
+        (Some(this), Some(other)) => shared_eq(this, other),
+        (None, None) => true,
+        (Some(_), None) | (None, Some(_)) => false,
+    }
+}
+
+/// Deserialize an entity shared with the other identical entities of the response
+#[cfg(feature = "interning")]
+pub(crate) fn deserialize_shared<'de, D, T>(deserializer: D) -> Result<Arc<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Internable + Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(intern)
+}
+
+/// Deserialize an optional entity shared with the other identical entities of the response
+#[cfg(feature = "interning")]
+pub(crate) fn deserialize_shared_option<'de, D, T>(
+    deserializer: D,
+) -> Result<Option<Arc<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Internable + Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(|value| value.map(intern))
+}
+
+#[cfg(feature = "interning")]
+type Interner = HashMap<(TypeId, String), Arc<dyn Any + Send + Sync>>;
+
+#[cfg(feature = "interning")]
+thread_local! {
+    /// The entities interned in the current scope, if any
+    static INTERNER: RefCell<Option<Interner>> = const { RefCell::new(None) };
+}
+
+/// Share the identical entities deserialized inside `f`.
+///
+/// The parser already does this for each response. This is useful when deserializing entities by hand,
+/// like the pages of a cache. The interned entities are released at the end of the call.
+///
+/// The entities are keyed by MBID, and only shared if they are equal:
+/// an entity with more fields than another one with the same MBID gets its own allocation.
+#[cfg(feature = "interning")]
+pub fn with_interning<R>(f: impl FnOnce() -> R) -> R {
+    /// Restore the outer scope, even if `f` panics
+    struct ScopeGuard(Option<Interner>);
+
+    impl Drop for ScopeGuard {
+        fn drop(&mut self) {
+            INTERNER.set(self.0.take());
+        }
+    }
+
+    let _guard = ScopeGuard(INTERNER.replace(Some(HashMap::new())));
+    f()
+}
+
+/// Run `f`. Without the `interning` feature, there is nothing to share.
+#[cfg(not(feature = "interning"))]
+pub(crate) fn with_interning<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
+
+#[cfg(feature = "interning")]
+fn intern<T: Internable>(value: T) -> Arc<T> {
+    INTERNER.with_borrow_mut(|interner| {
+        let Some(interner) = interner else {
+            return Arc::new(value);
+        };
+
+        let key = (TypeId::of::<T>(), value.mbid().to_string());
+        if let Some(shared) = interner
+            .get(&key)
+            .and_then(|shared| Arc::clone(shared).downcast::<T>().ok())
+        {
+            if *shared == value {
+                return shared;
+            }
+
+            // Keep the first instance, as the next ones are likely to look like it
+            return Arc::new(value);
+        }
+
+        let shared = Arc::new(value);
+        interner.insert(key, Arc::clone(&shared) as Arc<dyn Any + Send + Sync>);
+        shared
+    })
+}
+
+#[cfg(all(test, feature = "interning"))]
+mod test {
+    use api_bindium::ureq::http::StatusCode;
+
+    use super::*;
+    use crate::MusicBrainzParser;
+    use crate::entity::artist_credit::ArtistCredit;
+    use crate::entity::recording::Recording;
+    use crate::entity::search::SearchResult;
+
+    const NIRVANA: &str = r#"{"id":"5b11f4ce-a62d-471e-81fc-a69a8278c7da","name":"Nirvana","sort-name":"Nirvana","disambiguation":"90s US grunge band"}"#;
+
+    fn recordings() -> String {
+        let recording = |id: u8| {
+            format!(
+                r#"{{"id":"00000000-0000-0000-0000-0000000000{id:02}","title":"Track {id}","artist-credit":[{{"name":"Nirvana","joinphrase":"","artist":{NIRVANA}}}]}}"#
+            )
+        };
+        format!(
+            r#"{{"created":"2024-01-01T00:00:00.000Z","count":2,"offset":0,"recordings":[{},{}]}}"#,
+            recording(1),
+            recording(2)
+        )
+    }
+
+    fn credited_artist(recording: &Recording) -> &Arc<Artist> {
+        &recording.artist_credit.as_ref().unwrap()[0].artist
+    }
+
+    #[test]
+    fn should_share_identical_entities_of_a_response() {
+        let result = MusicBrainzParser::<SearchResult<Recording>>::parse_body(
+            StatusCode::OK,
+            recordings().as_bytes(),
+        )
+        .unwrap();
+
+        let [first, second] = result.entities.as_slice() else {
+            panic!("Expected two recordings");
+        };
+        assert!(Arc::ptr_eq(credited_artist(first), credited_artist(second)));
+        assert_eq!(credited_artist(first).name, "Nirvana");
+    }
+
+    #[test]
+    fn should_only_intern_inside_a_scope() {
+        let result: SearchResult<Recording> = serde_json::from_str(&recordings()).unwrap();
+
+        let [first, second] = result.entities.as_slice() else {
+            panic!("Expected two recordings");
+        };
+        assert!(!Arc::ptr_eq(
+            credited_artist(first),
+            credited_artist(second)
+        ));
+        assert_eq!(credited_artist(first), credited_artist(second));
+    }
+
+    #[test]
+    fn should_not_share_different_entities_with_the_same_mbid() {
+        let credit = |artist: &str| -> ArtistCredit {
+            serde_json::from_str(&format!(r#"{{"name":"Nirvana","artist":{artist}}}"#)).unwrap()
+        };
+        let (full, partial) = with_interning(|| {
+            let full = credit(NIRVANA).artist;
+            let partial = credit(
+                r#"{"id":"5b11f4ce-a62d-471e-81fc-a69a8278c7da","name":"Nirvana","sort-name":"Nirvana"}"#,
+            )
+            .artist;
+            (full, partial)
+        });
+
+        assert!(!Arc::ptr_eq(&full, &partial));
+        assert_eq!(full.disambiguation, "90s US grunge band");
+    }
+}
@@ -1,11 +1,17 @@
 use super::{Include, Subquery};
+use crate::api::complete_lists::impl_included_lists;
 use crate::api::impl_browse_includes::impl_browse_includes;
 use crate::api::impl_relations_includes::impl_relations_includes;
+use crate::api::typed_fetch::impl_typed_includes;
 use crate::entity::BrowseBy;
 use crate::entity::alias::Alias;
 use crate::entity::area::Area;
 use crate::entity::genre::Genre;
+#[cfg(feature = "interning")]
+use crate::entity::interning::deserialize_shared_option;
+use crate::entity::interning::{Shared, shared_option_eq};
 use crate::entity::lifespan::LifeSpan;
+use crate::entity::merge::impl_merge;
 use crate::entity::rating::Rating;
 use crate::entity::relations::Relation;
 use crate::entity::release::Release;
@@ -60,6 +66,16 @@ pub struct Label {
     pub score: Option<u8>,
 }
 
+impl_merge!(
+    Label,
+    required: [id, name],
+    optional: [
+        type_id, label_type, sort_name, disambiguation, relations, country, label_code,
+        releases, aliases, tags, rating, genres, annotation, area, ipis, isnis, life_span,
+        score,
+    ],
+);
+
 #[derive(Debug, Default, Serialize, Deserialize, QueryBuilder)]
 pub struct LabelSearchQuery {
     /// (part of) any alias attached to the label (diacritics are ignored)
@@ -102,7 +118,7 @@ pub struct LabelSearchQuery {
 }
 
 /// The label information of the release
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(
     feature = "legacy_serialize",
     serde(rename_all(deserialize = "kebab-case"))
@@ -113,7 +129,18 @@ pub struct LabelInfo {
     pub catalog_number: Option<String>,
     /// The label of the release. Note: It is possible to insert a catalog number without setting a label.
     /// So the option is not safe to unwrap even when fetching all the relations
-    pub label: Option<Label>,
+    #[cfg_attr(
+        feature = "interning",
+        serde(default, deserialize_with = "deserialize_shared_option")
+    )]
+    pub label: Option<Shared<Label>>,
+}
+
+impl PartialEq for LabelInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.catalog_number == other.catalog_number
+            && shared_option_eq(self.label.as_ref(), other.label.as_ref())
+    }
 }
 
 /// The type of a MusicBrainz label entity.
@@ -174,3 +201,23 @@ impl_includes!(
 
 // Relationships includes
 impl_relations_includes!(Label);
+
+impl_typed_includes!(
+    Label,
+    fields: [
+        (Aliases, aliases: Vec<Alias>),
+        (Genres, genres: Vec<Genre>),
+        (Rating, rating: Rating),
+        (Releases, releases: Vec<Release>),
+        (Tags, tags: Vec<Tag>),
+    ],
+    includes: [
+        (with_releases, Include::Subquery(Subquery::Releases), Releases),
+        (with_tags, Include::Subquery(Subquery::Tags), Tags),
+        (with_aliases, Include::Subquery(Subquery::Aliases), Aliases),
+        (with_ratings, Include::Subquery(Subquery::Rating), Rating),
+        (with_genres, Include::Subquery(Subquery::Genres), Genres),
+    ]
+);
+
+impl_included_lists!(Label, (releases, Release, by_label));
@@ -0,0 +1,55 @@
+//! Combination of partial instances of the same entity.
+
+/// Implemented by the entities that can be combined with another instance of themselves.
+///
+/// This is useful to complete partial entities, like the ones of search results or artist credits,
+/// with the full record of a lookup. See [`Hydrate`](crate::api::hydrate::Hydrate).
+pub trait Merge {
+    /// Combine both instances, keeping the fields of `self` and filling its `None` fields with the ones of `other`.
+    ///
+    /// The fields that aren't optional are always the ones of `self`, and the fields themselves aren't merged:
+    /// a list of `self` is kept as is, even if `other` has more elements.
+    #[must_use]
+    fn merge(self, other: Self) -> Self;
+}
+
+/// Implement [`Merge`] for an entity. All its fields must be listed, so adding one without merging it doesn't compile.
+macro_rules! impl_merge {
+    ($ty: ident, required: [$($required: ident),+ $(,)?], optional: [$($optional: ident),+ $(,)?] $(,)?) => {
+        impl crate::entity::merge::Merge for $ty {
+            fn merge(self, other: Self) -> Self {
+                $ty {
+                    $($required: self.$required,)+
+                    $($optional: self.$optional.or(other.$optional),)+
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use impl_merge;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::entity::artist::Artist;
+
+    #[test]
+    fn should_prefer_the_fields_of_self() {
+        let credited: Artist = serde_json::from_str(
+            r#"{"id":"5b11f4ce-a62d-471e-81fc-a69a8278c7da","name":"Nirvana","sort-name":"Nirvana","disambiguation":"90s US grunge band","country":"US"}"#,
+        )
+        .unwrap();
+        let searched: Artist = serde_json::from_str(
+            r#"{"id":"5b11f4ce-a62d-471e-81fc-a69a8278c7da","name":"nirvana","sort-name":"Nirvana","disambiguation":"","country":"GB","score":100}"#,
+        )
+        .unwrap();
+
+        let merged = credited.merge(searched);
+
+        assert_eq!(merged.name, "Nirvana");
+        assert_eq!(merged.disambiguation, "90s US grunge band");
+        assert_eq!(merged.country.as_deref(), Some("US"));
+        assert_eq!(merged.score, Some(100));
+    }
+}
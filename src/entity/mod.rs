@@ -9,6 +9,8 @@ use crate::APIPath;
 use crate::Browse;
 use crate::Fetch;
 use crate::Search;
+use crate::api::hydrate::impl_hydrate;
+use crate::client::scheduler::RequestPriority;
 use crate::entity::annotation::Annotation;
 use crate::entity::area::Area;
 use crate::entity::artist::Artist;
@@ -29,15 +31,25 @@ use crate::entity::work::Work;
 macro_rules! impl_includes {
     ($ty: ty, $(($args:ident, $inc: expr_2021)),+) => {
         impl crate::FetchQuery<$ty> {
-               $(pub fn $args(&mut self) -> &mut Self  {
-                     self.0.include = self.0.include($inc).include.to_owned();
+               $(#[must_use]
+               pub fn $args(mut self) -> Self  {
+                     self.0.include($inc);
                    self
                })*
             }
 
         impl crate::SearchQuery<$ty> {
-               $(pub fn $args(&mut self) -> &mut Self  {
-                     self.inner.include = self.inner.include($inc).include.to_owned();
+               $(#[must_use]
+               pub fn $args(mut self) -> Self  {
+                     self.inner.include($inc);
+                   self
+               })*
+            }
+
+        impl crate::FetchManyQuery<$ty> {
+               $(#[must_use]
+               pub fn $args(mut self) -> Self  {
+                     self.inner.include($inc);
                    self
                })*
             }
@@ -47,7 +59,8 @@ macro_rules! impl_includes {
 macro_rules! impl_browse {
     ($ty: ty, $(($args:ident, $browse: expr_2021)),+) => {
         impl crate::BrowseQuery<$ty> {
-               $(pub fn $args(&mut self, id: &str) -> &mut Self  {
+               $(#[must_use]
+               pub fn $args(mut self, id: &str) -> Self  {
                     self.filter_entity = $browse.as_str().to_string();
                     self.filter_mbid = id.to_string();
                     self
@@ -56,20 +69,33 @@ macro_rules! impl_browse {
         }
 }
 
+macro_rules! impl_fetch_many {
+    ($(($t: ty, $field: expr_2021)),+) => {
+        $(impl crate::FetchMany for $t {
+            const MBID_SEARCH_FIELD: &'static str = $field;
+
+            fn mbid(&self) -> &str {
+                &self.id
+            }
+        })+
+    }
+}
+
 macro_rules! impl_fetchcoverart {
     ($($t: ty), +) => {
         $(impl crate::FetchCoverart for $t {
             fn get_coverart(&self) -> crate::FetchCoverartQuery<Self> {
-                let mut coverart_query = crate::FetchCoverartQuery(crate::CoverartQuery {
+                crate::FetchCoverartQuery(crate::CoverartQuery {
                     path: Self::path().to_string(),
                     phantom: PhantomData,
+                    priority: RequestPriority::default(),
+                    timeout: None,
                     target: CoverartTarget {
                         img_type: None,
                         img_res: None,
                     },
-                });
-                coverart_query.id(&self.id);
-                coverart_query
+                })
+                .id(&self.id)
             }
         })+
     }
@@ -88,8 +114,10 @@ pub mod discid;
 pub mod event;
 pub mod genre;
 pub mod instrument;
+pub mod interning;
 pub mod label;
 pub mod lifespan;
+pub mod merge;
 pub mod place;
 pub mod rating;
 pub mod recording;
@@ -116,8 +144,37 @@ impl Fetch for Series {}
 impl Fetch for Url {}
 impl Fetch for Discid {}
 
+impl_fetch_many!(
+    (Area, "aid"),
+    (Artist, "arid"),
+    (Event, "eid"),
+    (Instrument, "iid"),
+    (Label, "laid"),
+    (Recording, "rid"),
+    (Release, "reid"),
+    (ReleaseGroup, "rgid"),
+    (Series, "sid"),
+    (Work, "wid")
+);
+
 impl_fetchcoverart!(Release, ReleaseGroup);
 
+impl_hydrate!(
+    Area,
+    Artist,
+    Discid,
+    Event,
+    Instrument,
+    Label,
+    Place,
+    Recording,
+    Release,
+    ReleaseGroup,
+    Series,
+    Url,
+    Work
+);
+
 impl Browse for Artist {}
 impl Browse for Area {}
 impl Browse for Recording {}
@@ -246,6 +303,9 @@ pub(crate) enum Include {
 }
 
 impl Include {
+    /// The includes only passed as strings
+    const OTHERS: [&'static str; 2] = ["user-genres", "user-tags"];
+
     pub(crate) fn as_str(&self) -> &'static str {
         match self {
             Include::Subquery(i) => i.as_str(),
@@ -253,10 +313,49 @@ impl Include {
             Include::Other(val) => val,
         }
     }
+
+    /// Find the include with this value in the `inc` parameter
+    pub(crate) fn from_param(value: &str) -> Option<Self> {
+        Subquery::ALL
+            .into_iter()
+            .find(|subquery| subquery.as_str() == value)
+            .map(Include::Subquery)
+            .or_else(|| {
+                Relationship::ALL
+                    .into_iter()
+                    .find(|relationship| relationship.as_str() == value)
+                    .map(Include::Relationship)
+            })
+            .or_else(|| {
+                Self::OTHERS
+                    .into_iter()
+                    .find(|other| *other == value)
+                    .map(Include::Other)
+            })
+    }
+}
+
+impl Serialize for Include {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Include {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        Self::from_param(&value)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown include `{value}`")))
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
-#[expect(unused, reason = "Some subqueries aren't used in the api")]
 pub(crate) enum Subquery {
     Urls,
     Areas,
@@ -283,6 +382,31 @@ pub(crate) enum Subquery {
 }
 
 impl Subquery {
+    const ALL: [Self; 22] = [
+        Self::Urls,
+        Self::Areas,
+        Self::ArtistCredits,
+        Self::Labels,
+        Self::Events,
+        Self::Places,
+        Self::DiscIds,
+        Self::Releases,
+        Self::ReleasesWithDiscIds,
+        Self::ReleaseGroups,
+        Self::Recordings,
+        Self::Aliases,
+        Self::Works,
+        Self::Tags,
+        Self::Rating,
+        Self::Genres,
+        Self::Annotations,
+        Self::Artists,
+        Self::Series,
+        Self::Instruments,
+        Self::ISRCs,
+        Self::Media,
+    ];
+
     pub(crate) fn as_str(&self) -> &'static str {
         match self {
             Subquery::Labels => "labels",
@@ -332,6 +456,25 @@ pub(crate) enum Relationship {
 }
 
 impl Relationship {
+    const ALL: [Self; 16] = [
+        Self::Area,
+        Self::Artist,
+        Self::Event,
+        Self::Genre,
+        Self::Instrument,
+        Self::Label,
+        Self::Place,
+        Self::Recording,
+        Self::Release,
+        Self::ReleaseGroup,
+        Self::Series,
+        Self::Url,
+        Self::Work,
+        Self::RecordingLevel,
+        Self::ReleaseGroupLevel,
+        Self::WorkLevel,
+    ];
+
     pub(crate) fn as_str(&self) -> &'static str {
         match self {
             // Main entity relations
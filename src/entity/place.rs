@@ -10,6 +10,7 @@ use crate::entity::alias::Alias;
 use crate::entity::area::Area;
 use crate::entity::genre::Genre;
 use crate::entity::lifespan::LifeSpan;
+use crate::entity::merge::impl_merge;
 use crate::entity::relations::Relation;
 use crate::entity::tag::Tag;
 
@@ -57,6 +58,15 @@ pub struct Place {
     pub score: Option<u8>,
 }
 
+impl_merge!(
+    Place,
+    required: [id, name],
+    optional: [
+        place_type, type_id, life_span, coordinates, relations, area, address,
+        disambiguation, aliases, tags, genres, annotation, score,
+    ],
+);
+
 /// The type of a MusicBrainz place entity.
 /// Note that this enum is `non_exhaustive`; The list of place types is subject to change and these
 /// changes are only reflected in the DB, not in actual MB code.
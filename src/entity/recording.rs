@@ -1,10 +1,13 @@
+use crate::api::complete_lists::impl_included_lists;
 use crate::api::impl_browse_includes::impl_browse_includes;
 use crate::api::impl_relations_includes::impl_relations_includes;
+use crate::api::typed_fetch::impl_typed_includes;
 use crate::entity::BrowseBy;
 use crate::entity::alias::Alias;
 use crate::entity::artist_credit::ArtistCredit;
 use crate::entity::date_string::DateString;
 use crate::entity::genre::Genre;
+use crate::entity::merge::impl_merge;
 use crate::entity::rating::Rating;
 use crate::entity::relations::Relation;
 use crate::entity::release::Release;
@@ -66,6 +69,15 @@ pub struct Recording {
     pub score: Option<u8>,
 }
 
+impl_merge!(
+    Recording,
+    required: [id, title],
+    optional: [
+        video, length, disambiguation, isrcs, relations, releases, artist_credit, aliases,
+        tags, rating, genres, annotation, first_release_date, score,
+    ],
+);
+
 #[derive(Debug, Default, Serialize, Deserialize, QueryBuilder)]
 pub struct RecordingSearchQuery {
     /// (part of) any alias attached to the recording (diacritics are ignored)
@@ -185,3 +197,27 @@ impl_browse_includes!(
 
 // Relationships includes
 impl_relations_includes!(Recording);
+
+impl_typed_includes!(
+    Recording,
+    fields: [
+        (Aliases, aliases: Vec<Alias>),
+        (ArtistCredit, artist_credit: Vec<ArtistCredit>),
+        (Genres, genres: Vec<Genre>),
+        (Isrcs, isrcs: Vec<String>),
+        (Rating, rating: Rating),
+        (Releases, releases: Vec<Release>),
+        (Tags, tags: Vec<Tag>),
+    ],
+    includes: [
+        (with_artists, Include::Subquery(Subquery::Artists), ArtistCredit),
+        (with_releases, Include::Subquery(Subquery::Releases), Releases),
+        (with_tags, Include::Subquery(Subquery::Tags), Tags),
+        (with_aliases, Include::Subquery(Subquery::Aliases), Aliases),
+        (with_genres, Include::Subquery(Subquery::Genres), Genres),
+        (with_ratings, Include::Subquery(Subquery::Rating), Rating),
+        (with_isrcs, Include::Subquery(Subquery::ISRCs), Isrcs),
+    ]
+);
+
+impl_included_lists!(Recording, (releases, Release, by_recording));
@@ -76,3 +76,191 @@ pub enum RelationContent {
     Url(Box<Url>),
     Work(Box<Work>),
 }
+
+/// Relations kept as the raw JSON of the response, and only deserialized when needed.
+///
+/// The entities read with [`WithLazyRelations`] keep them in this form instead of in their `relations` field.
+/// Serializing them writes back the JSON they were read from.
+///
+/// simd-json can't give the raw JSON of a value. With the `simd-json` feature, the relations are read as a
+/// [`serde_json::Value`] and written back as JSON, which saves less.
+#[cfg(feature = "lazy_relations")]
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(not(feature = "simd-json"), derive(Deserialize))]
+#[serde(transparent)]
+pub struct LazyRelations(Box<serde_json::value::RawValue>);
+
+#[cfg(all(feature = "lazy_relations", feature = "simd-json"))]
+impl<'de> Deserialize<'de> for LazyRelations {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let relations = serde_json::Value::deserialize(deserializer)?;
+        serde_json::value::to_raw_value(&relations)
+            .map(Self)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "lazy_relations")]
+impl LazyRelations {
+    /// Deserialize the relations
+    pub fn decode(&self) -> Result<Vec<Relation>, serde_json::Error> {
+        serde_json::from_str(self.0.get())
+    }
+
+    /// The raw JSON of the relations
+    pub fn raw(&self) -> &serde_json::value::RawValue {
+        &self.0
+    }
+}
+
+#[cfg(feature = "lazy_relations")]
+impl PartialEq for LazyRelations {
+    /// Compare the decoded relations, as the same relations can be formatted differently
+    fn eq(&self, other: &Self) -> bool {
+        self.0.get() == other.0.get() || self.decode().ok() == other.decode().ok()
+    }
+}
+
+/// An entity read with its relations kept as raw JSON, only deserialized when calling [`LazyRelations::decode`].
+///
+/// Heavily linked entities have thousands of relations, each holding the entity it targets. Keeping them raw
+/// saves the allocations of the relations that are never looked at. As they are held in [`Self::relations`],
+/// the `relations` field of the [`entity`](Self::entity) is left empty.
+///
+/// Fetch queries return this type after calling [`FetchQuery::with_lazy_relations`](crate::FetchQuery::with_lazy_relations).
+#[cfg(feature = "lazy_relations")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithLazyRelations<T> {
+    pub entity: T,
+    pub relations: Option<LazyRelations>,
+}
+
+/// A field of an entity, kept as JSON until the entity is deserialized from it
+#[cfg(all(feature = "lazy_relations", not(feature = "simd-json")))]
+type RawField = Box<serde_json::value::RawValue>;
+
+/// A field of an entity, kept as JSON until the entity is deserialized from it
+#[cfg(all(feature = "lazy_relations", feature = "simd-json"))]
+type RawField = serde_json::Value;
+
+#[cfg(all(feature = "lazy_relations", not(feature = "simd-json")))]
+fn field_deserializer(field: &RawField) -> &serde_json::value::RawValue {
+    field
+}
+
+#[cfg(all(feature = "lazy_relations", feature = "simd-json"))]
+fn field_deserializer(field: &RawField) -> &serde_json::Value {
+    field
+}
+
+#[cfg(feature = "lazy_relations")]
+impl<'de, T> Deserialize<'de> for WithLazyRelations<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error as _;
+        use serde::de::value::MapDeserializer;
+
+        let mut fields = HashMap::<String, RawField>::deserialize(deserializer)?;
+
+        let relations = match fields.remove("relations") {
+            Some(relations) => Option::<LazyRelations>::deserialize(field_deserializer(&relations)),
+            None => Ok(None),
+        }
+        .map_err(D::Error::custom)?;
+
+        let fields = fields
+            .iter()
+            .map(|(name, value)| (name.as_str(), field_deserializer(value)));
+        let entity = T::deserialize(MapDeserializer::<_, serde_json::Error>::new(fields))
+            .map_err(D::Error::custom)?;
+
+        Ok(Self { entity, relations })
+    }
+}
+
+#[cfg(feature = "lazy_relations")]
+impl<T> Serialize for WithLazyRelations<T>
+where
+    T: Serialize,
+{
+    /// Serialize the entity, with the raw relations in place of its empty `relations` field
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::Error as _;
+        use serde::ser::SerializeMap as _;
+
+        let serde_json::Value::Object(fields) =
+            serde_json::to_value(&self.entity).map_err(S::Error::custom)?
+        else {
+            return Err(S::Error::custom("The entity isn't serialized as a map"));
+        };
+
+        let mut map = serializer.serialize_map(Some(fields.len()))?;
+        for (name, value) in &fields {
+            if name == "relations" {
+                map.serialize_entry(name, &self.relations)?;
+            } else {
+                map.serialize_entry(name, value)?;
+            }
+        }
+        map.end()
+    }
+}
+
+#[cfg(all(test, feature = "lazy_relations"))]
+mod test {
+    use api_bindium::ureq::http::StatusCode;
+
+    use super::*;
+    use crate::MusicBrainzParser;
+
+    const ARTIST: &str = r#"{"id":"5b11f4ce-a62d-471e-81fc-a69a8278c7da","name":"Nirvana","sort-name":"Nirvana","relations":[{"type":"member of band","type-id":"5be4c609-9afa-4ea0-910b-12ffb71e3821","target-type":"artist","direction":"backward","ended":true,"artist":{"id":"5c0b2a52-c0a6-4fd8-b3b0-f3fdc2e05e9d","name":"Kurt Cobain","sort-name":"Cobain, Kurt"}}]}"#;
+
+    #[test]
+    fn should_decode_lazy_relations_on_access() {
+        let artist = MusicBrainzParser::<WithLazyRelations<Artist>>::parse_body(
+            StatusCode::OK,
+            ARTIST.as_bytes(),
+        )
+        .unwrap();
+        let relations = artist.relations.unwrap().decode().unwrap();
+
+        assert_eq!(artist.entity.name, "Nirvana");
+        assert!(artist.entity.relations.is_none());
+        assert_eq!(relations.len(), 1);
+        assert_eq!(relations[0].relation_type, "member of band");
+    }
+
+    #[test]
+    fn should_keep_the_relations_of_the_entities() {
+        let artist =
+            MusicBrainzParser::<Artist>::parse_body(StatusCode::OK, ARTIST.as_bytes()).unwrap();
+
+        assert_eq!(artist.relations.unwrap().len(), 1);
+    }
+
+    // With simd-json, the relations go through a `serde_json::Value`, which doesn't keep the order of the keys
+    #[cfg(not(feature = "simd-json"))]
+    #[test]
+    fn should_serialize_lazy_relations_as_read() {
+        let artist: WithLazyRelations<Artist> = serde_json::from_str(ARTIST).unwrap();
+        let serialized: serde_json::Value = serde_json::to_value(&artist).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&artist.relations).unwrap(),
+            ARTIST[ARTIST.find(r#"[{"type""#).unwrap()..ARTIST.len() - 1]
+        );
+        assert_eq!(serialized["name"], "Nirvana");
+        assert_eq!(serialized["relations"][0]["type"], "member of band");
+    }
+}
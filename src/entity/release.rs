@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use super::{Include, Relationship, Subquery};
 use crate::api::impl_browse_includes::impl_browse_includes;
 use crate::api::impl_relations_includes::impl_relations_includes;
+use crate::api::typed_fetch::impl_typed_includes;
 use crate::entity::BrowseBy;
 use crate::entity::alias::Alias;
 use crate::entity::area::Area;
@@ -11,7 +12,11 @@ use crate::entity::artist_credit::ArtistCredit;
 use crate::entity::date_string::DateString;
 use crate::entity::discid::Disc;
 use crate::entity::genre::Genre;
+#[cfg(feature = "interning")]
+use crate::entity::interning::deserialize_shared_option;
+use crate::entity::interning::{Shared, shared_option_eq};
 use crate::entity::label::LabelInfo;
+use crate::entity::merge::impl_merge;
 use crate::entity::recording::Recording;
 use crate::entity::relations::Relation;
 use crate::entity::release_group::ReleaseGroup;
@@ -109,10 +114,31 @@ pub struct Release {
     pub score: Option<u8>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+impl_merge!(
+    Release,
+    required: [id, title],
+    optional: [
+        status_id, status, date, country, quality, barcode, disambiguation, packaging_id,
+        packaging, relations, release_group, artist_credit, media, label_info, tags,
+        aliases, genres, annotation, asin, text_representation, cover_art_archive,
+        release_events, score,
+    ],
+);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ReleaseEvent {
     pub date: Option<DateString>,
-    pub area: Option<Area>,
+    #[cfg_attr(
+        feature = "interning",
+        serde(default, deserialize_with = "deserialize_shared_option")
+    )]
+    pub area: Option<Shared<Area>>,
+}
+
+impl PartialEq for ReleaseEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.date == other.date && shared_option_eq(self.area.as_ref(), other.area.as_ref())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -2156,3 +2182,27 @@ impl_includes!(
 
 // Relationships includes
 impl_relations_includes!(Release);
+
+impl_typed_includes!(
+    Release,
+    fields: [
+        (Aliases, aliases: Vec<Alias>),
+        (ArtistCredit, artist_credit: Vec<ArtistCredit>),
+        (Genres, genres: Vec<Genre>),
+        (LabelInfo, label_info: Vec<LabelInfo>),
+        (Media, media: Vec<Media>),
+        (ReleaseGroup, release_group: ReleaseGroup),
+        (Tags, tags: Vec<Tag>),
+    ],
+    includes: [
+        (with_artists, Include::Subquery(Subquery::Artists), ArtistCredit),
+        (with_labels, Include::Subquery(Subquery::Labels), LabelInfo),
+        (with_recordings, Include::Subquery(Subquery::Recordings), Media),
+        (with_release_groups, Include::Subquery(Subquery::ReleaseGroups), ReleaseGroup),
+        (with_tags, Include::Subquery(Subquery::Tags), Tags),
+        (with_aliases, Include::Subquery(Subquery::Aliases), Aliases),
+        (with_genres, Include::Subquery(Subquery::Genres), Genres),
+        (with_artist_credits, Include::Subquery(Subquery::ArtistCredits), ArtistCredit),
+        (with_media, Include::Subquery(Subquery::Media), Media),
+    ]
+);
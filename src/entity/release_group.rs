@@ -1,11 +1,14 @@
 use super::{Include, Subquery};
+use crate::api::complete_lists::impl_included_lists;
 use crate::api::impl_browse_includes::impl_browse_includes;
 use crate::api::impl_relations_includes::impl_relations_includes;
+use crate::api::typed_fetch::impl_typed_includes;
 use crate::entity::BrowseBy;
 use crate::entity::alias::Alias;
 use crate::entity::artist_credit::ArtistCredit;
 use crate::entity::date_string::DateString;
 use crate::entity::genre::Genre;
+use crate::entity::merge::impl_merge;
 use crate::entity::rating::Rating;
 use crate::entity::relations::Relation;
 use crate::entity::release::Release;
@@ -65,6 +68,15 @@ pub struct ReleaseGroup {
     pub score: Option<u8>,
 }
 
+impl_merge!(
+    ReleaseGroup,
+    required: [id, secondary_type_ids, secondary_types, title, disambiguation],
+    optional: [
+        primary_type_id, primary_type, first_release_date, relations, artist_credit,
+        releases, tags, rating, aliases, genres, annotation, score,
+    ],
+);
+
 /// The primary type of a MusicBrainz release group.
 /// Note that this enum is `non_exhaustive`; The list of release types is subject to change and
 /// these changes are only reflected in the DB, not in actual MB code.
@@ -191,3 +203,25 @@ impl_includes!(
 
 // Relationships includes
 impl_relations_includes!(ReleaseGroup);
+
+impl_typed_includes!(
+    ReleaseGroup,
+    fields: [
+        (Aliases, aliases: Vec<Alias>),
+        (ArtistCredit, artist_credit: Vec<ArtistCredit>),
+        (Genres, genres: Vec<Genre>),
+        (Rating, rating: Rating),
+        (Releases, releases: Vec<Release>),
+        (Tags, tags: Vec<Tag>),
+    ],
+    includes: [
+        (with_artists, Include::Subquery(Subquery::Artists), ArtistCredit),
+        (with_releases, Include::Subquery(Subquery::Releases), Releases),
+        (with_tags, Include::Subquery(Subquery::Tags), Tags),
+        (with_aliases, Include::Subquery(Subquery::Aliases), Aliases),
+        (with_genres, Include::Subquery(Subquery::Genres), Genres),
+        (with_ratings, Include::Subquery(Subquery::Rating), Rating),
+    ]
+);
+
+impl_included_lists!(ReleaseGroup, (releases, Release, by_release_group));
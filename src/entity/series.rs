@@ -4,6 +4,7 @@ use crate::api::impl_relations_includes::impl_relations_includes;
 use crate::entity::BrowseBy;
 use crate::entity::alias::Alias;
 use crate::entity::genre::Genre;
+use crate::entity::merge::impl_merge;
 use crate::entity::relations::Relation;
 use crate::entity::tag::Tag;
 use serde::{Deserialize, Serialize};
@@ -49,6 +50,14 @@ pub struct Series {
     pub score: Option<u8>,
 }
 
+impl_merge!(
+    Series,
+    required: [id, name, series_type, disambiguation, type_id],
+    optional: [
+        relations, tags, aliases, genres, annotation, score,
+    ],
+);
+
 /// The type of a MusicBrainz series entity.
 /// Note that this enum is `non_exhaustive`; The list of series types is subject to change and these
 /// changes are only reflected in the DB, not in actual MB code.
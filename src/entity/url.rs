@@ -3,6 +3,7 @@ use super::Include;
 use serde::{Deserialize, Serialize};
 
 use crate::api::impl_relations_includes::impl_relations_includes;
+use crate::entity::merge::impl_merge;
 use crate::entity::relations::Relation;
 use crate::entity::tag::Tag;
 
@@ -21,6 +22,15 @@ pub struct Url {
     pub relations: Option<Vec<Relation>>,
 }
 
+impl_merge!(
+    Url,
+    required: [id, resource],
+    optional: [
+        tags,
+        relations,
+    ],
+);
+
 // impl_includes!(
 //     Url,
 //     (with_tags, Include::Subquery(Subquery::Tags)),
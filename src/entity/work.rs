@@ -4,6 +4,7 @@ use crate::api::impl_relations_includes::impl_relations_includes;
 use crate::entity::BrowseBy;
 use crate::entity::alias::Alias;
 use crate::entity::genre::Genre;
+use crate::entity::merge::impl_merge;
 use crate::entity::rating::Rating;
 use crate::entity::relations::Relation;
 use crate::entity::tag::Tag;
@@ -642,6 +643,15 @@ pub struct Work {
     pub score: Option<u8>,
 }
 
+impl_merge!(
+    Work,
+    required: [id, title],
+    optional: [
+        type_id, work_type, language, languages, iswcs, attributes, disambiguation,
+        relations, tags, rating, aliases, genres, annotation, score,
+    ],
+);
+
 #[derive(Debug, Default, Serialize, Deserialize, QueryBuilder)]
 pub struct WorkSearchQuery {
     /// (part of) any alias attached to the work (diacritics are ignored)
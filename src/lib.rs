@@ -52,18 +52,38 @@ pub mod utils;
 
 // === Re-exports ===
 pub use crate::api::ApiEndpointError;
+pub use crate::api::ErrorKind;
 pub use crate::api::browse_query::Browse;
 pub use crate::api::browse_query::BrowseQuery;
+pub use crate::api::complete_lists::Completed;
+pub use crate::api::complete_lists::IncludedLists;
 pub use crate::api::coverart_query::CoverartQuery;
 pub use crate::api::coverart_query::FetchCoverart;
 pub use crate::api::coverart_query::FetchCoverartQuery;
+pub use crate::api::fetch_many::FetchMany;
+pub use crate::api::fetch_many::FetchManyQuery;
 pub use crate::api::fetch_query::Fetch;
 pub use crate::api::fetch_query::FetchQuery;
+pub use crate::api::hydrate::Hydrate;
+#[cfg(any(feature = "sync", feature = "async"))]
+pub use crate::api::mock::MockMusicBrainzApi;
+#[cfg(feature = "sync")]
+pub use crate::api::musicbrainz_api::MusicBrainzApi;
+#[cfg(feature = "async")]
+pub use crate::api::musicbrainz_api::MusicBrainzApiAsync;
 pub use crate::api::parser::MusicBrainzParser;
+pub use crate::api::parser::ResponseFormat;
 pub use crate::api::parser::parsing_error::ParsingError;
+pub use crate::api::query::QueryParseError;
+pub use crate::api::sans_io::HttpRequestError;
 pub use crate::api::search_query::Search;
 pub use crate::api::search_query::SearchQuery;
+pub use crate::api::typed_fetch::Included;
+pub use crate::api::typed_fetch::TypedFetchQuery;
 pub use crate::client::MusicBrainzClient;
+pub use crate::client::config::MusicBrainzClientConfig;
+pub use crate::client::global::set_global_client;
+pub use crate::client::scheduler::RequestPriority;
 
 // === Crate Reexports ==
 
@@ -1,4 +1,11 @@
 pub use crate::Browse;
 pub use crate::Fetch;
 pub use crate::FetchCoverart;
+pub use crate::FetchMany;
+pub use crate::Hydrate;
 pub use crate::Search;
+#[cfg(feature = "sync")]
+pub use crate::api::musicbrainz_api::MusicBrainzApi;
+#[cfg(feature = "async")]
+pub use crate::api::musicbrainz_api::MusicBrainzApiAsync;
+pub use crate::entity::merge::Merge;
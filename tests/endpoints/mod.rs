@@ -1 +1,3 @@
 pub mod recordings;
+#[cfg(all(feature = "sync", feature = "xml"))]
+pub mod xml;
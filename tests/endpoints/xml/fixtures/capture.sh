@@ -0,0 +1,24 @@
+#!/usr/bin/env sh
+# Replace the fixtures of the XML tests with responses captured from the API, in both formats.
+set -eu
+
+USER_AGENT="musicbrainz_rs_testing/1.0.0 (https://github.com/RustyNova016/musicbrainz_rs)"
+API="https://musicbrainz.org/ws/2"
+FIXTURES="$(dirname "$0")"
+
+capture() {
+    for format in json xml; do
+        curl --fail --silent --show-error --user-agent "$USER_AGENT" "$API/$2&fmt=$format" >"$FIXTURES/$1.$format"
+        # Stay under the rate limit of the API
+        sleep 1
+    done
+}
+
+capture artist_lookup "artist/5b11f4ce-a62d-471e-81fc-a69a8278c7da?inc=aliases+tags+ratings+genres+artist-rels+url-rels"
+capture release_lookup "release/4642ee19-7790-3c8d-ab5e-d133de942db6?inc=artist-credits+labels+recordings+release-groups+media+discids+isrcs"
+capture recording_lookup "recording/de552ba4-572c-4c59-b2a9-0508619696ac?inc=artists+releases+isrcs+ratings+work-rels"
+capture release_group_lookup "release-group/2a0981fb-9593-3019-864b-ce934d97a16e?inc=artists+releases+tags"
+capture label_lookup "label/dc940013-b8a8-4362-a465-291026c04b42?inc=aliases+url-rels"
+capture work_lookup "work/22457dc0-ecbf-38f5-9056-11c858530a50?inc=aliases+artist-rels+recording-rels"
+capture release_browse "release?label=dc940013-b8a8-4362-a465-291026c04b42"
+capture artist_search "artist?query=artist:nirvana%20AND%20country:US"
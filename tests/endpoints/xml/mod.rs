@@ -0,0 +1,168 @@
+//! Check that the XML responses of the API are parsed into the same entities as the JSON ones.
+//!
+//! The responses are served from the paired `.json` and `.xml` files of `fixtures/`, written in the shape of the API's
+//! responses to the queries below. `fixtures/capture.sh` replaces them with responses captured from the API.
+use core::fmt::Debug;
+use std::path::Path;
+use std::sync::Arc;
+
+use musicbrainz_rs::ApiEndpointError;
+use musicbrainz_rs::Browse as _;
+use musicbrainz_rs::Fetch as _;
+use musicbrainz_rs::MusicBrainzClient;
+use musicbrainz_rs::ResponseFormat;
+use musicbrainz_rs::Search as _;
+use musicbrainz_rs::api_bindium::ureq::http::Request;
+use musicbrainz_rs::api_bindium::ureq::http::Response;
+use musicbrainz_rs::api_bindium::ureq::http::header::CONTENT_TYPE;
+use musicbrainz_rs::client::transport::ResponseBody;
+use musicbrainz_rs::client::transport::Transport;
+use musicbrainz_rs::client::transport::TransportError;
+use musicbrainz_rs::entity::artist::Artist;
+use musicbrainz_rs::entity::label::Label;
+use musicbrainz_rs::entity::recording::Recording;
+use musicbrainz_rs::entity::release::Release;
+use musicbrainz_rs::entity::release_group::ReleaseGroup;
+use musicbrainz_rs::entity::work::Work;
+
+/// Answers the requests with the fixture of the format they ask for
+struct Fixture(&'static str);
+
+impl Transport for Fixture {
+    fn send(&self, request: Request<()>) -> Result<Response<ResponseBody>, TransportError> {
+        let format = if request
+            .uri()
+            .query()
+            .unwrap_or_default()
+            .contains("fmt=xml")
+        {
+            "xml"
+        } else {
+            "json"
+        };
+
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/endpoints/xml/fixtures")
+            .join(format!("{}.{format}", self.0));
+
+        Ok(Response::builder()
+            .header(CONTENT_TYPE, format!("application/{format}"))
+            .body(std::fs::read(path)?.into())?)
+    }
+}
+
+/// Execute the query in both formats on the fixture, and check that the results are the same
+fn assert_same_in_xml<T>(
+    fixture: &'static str,
+    query: impl Fn(&MusicBrainzClient) -> Result<T, ApiEndpointError>,
+) where
+    T: PartialEq + Debug,
+{
+    let client = |format| {
+        MusicBrainzClient::builder()
+            .response_format(format)
+            .transport(Arc::new(Fixture(fixture)))
+            .build()
+    };
+
+    let from_json = query(&client(ResponseFormat::Json)).unwrap();
+    let from_xml = query(&client(ResponseFormat::Xml)).unwrap();
+
+    assert_eq!(from_json, from_xml);
+}
+
+#[test]
+pub fn artist_lookup() {
+    assert_same_in_xml("artist_lookup", |client| {
+        Artist::fetch()
+            .id("5b11f4ce-a62d-471e-81fc-a69a8278c7da")
+            .with_aliases()
+            .with_tags()
+            .with_rating()
+            .with_genres()
+            .with_artist_relations()
+            .with_url_relations()
+            .execute_with_client(client)
+    });
+}
+
+#[test]
+pub fn release_lookup() {
+    assert_same_in_xml("release_lookup", |client| {
+        Release::fetch()
+            .id("4642ee19-7790-3c8d-ab5e-d133de942db6")
+            .with_artist_credits()
+            .with_labels()
+            .with_recordings()
+            .with_release_groups()
+            .with_media()
+            .with_discids()
+            .with_isrcs()
+            .execute_with_client(client)
+    });
+}
+
+#[test]
+pub fn recording_lookup() {
+    assert_same_in_xml("recording_lookup", |client| {
+        Recording::fetch()
+            .id("de552ba4-572c-4c59-b2a9-0508619696ac")
+            .with_artists()
+            .with_releases()
+            .with_isrcs()
+            .with_ratings()
+            .with_work_relations()
+            .execute_with_client(client)
+    });
+}
+
+#[test]
+pub fn release_group_lookup() {
+    assert_same_in_xml("release_group_lookup", |client| {
+        ReleaseGroup::fetch()
+            .id("2a0981fb-9593-3019-864b-ce934d97a16e")
+            .with_artists()
+            .with_releases()
+            .with_tags()
+            .execute_with_client(client)
+    });
+}
+
+#[test]
+pub fn label_lookup() {
+    assert_same_in_xml("label_lookup", |client| {
+        Label::fetch()
+            .id("dc940013-b8a8-4362-a465-291026c04b42")
+            .with_aliases()
+            .with_url_relations()
+            .execute_with_client(client)
+    });
+}
+
+#[test]
+pub fn work_lookup() {
+    assert_same_in_xml("work_lookup", |client| {
+        Work::fetch()
+            .id("22457dc0-ecbf-38f5-9056-11c858530a50")
+            .with_aliases()
+            .with_artist_relations()
+            .with_recording_relations()
+            .execute_with_client(client)
+    });
+}
+
+#[test]
+pub fn release_browse() {
+    assert_same_in_xml("release_browse", |client| {
+        Release::browse()
+            .by_label("dc940013-b8a8-4362-a465-291026c04b42")
+            .execute_with_client(client)
+    });
+}
+
+#[test]
+pub fn artist_search() {
+    assert_same_in_xml("artist_search", |client| {
+        Artist::search("artist:nirvana AND country:US".to_string()).execute_with_client(client)
+    });
+}